@@ -0,0 +1,464 @@
+#![deny(missing_docs)]
+/*!
+# Iceberg-rs derive
+
+Provides `#[derive(IcebergSchema)]`, which generates an Iceberg
+[SchemaV2](https://docs.rs/iceberg-rs/latest/iceberg_rs/model/schema/struct.SchemaV2.html)
+from a Rust struct. Use it through the `derive` feature of `iceberg-rs`.
+
+```rust,ignore
+use iceberg_rs::IcebergSchema;
+
+#[derive(IcebergSchema)]
+#[iceberg(schema_id = 1)]
+struct Event {
+    #[iceberg(id = 1, identifier)]
+    id: i64,
+    #[iceberg(id = 2, doc = "Time of the event", type = "timestamptz")]
+    ts: i64,
+    #[iceberg(id = 3, element_id = 4)]
+    tags: Vec<String>,
+    #[iceberg(id = 5, key_id = 6, value_id = 7)]
+    properties: Option<HashMap<String, String>>,
+}
+```
+
+Every field needs an `id`. List fields (`Vec<T>`) also need an `element_id` and map
+fields (`HashMap<K, V>` or `BTreeMap<K, V>`) a `key_id` and `value_id`. `Option<T>`
+makes a field, list element or map value optional and `Vec<u8>` maps to `binary`.
+Other field types must implement `IcebergType`, which includes structs deriving
+`IcebergSchema`. The `type` attribute overrides the type of a primitive field.
+
+Type names, duplicate ids and optional identifier fields are checked when the macro
+expands, so mistakes in the attributes fail to compile. Ids of nested structs are only
+known at run time, so `iceberg_schema()` panics if they collide with other ids.
+*/
+use std::collections::HashSet;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, LitInt, LitStr, PathArguments,
+    Type,
+};
+
+/// Derive `IcebergType` and `IcebergSchema` for a struct with named fields.
+#[proc_macro_derive(IcebergSchema, attributes(iceberg))]
+pub fn derive_iceberg_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct FieldAttributes {
+    id: Option<LitInt>,
+    element_id: Option<LitInt>,
+    key_id: Option<LitInt>,
+    value_id: Option<LitInt>,
+    name: Option<LitStr>,
+    doc: Option<LitStr>,
+    type_override: Option<LitStr>,
+    identifier: Option<Span>,
+}
+
+fn parse_field_attributes(field: &syn::Field) -> syn::Result<FieldAttributes> {
+    let mut attributes = FieldAttributes::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("iceberg")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                attributes.id = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("element_id") {
+                attributes.element_id = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("key_id") {
+                attributes.key_id = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("value_id") {
+                attributes.value_id = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("name") {
+                attributes.name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("doc") {
+                attributes.doc = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("type") {
+                attributes.type_override = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("identifier") {
+                attributes.identifier = Some(meta.path.span());
+            } else {
+                return Err(meta.error("unknown iceberg attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attributes)
+}
+
+fn parse_schema_id(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let mut schema_id = quote!(0);
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("iceberg")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("schema_id") {
+                let id: LitInt = meta.value()?.parse()?;
+                schema_id = quote!(#id);
+                Ok(())
+            } else {
+                Err(meta.error("unknown iceberg attribute"))
+            }
+        })?;
+    }
+    Ok(schema_id)
+}
+
+/// Returns the type argument of `ty` if it is a path ending in `wrapper`, such as `Option<T>`.
+fn type_arguments<'a>(ty: &'a Type, wrapper: &str) -> Option<Vec<&'a Type>> {
+    let path = match ty {
+        Type::Path(p) if p.qself.is_none() => &p.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => Some(
+            args.args
+                .iter()
+                .filter_map(|a| match a {
+                    GenericArgument::Type(t) => Some(t),
+                    _ => None,
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn is_u8(ty: &Type) -> bool {
+    matches!(ty, Type::Path(p) if p.path.is_ident("u8"))
+}
+
+fn is_container(ty: &Type) -> bool {
+    type_arguments(ty, "Vec").is_some()
+        || type_arguments(ty, "HashMap").is_some()
+        || type_arguments(ty, "BTreeMap").is_some()
+}
+
+/// Strips an `Option` from `ty`, returning the inner type and whether it was optional.
+fn unwrap_option(ty: &Type) -> (&Type, bool) {
+    match type_arguments(ty, "Option").as_deref() {
+        Some([inner]) => (inner, true),
+        _ => (ty, false),
+    }
+}
+
+/// The expression producing the `AllType` of a non-container type.
+fn leaf_type(ty: &Type, span: Span) -> syn::Result<TokenStream2> {
+    if is_container(ty) && !matches!(type_arguments(ty, "Vec").as_deref(), Some([t]) if is_u8(t)) {
+        return Err(syn::Error::new(
+            span,
+            "nested lists and maps are not supported by #[derive(IcebergSchema)]",
+        ));
+    }
+    if matches!(type_arguments(ty, "Vec").as_deref(), Some([t]) if is_u8(t)) {
+        return Ok(quote!(::iceberg_rs::model::schema::AllType::Primitive(
+            ::iceberg_rs::model::schema::PrimitiveType::Binary
+        )));
+    }
+    Ok(quote!(<#ty as ::iceberg_rs::model::schema::IcebergType>::iceberg_type()))
+}
+
+fn required_id(id: &Option<LitInt>, name: &str, span: Span) -> syn::Result<LitInt> {
+    id.clone()
+        .ok_or_else(|| syn::Error::new(span, format!("missing #[iceberg({name} = ...)] attribute")))
+}
+
+/// The expression producing the `PrimitiveType` named by a `type` attribute, following the
+/// type names of the schema JSON.
+fn primitive_type(lit: &LitStr) -> syn::Result<TokenStream2> {
+    let name = lit.value();
+    let invalid = |reason: &str| syn::Error::new(lit.span(), format!("{reason}: {name}"));
+    let simple = match name.as_str() {
+        "boolean" => Some(quote!(Boolean)),
+        "int" => Some(quote!(Int)),
+        "long" => Some(quote!(Long)),
+        "float" => Some(quote!(Float)),
+        "double" => Some(quote!(Double)),
+        "date" => Some(quote!(Date)),
+        "time" => Some(quote!(Time)),
+        "timestamp" => Some(quote!(Timestamp)),
+        "timestamptz" | "timestampz" => Some(quote!(Timestampz)),
+        "timestamp_ns" => Some(quote!(TimestampNs)),
+        "timestamptz_ns" => Some(quote!(TimestampzNs)),
+        "string" => Some(quote!(String)),
+        "uuid" => Some(quote!(Uuid)),
+        "binary" => Some(quote!(Binary)),
+        "unknown" => Some(quote!(Unknown)),
+        "variant" => Some(quote!(Variant)),
+        _ => None,
+    };
+    let primitive = if let Some(simple) = simple {
+        simple
+    } else if let Some(args) = name
+        .strip_prefix("decimal(")
+        .and_then(|s| s.strip_suffix(')'))
+    {
+        let (precision, scale) = args
+            .split_once(',')
+            .filter(|(p, s)| is_digits(p) && is_digits(s))
+            .ok_or_else(|| invalid("invalid decimal type"))?;
+        let precision: i32 = precision
+            .parse()
+            .map_err(|_| invalid("invalid decimal precision"))?;
+        let scale: u8 = scale
+            .parse()
+            .map_err(|_| invalid("invalid decimal scale"))?;
+        if !(1..=38).contains(&precision) || i32::from(scale) > precision {
+            return Err(invalid(
+                "decimal precision must be between 1 and 38 and at least the scale",
+            ));
+        }
+        quote!(Decimal {
+            precision: #precision,
+            scale: #scale,
+        })
+    } else if let Some(length) = name
+        .strip_prefix("fixed[")
+        .and_then(|s| s.strip_suffix(']'))
+    {
+        let length: u64 = length
+            .parse()
+            .ok()
+            .filter(|l| is_digits(length) && *l > 0)
+            .ok_or_else(|| invalid("fixed length must be a positive integer"))?;
+        quote!(Fixed(#length))
+    } else if let Some(params) = geo_params(&name, "geometry") {
+        let crs = match params.as_slice() {
+            [] => quote!(::iceberg_rs::model::schema::DEFAULT_CRS.to_string()),
+            [crs] => quote!(#crs.to_string()),
+            _ => return Err(invalid("invalid geometry type")),
+        };
+        quote!(Geometry { crs: #crs })
+    } else if let Some(params) = geo_params(&name, "geography") {
+        let (crs, algorithm) = match params.as_slice() {
+            [] => (
+                quote!(::iceberg_rs::model::schema::DEFAULT_CRS.to_string()),
+                "spherical",
+            ),
+            [crs] => (quote!(#crs.to_string()), "spherical"),
+            [crs, algorithm] => (quote!(#crs.to_string()), *algorithm),
+            _ => return Err(invalid("invalid geography type")),
+        };
+        let algorithm = match algorithm {
+            "spherical" => quote!(Spherical),
+            "vincenty" => quote!(Vincenty),
+            "thomas" => quote!(Thomas),
+            "andoyer" => quote!(Andoyer),
+            "karney" => quote!(Karney),
+            _ => return Err(invalid("unknown edge algorithm")),
+        };
+        quote!(Geography {
+            crs: #crs,
+            algorithm: ::iceberg_rs::model::schema::EdgeAlgorithm::#algorithm,
+        })
+    } else {
+        return Err(invalid("unknown iceberg type"));
+    };
+    Ok(quote!(::iceberg_rs::model::schema::PrimitiveType::#primitive))
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The trimmed parameters of a geospatial type such as `geography(OGC:CRS84, karney)`, or
+/// `None` if `name` is not the geospatial type `prefix`.
+fn geo_params<'a>(name: &'a str, prefix: &str) -> Option<Vec<&'a str>> {
+    let rest = name.strip_prefix(prefix)?;
+    if rest.is_empty() {
+        return Some(vec![]);
+    }
+    let params: Vec<&str> = rest
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .split(',')
+        .map(str::trim)
+        .collect();
+    params
+        .iter()
+        .all(|p| !p.is_empty() && !p.contains(['(', ')']))
+        .then_some(params)
+}
+
+fn expand_field(field: &syn::Field) -> syn::Result<(TokenStream2, Option<LitInt>)> {
+    let ident = field.ident.as_ref().expect("named field");
+    let span = ident.span();
+    let attributes = parse_field_attributes(field)?;
+    let id = required_id(&attributes.id, "id", span)?;
+    let name = attributes
+        .name
+        .clone()
+        .unwrap_or_else(|| LitStr::new(&ident.to_string(), span));
+    let doc = match &attributes.doc {
+        Some(doc) => quote!(Some(#doc.to_string())),
+        None => quote!(None),
+    };
+    let (ty, optional) = unwrap_option(&field.ty);
+    let required = !optional;
+    if let (Some(identifier), true) = (attributes.identifier, optional) {
+        return Err(syn::Error::new(
+            identifier,
+            "identifier fields must be required, not Option",
+        ));
+    }
+
+    let field_type = if let Some(type_override) = &attributes.type_override {
+        if is_container(ty)
+            && !matches!(type_arguments(ty, "Vec").as_deref(), Some([t]) if is_u8(t))
+        {
+            return Err(syn::Error::new(
+                type_override.span(),
+                "the type of list and map fields cannot be overridden",
+            ));
+        }
+        let primitive = primitive_type(type_override)?;
+        quote!(::iceberg_rs::model::schema::AllType::Primitive(#primitive))
+    } else if let Some([element]) = type_arguments(ty, "Vec")
+        .as_deref()
+        .filter(|args| !matches!(args, [t] if is_u8(t)))
+    {
+        let element_id = required_id(&attributes.element_id, "element_id", span)?;
+        let (element, element_optional) = unwrap_option(element);
+        let element_required = !element_optional;
+        let element_type = leaf_type(element, span)?;
+        quote!(::iceberg_rs::model::schema::AllType::List(
+            ::iceberg_rs::model::schema::List {
+                element_id: #element_id,
+                element_required: #element_required,
+                element: Box::new(#element_type),
+            }
+        ))
+    } else if let Some([key, value]) = type_arguments(ty, "HashMap")
+        .or_else(|| type_arguments(ty, "BTreeMap"))
+        .as_deref()
+    {
+        let key_id = required_id(&attributes.key_id, "key_id", span)?;
+        let value_id = required_id(&attributes.value_id, "value_id", span)?;
+        let key_type = leaf_type(key, span)?;
+        let (value, value_optional) = unwrap_option(value);
+        let value_required = !value_optional;
+        let value_type = leaf_type(value, span)?;
+        quote!(::iceberg_rs::model::schema::AllType::Map(
+            ::iceberg_rs::model::schema::Map {
+                key_id: #key_id,
+                key: Box::new(#key_type),
+                value_id: #value_id,
+                value_required: #value_required,
+                value: Box::new(#value_type),
+            }
+        ))
+    } else {
+        leaf_type(ty, span)?
+    };
+
+    let struct_field = quote!(::iceberg_rs::model::schema::StructField {
+        id: #id,
+        name: #name.to_string(),
+        required: #required,
+        field_type: #field_type,
+        doc: #doc,
+    });
+    Ok((struct_field, attributes.identifier.map(|_| id)))
+}
+
+/// Returns an error at the second use of any field, element, key or value id of the struct.
+fn check_unique_ids(fields: &[FieldAttributes]) -> syn::Result<()> {
+    let mut seen = HashSet::new();
+    let ids = fields.iter().flat_map(|a| {
+        [&a.id, &a.element_id, &a.key_id, &a.value_id]
+            .into_iter()
+            .flatten()
+    });
+    for id in ids {
+        let value: i64 = id.base10_parse()?;
+        if !seen.insert(value) {
+            return Err(syn::Error::new(
+                id.span(),
+                format!("duplicate iceberg field id {value}"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "#[derive(IcebergSchema)] requires named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "#[derive(IcebergSchema)] is only supported on structs",
+            ))
+        }
+    };
+    let schema_id = parse_schema_id(&input)?;
+    check_unique_ids(
+        &fields
+            .iter()
+            .map(parse_field_attributes)
+            .collect::<syn::Result<Vec<_>>>()?,
+    )?;
+    let mut struct_fields = vec![];
+    let mut identifiers = vec![];
+    for field in fields {
+        let (struct_field, identifier) = expand_field(field)?;
+        struct_fields.push(struct_field);
+        identifiers.extend(identifier);
+    }
+    let identifier_field_ids = if identifiers.is_empty() {
+        quote!(None)
+    } else {
+        quote!(Some(vec![#(#identifiers),*]))
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::iceberg_rs::model::schema::IcebergType for #ident #ty_generics #where_clause {
+            fn iceberg_type() -> ::iceberg_rs::model::schema::AllType {
+                ::iceberg_rs::model::schema::AllType::Struct(
+                    ::iceberg_rs::model::schema::Struct {
+                        fields: vec![#(#struct_fields),*],
+                    }
+                )
+            }
+        }
+
+        impl #impl_generics ::iceberg_rs::model::schema::IcebergSchema for #ident #ty_generics #where_clause {
+            fn iceberg_schema() -> ::iceberg_rs::model::schema::SchemaV2 {
+                let struct_fields = match <Self as ::iceberg_rs::model::schema::IcebergType>::iceberg_type() {
+                    ::iceberg_rs::model::schema::AllType::Struct(s) => s,
+                    _ => unreachable!(),
+                };
+                let schema = ::iceberg_rs::model::schema::SchemaV2 {
+                    schema_id: #schema_id,
+                    identifier_field_ids: #identifier_field_ids,
+                    name_mapping: None,
+                    struct_fields,
+                };
+                if let Err(err) = schema.check_unique_ids() {
+                    panic!("#[derive(IcebergSchema)] on {}: {}", stringify!(#ident), err);
+                }
+                schema
+            }
+        }
+    })
+}
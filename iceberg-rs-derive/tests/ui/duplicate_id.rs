@@ -0,0 +1,11 @@
+use iceberg_rs_derive::IcebergSchema;
+
+#[derive(IcebergSchema)]
+struct Event {
+    #[iceberg(id = 1)]
+    id: i64,
+    #[iceberg(id = 2, element_id = 1)]
+    tags: Vec<String>,
+}
+
+fn main() {}
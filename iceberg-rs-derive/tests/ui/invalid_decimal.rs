@@ -0,0 +1,9 @@
+use iceberg_rs_derive::IcebergSchema;
+
+#[derive(IcebergSchema)]
+struct Event {
+    #[iceberg(id = 1, type = "decimal(40,2)")]
+    price: i64,
+}
+
+fn main() {}
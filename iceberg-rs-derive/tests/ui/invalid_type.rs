@@ -0,0 +1,9 @@
+use iceberg_rs_derive::IcebergSchema;
+
+#[derive(IcebergSchema)]
+struct Event {
+    #[iceberg(id = 1, type = "timestamp_tz")]
+    ts: i64,
+}
+
+fn main() {}
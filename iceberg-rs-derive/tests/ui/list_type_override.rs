@@ -0,0 +1,9 @@
+use iceberg_rs_derive::IcebergSchema;
+
+#[derive(IcebergSchema)]
+struct Event {
+    #[iceberg(id = 1, element_id = 2, type = "string")]
+    tags: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use std::collections::HashMap;
+
+use iceberg_rs_derive::IcebergSchema;
+
+#[derive(IcebergSchema)]
+struct Event {
+    #[iceberg(id = 1, key_id = 2, value_id = 3, type = "string")]
+    properties: Option<HashMap<String, String>>,
+}
+
+fn main() {}
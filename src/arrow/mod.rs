@@ -0,0 +1,239 @@
+/*!
+Support for [Apache Arrow](https://arrow.apache.org/) record batches, behind the `arrow` feature.
+
+Columns of a batch are matched to the fields of an Iceberg schema by the `PARQUET:field_id`
+metadata of the Arrow fields, as set by readers of Iceberg data files. Arrow fields without a
+field id are matched by name.
+
+| Iceberg type | Arrow type |
+|---|---|
+| `boolean` | `Boolean` |
+| `int` / `long` | `Int32` / `Int64` |
+| `float` / `double` | `Float32` / `Float64` |
+| `decimal(P, S)` | `Decimal128(P, S)` |
+| `date` | `Date32` |
+| `time` | `Time64(Microsecond)` |
+| `timestamp` / `timestamptz` | `Timestamp(Microsecond, _)` |
+| `timestamp_ns` / `timestamptz_ns` | `Timestamp(Nanosecond, _)` |
+| `string` | `Utf8`, `LargeUtf8` or `Utf8View` |
+| `uuid` / `fixed(L)` | `FixedSizeBinary(16)` / `FixedSizeBinary(L)` |
+| `binary` | `Binary`, `LargeBinary` or `BinaryView` |
+*/
+use arrow_array::{
+    cast::AsArray,
+    make_array,
+    types::{
+        Date32Type, Decimal128Type, Float32Type, Float64Type, Int32Type, Int64Type,
+        Time64MicrosecondType, TimestampMicrosecondType, TimestampNanosecondType,
+    },
+    Array, ArrayRef, RecordBatch,
+};
+use arrow_buffer::NullBuffer;
+use arrow_schema::{ArrowError, DataType, Fields};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::model::accessor::Accessor;
+use crate::model::schema::PrimitiveType;
+use crate::model::values::Value;
+
+pub mod partition;
+pub mod sort;
+pub mod transform;
+
+/// The metadata key of the Iceberg field id of an Arrow field.
+pub const PARQUET_FIELD_ID_META_KEY: &str = "PARQUET:field_id";
+
+pub(crate) fn arrow_error(error: ArrowError) -> Error {
+    Error::Invalid(format!("arrow error: {error}"))
+}
+
+/// A column of a batch with the nulls of the structs that contain it.
+pub(crate) struct SourceColumn {
+    pub(crate) array: ArrayRef,
+    pub(crate) nulls: Option<NullBuffer>,
+}
+
+impl SourceColumn {
+    /// The column as an array with the nulls of the structs that contain it.
+    pub(crate) fn into_array(self) -> Result<ArrayRef> {
+        if self.nulls == self.array.logical_nulls() {
+            return Ok(self.array);
+        }
+        let data = self
+            .array
+            .to_data()
+            .into_builder()
+            .nulls(self.nulls)
+            .build()
+            .map_err(arrow_error)?;
+        Ok(make_array(data))
+    }
+}
+
+fn find_field(fields: &Fields, id: i32, name: &str) -> Option<usize> {
+    let field_id = |i: usize| {
+        fields[i]
+            .metadata()
+            .get(PARQUET_FIELD_ID_META_KEY)
+            .map(|v| v.parse::<i32>().ok())
+    };
+    (0..fields.len())
+        .find(|i| field_id(*i) == Some(Some(id)))
+        .or_else(|| (0..fields.len()).find(|i| field_id(*i).is_none() && fields[*i].name() == name))
+}
+
+/// The column of `batch` read by `accessor`.
+pub(crate) fn source_column(batch: &RecordBatch, accessor: &Accessor) -> Result<SourceColumn> {
+    let ids = accessor.field_ids();
+    let names = accessor.names();
+    let mut fields = batch.schema().fields().clone();
+    let mut columns = batch.columns().to_vec();
+    let mut nulls: Option<NullBuffer> = None;
+    for (level, (id, name)) in ids.iter().zip(&names).enumerate() {
+        let index = find_field(&fields, *id, name).ok_or_else(|| {
+            Error::NotFound(format!(
+                "field {id} ({}) is not in the batch",
+                names.join(".")
+            ))
+        })?;
+        let array = columns[index].clone();
+        nulls = NullBuffer::union(nulls.as_ref(), array.logical_nulls().as_ref());
+        if level + 1 == ids.len() {
+            return Ok(SourceColumn { array, nulls });
+        }
+        let struct_array = array
+            .as_struct_opt()
+            .ok_or_else(|| Error::Invalid(format!("column {name} of the batch is not a struct")))?;
+        fields = struct_array.fields().clone();
+        columns = struct_array.columns().to_vec();
+    }
+    Err(Error::Invalid("accessor without fields".to_string()))
+}
+
+/// The value of `column` in `row` as a value of `data_type`.
+pub(crate) fn value_at(
+    column: &SourceColumn,
+    row: usize,
+    data_type: &PrimitiveType,
+) -> Result<Option<Value>> {
+    if column.nulls.as_ref().is_some_and(|n| n.is_null(row)) {
+        return Ok(None);
+    }
+    let array = &column.array;
+    let mismatch = || {
+        Error::Invalid(format!(
+            "cannot read {:?} column as {data_type:?}",
+            array.data_type()
+        ))
+    };
+    let value = match data_type {
+        PrimitiveType::Boolean => {
+            Value::Boolean(array.as_boolean_opt().ok_or_else(mismatch)?.value(row))
+        }
+        PrimitiveType::Int => Value::Int(
+            array
+                .as_primitive_opt::<Int32Type>()
+                .ok_or_else(mismatch)?
+                .value(row),
+        ),
+        PrimitiveType::Long => Value::Long(
+            array
+                .as_primitive_opt::<Int64Type>()
+                .ok_or_else(mismatch)?
+                .value(row),
+        ),
+        PrimitiveType::Float => Value::Float(
+            array
+                .as_primitive_opt::<Float32Type>()
+                .ok_or_else(mismatch)?
+                .value(row),
+        ),
+        PrimitiveType::Double => Value::Double(
+            array
+                .as_primitive_opt::<Float64Type>()
+                .ok_or_else(mismatch)?
+                .value(row),
+        ),
+        PrimitiveType::Decimal { .. } => Value::Decimal(
+            array
+                .as_primitive_opt::<Decimal128Type>()
+                .ok_or_else(mismatch)?
+                .value(row),
+        ),
+        PrimitiveType::Date => Value::Date(
+            array
+                .as_primitive_opt::<Date32Type>()
+                .ok_or_else(mismatch)?
+                .value(row),
+        ),
+        PrimitiveType::Time => Value::Time(
+            array
+                .as_primitive_opt::<Time64MicrosecondType>()
+                .ok_or_else(mismatch)?
+                .value(row),
+        ),
+        PrimitiveType::Timestamp | PrimitiveType::Timestampz => {
+            let v = array
+                .as_primitive_opt::<TimestampMicrosecondType>()
+                .ok_or_else(mismatch)?
+                .value(row);
+            if *data_type == PrimitiveType::Timestamp {
+                Value::Timestamp(v)
+            } else {
+                Value::Timestampz(v)
+            }
+        }
+        PrimitiveType::TimestampNs | PrimitiveType::TimestampzNs => {
+            let v = array
+                .as_primitive_opt::<TimestampNanosecondType>()
+                .ok_or_else(mismatch)?
+                .value(row);
+            if *data_type == PrimitiveType::TimestampNs {
+                Value::TimestampNs(v)
+            } else {
+                Value::TimestampzNs(v)
+            }
+        }
+        PrimitiveType::String => Value::String(
+            match array.data_type() {
+                DataType::Utf8 => array.as_string::<i32>().value(row),
+                DataType::LargeUtf8 => array.as_string::<i64>().value(row),
+                DataType::Utf8View => array.as_string_view().value(row),
+                _ => return Err(mismatch()),
+            }
+            .to_string(),
+        ),
+        PrimitiveType::Uuid => Value::Uuid(
+            Uuid::from_slice(
+                array
+                    .as_fixed_size_binary_opt()
+                    .ok_or_else(mismatch)?
+                    .value(row),
+            )
+            .map_err(|_| mismatch())?,
+        ),
+        PrimitiveType::Fixed(_) => Value::Fixed(
+            array
+                .as_fixed_size_binary_opt()
+                .ok_or_else(mismatch)?
+                .value(row)
+                .to_vec(),
+        ),
+        PrimitiveType::Binary => Value::Binary(
+            match array.data_type() {
+                DataType::Binary => array.as_binary::<i32>().value(row),
+                DataType::LargeBinary => array.as_binary::<i64>().value(row),
+                DataType::BinaryView => array.as_binary_view().value(row),
+                _ => return Err(mismatch()),
+            }
+            .to_vec(),
+        ),
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "reading {data_type:?} values from arrow"
+            )))
+        }
+    };
+    Ok(Some(value))
+}
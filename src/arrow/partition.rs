@@ -0,0 +1,254 @@
+/*!
+Splitting record batches by partition, to route rows to per-partition writers.
+*/
+use std::collections::HashMap;
+
+use arrow_array::{new_null_array, Array, RecordBatch, UInt32Array};
+use arrow_schema::DataType;
+use arrow_select::take::take_record_batch;
+
+use crate::arrow::{arrow_error, source_column, value_at, SourceColumn};
+use crate::error::Result;
+use crate::model::partition::{BoundPartitionSpec, PartitionKey};
+
+/// The partition keys of the rows of `batch`, whose columns are the fields of the schema
+/// `spec` is bound to.
+pub fn partition_keys(spec: &BoundPartitionSpec, batch: &RecordBatch) -> Result<Vec<PartitionKey>> {
+    let columns = spec
+        .fields()
+        .iter()
+        .map(|f| {
+            let array = match f.accessor() {
+                Some(accessor) => {
+                    let source = source_column(batch, accessor)?.into_array()?;
+                    f.field().transform.apply_array(source.as_ref())?
+                }
+                // Void fields whose source is not in the schema are always null.
+                None => new_null_array(&DataType::Null, batch.num_rows()),
+            };
+            Ok(SourceColumn {
+                nulls: array.logical_nulls(),
+                array,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    (0..batch.num_rows())
+        .map(|row| {
+            spec.fields()
+                .iter()
+                .zip(&columns)
+                .map(|(f, column)| value_at(column, row, f.result_type()))
+                .collect::<Result<Vec<_>>>()
+                .map(PartitionKey)
+        })
+        .collect()
+}
+
+/// Split `batch` into one batch per partition, in the order in which the partitions first
+/// appear. Rows keep their relative order within each partition.
+pub fn split_record_batch(
+    spec: &BoundPartitionSpec,
+    batch: &RecordBatch,
+) -> Result<Vec<(PartitionKey, RecordBatch)>> {
+    if spec.is_unpartitioned() {
+        return Ok(vec![(PartitionKey(vec![]), batch.clone())]);
+    }
+    let mut groups: Vec<(PartitionKey, Vec<u32>)> = vec![];
+    let mut positions: HashMap<PartitionKey, usize> = HashMap::new();
+    for (row, key) in partition_keys(spec, batch)?.into_iter().enumerate() {
+        let position = *positions.entry(key.clone()).or_insert_with(|| {
+            groups.push((key, vec![]));
+            groups.len() - 1
+        });
+        groups[position].1.push(row as u32);
+    }
+    groups
+        .into_iter()
+        .map(|(key, rows)| {
+            let batch = take_record_batch(batch, &UInt32Array::from(rows)).map_err(arrow_error)?;
+            Ok((key, batch))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use arrow_array::{
+        cast::AsArray, types::Int64Type, Array, ArrayRef, Int64Array, StringArray, StructArray,
+        TimestampMicrosecondArray,
+    };
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+
+    use super::*;
+    use crate::arrow::PARQUET_FIELD_ID_META_KEY;
+    use crate::model::partition::PartitionSpec;
+    use crate::model::schema::SchemaV2;
+    use crate::model::values::Value;
+
+    fn schema() -> SchemaV2 {
+        serde_json::from_str(
+            r#"
+            {
+                "schema-id": 0,
+                "type": "struct",
+                "fields": [
+                    { "id": 1, "name": "id", "required": true, "field_type": "long" },
+                    { "id": 2, "name": "ts", "required": true, "field_type": "timestamptz" },
+                    {
+                        "id": 3,
+                        "name": "location",
+                        "required": false,
+                        "field_type": {
+                            "type": "struct",
+                            "fields": [
+                                { "id": 4, "name": "city", "required": false, "field_type": "string" }
+                            ]
+                        }
+                    }
+                ]
+            }
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn with_id(field: Field, id: i32) -> Field {
+        field.with_metadata(HashMap::from([(
+            PARQUET_FIELD_ID_META_KEY.to_string(),
+            id.to_string(),
+        )]))
+    }
+
+    fn batch() -> RecordBatch {
+        let day = 86_400_000_000;
+        let city = Arc::new(with_id(Field::new("city", DataType::Utf8, true), 4));
+        let location = StructArray::from(vec![(
+            city,
+            Arc::new(StringArray::from(vec![
+                Some("Paris"),
+                Some("Lyon"),
+                Some("Paris"),
+                None,
+            ])) as ArrayRef,
+        )]);
+        let schema = Schema::new(vec![
+            // The column names differ from the schema, so columns are matched by id.
+            with_id(Field::new("row_id", DataType::Int64, false), 1),
+            with_id(
+                Field::new(
+                    "event_ts",
+                    DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                    false,
+                ),
+                2,
+            ),
+            with_id(
+                Field::new("location", location.data_type().clone(), true),
+                3,
+            ),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+                Arc::new(
+                    TimestampMicrosecondArray::from(vec![0, day, 1, day + 1]).with_timezone("UTC"),
+                ),
+                Arc::new(location),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_split_record_batch() {
+        let spec = PartitionSpec::builder(&schema())
+            .day("ts")
+            .identity("location.city")
+            .build()
+            .unwrap()
+            .bind(&schema())
+            .unwrap();
+        let batches = split_record_batch(&spec, &batch()).unwrap();
+        let summary: Vec<_> = batches
+            .iter()
+            .map(|(key, batch)| {
+                (
+                    key.values().to_vec(),
+                    batch
+                        .column(0)
+                        .as_primitive::<Int64Type>()
+                        .values()
+                        .to_vec(),
+                )
+            })
+            .collect();
+        let city = |c: &str| Some(Value::String(c.to_string()));
+        assert_eq!(
+            vec![
+                (vec![Some(Value::Date(0)), city("Paris")], vec![1, 3]),
+                (vec![Some(Value::Date(1)), city("Lyon")], vec![2]),
+                (vec![Some(Value::Date(1)), None], vec![4]),
+            ],
+            summary
+        );
+    }
+
+    #[test]
+    fn test_void_without_source() {
+        let spec: PartitionSpec = serde_json::from_str(
+            r#"
+            {
+                "spec-id": 1,
+                "fields": [
+                    { "source-id": 2, "field-id": 1000, "name": "ts_day", "transform": "day" },
+                    { "source-id": 9, "field-id": 1001, "name": "dropped", "transform": "void" }
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+        let spec = spec.bind(&schema()).unwrap();
+        let keys: Vec<_> = split_record_batch(&spec, &batch())
+            .unwrap()
+            .into_iter()
+            .map(|(key, batch)| (key.values().to_vec(), batch.num_rows()))
+            .collect();
+        assert_eq!(
+            vec![
+                (vec![Some(Value::Date(0)), None], 2),
+                (vec![Some(Value::Date(1)), None], 2),
+            ],
+            keys
+        );
+    }
+
+    #[test]
+    fn test_match_by_name() {
+        let schema = Schema::new(vec![Field::new("id", DataType::Int64, false)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(Int64Array::from(vec![34, 34]))],
+        )
+        .unwrap();
+        let spec = PartitionSpec::builder(&self::schema())
+            .bucket("id", 16)
+            .build()
+            .unwrap()
+            .bind(&self::schema())
+            .unwrap();
+        let batches = split_record_batch(&spec, &batch).unwrap();
+        assert_eq!(1, batches.len());
+        assert_eq!(2, batches[0].1.num_rows());
+
+        let spec = PartitionSpec::builder(&self::schema())
+            .day("ts")
+            .build()
+            .unwrap()
+            .bind(&self::schema())
+            .unwrap();
+        assert!(split_record_batch(&spec, &batch).is_err());
+    }
+}
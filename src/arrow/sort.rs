@@ -0,0 +1,227 @@
+/*!
+Sorting record batches by a table [sort order](crate::model::sort), before writing data files.
+
+The sort key of each field of the order is its transform applied to the source column, see
+[Transform::apply_array](crate::model::partition::Transform::apply_array). Keys are compared as
+by [BoundSortOrder::compare_keys]: floating point `NaN`s sort after every other value.
+*/
+use std::{cmp::Reverse, collections::BinaryHeap, sync::Arc};
+
+use arrow_array::{
+    cast::AsArray,
+    types::{Float32Type, Float64Type},
+    Array, ArrayRef, RecordBatch, UInt32Array,
+};
+use arrow_row::{RowConverter, Rows, SortField as RowSortField};
+use arrow_schema::{DataType, SortOptions};
+use arrow_select::{
+    concat::concat_batches, interleave::interleave_record_batch, take::take_record_batch,
+};
+
+use crate::arrow::{arrow_error, source_column};
+use crate::error::{Error, Result};
+use crate::model::sort::{BoundSortOrder, NullOrder, SortDirection};
+
+/// The sort key columns of `batch`. The sign of `NaN`s is cleared, so that they sort after
+/// every other value as they do in Iceberg rather than by their sign.
+fn key_columns(order: &BoundSortOrder, batch: &RecordBatch) -> Result<Vec<ArrayRef>> {
+    order
+        .fields()
+        .iter()
+        .map(|f| {
+            let source = source_column(batch, f.accessor())?.into_array()?;
+            let key = f.field().transform.apply_array(source.as_ref())?;
+            let key: ArrayRef = match key.data_type() {
+                DataType::Float32 => Arc::new(
+                    key.as_primitive::<Float32Type>()
+                        .unary::<_, Float32Type>(|v| if v.is_nan() { v.abs() } else { v }),
+                ),
+                DataType::Float64 => Arc::new(
+                    key.as_primitive::<Float64Type>()
+                        .unary::<_, Float64Type>(|v| if v.is_nan() { v.abs() } else { v }),
+                ),
+                _ => key,
+            };
+            Ok(key)
+        })
+        .collect()
+}
+
+/// The row-encoded sort keys of `batches`, which must have the same schema.
+fn sort_keys(order: &BoundSortOrder, batches: &[&RecordBatch]) -> Result<Vec<Rows>> {
+    let columns = batches
+        .iter()
+        .map(|batch| key_columns(order, batch))
+        .collect::<Result<Vec<_>>>()?;
+    let fields = order
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let options = SortOptions {
+                descending: f.field().direction == SortDirection::Descending,
+                nulls_first: f.field().null_order == NullOrder::First,
+            };
+            let data_type = columns
+                .first()
+                .map_or(DataType::Null, |c| c[i].data_type().clone());
+            RowSortField::new_with_options(data_type, options)
+        })
+        .collect();
+    let converter = RowConverter::new(fields).map_err(arrow_error)?;
+    columns
+        .iter()
+        .map(|c| converter.convert_columns(c).map_err(arrow_error))
+        .collect()
+}
+
+/// Sort the rows of `batch`, whose columns are the fields of the schema `order` is bound to.
+/// The sort is stable, so rows with equal keys keep their order.
+pub fn sort_record_batch(order: &BoundSortOrder, batch: &RecordBatch) -> Result<RecordBatch> {
+    if order.is_unsorted() {
+        return Ok(batch.clone());
+    }
+    let keys = sort_keys(order, &[batch])?.remove(0);
+    let mut indices: Vec<u32> = (0..batch.num_rows() as u32).collect();
+    indices.sort_by(|a, b| keys.row(*a as usize).cmp(&keys.row(*b as usize)));
+    take_record_batch(batch, &UInt32Array::from(indices)).map_err(arrow_error)
+}
+
+/// Merge `batches`, each already sorted by `order`, into a single sorted batch. Rows with
+/// equal keys are taken from earlier batches first.
+pub fn merge_sorted_batches(
+    order: &BoundSortOrder,
+    batches: &[RecordBatch],
+) -> Result<RecordBatch> {
+    let batches: Vec<&RecordBatch> = batches.iter().collect();
+    let Some(first) = batches.first() else {
+        return Err(Error::Invalid("no batches to merge".to_string()));
+    };
+    if order.is_unsorted() {
+        return concat_batches(&first.schema(), batches.iter().copied()).map_err(arrow_error);
+    }
+    let keys = sort_keys(order, &batches)?;
+    let mut heap = BinaryHeap::new();
+    for (i, rows) in keys.iter().enumerate() {
+        if rows.num_rows() > 0 {
+            heap.push(Reverse((rows.row(0), i, 0)));
+        }
+    }
+    let mut indices = Vec::with_capacity(keys.iter().map(Rows::num_rows).sum());
+    while let Some(Reverse((_, i, row))) = heap.pop() {
+        indices.push((i, row));
+        if row + 1 < keys[i].num_rows() {
+            heap.push(Reverse((keys[i].row(row + 1), i, row + 1)));
+        }
+    }
+    interleave_record_batch(&batches, &indices).map_err(arrow_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{types::Int64Type, Float64Array, Int64Array, StringArray};
+    use arrow_schema::{Field, Schema};
+
+    use super::*;
+    use crate::model::partition::Transform;
+    use crate::model::schema::SchemaV2;
+    use crate::model::sort::SortOrder;
+
+    fn schema() -> SchemaV2 {
+        serde_json::from_str(
+            r#"
+            {
+                "schema-id": 0,
+                "type": "struct",
+                "fields": [
+                    { "id": 1, "name": "id", "required": true, "field_type": "long" },
+                    { "id": 2, "name": "name", "required": false, "field_type": "string" },
+                    { "id": 3, "name": "score", "required": false, "field_type": "double" }
+                ]
+            }
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn batch(ids: Vec<i64>, names: Vec<Option<&str>>, scores: Vec<Option<f64>>) -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("score", DataType::Float64, true),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int64Array::from(ids)),
+                Arc::new(StringArray::from(names)),
+                Arc::new(Float64Array::from(scores)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn ids(batch: &RecordBatch) -> Vec<i64> {
+        batch
+            .column(0)
+            .as_primitive::<Int64Type>()
+            .values()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_sort_record_batch() {
+        let order = SortOrder::builder(&schema())
+            .asc("name", Transform::Truncate(1))
+            .desc("score", Transform::Identity)
+            .build()
+            .unwrap()
+            .bind(&schema())
+            .unwrap();
+        let batch = batch(
+            vec![1, 2, 3, 4, 5, 6, 7],
+            vec![
+                Some("bob"),
+                Some("alice"),
+                None,
+                Some("bea"),
+                Some("amy"),
+                Some("ann"),
+                Some("ben"),
+            ],
+            vec![
+                Some(1.0),
+                Some(2.0),
+                Some(0.0),
+                Some(f64::NAN),
+                None,
+                Some(-f64::NAN),
+                Some(1.0),
+            ],
+        );
+        let sorted = sort_record_batch(&order, &batch).unwrap();
+        // Nulls first for ascending names; NaNs first and nulls last for descending scores.
+        assert_eq!(vec![3, 6, 2, 5, 4, 1, 7], ids(&sorted));
+    }
+
+    #[test]
+    fn test_merge_sorted_batches() {
+        let order = SortOrder::builder(&schema())
+            .asc("id", Transform::Identity)
+            .build()
+            .unwrap()
+            .bind(&schema())
+            .unwrap();
+        let batches = vec![
+            batch(vec![1, 4, 6], vec![Some("a"); 3], vec![None; 3]),
+            batch(vec![], vec![], vec![]),
+            batch(vec![2, 4, 5, 9], vec![Some("b"); 4], vec![None; 4]),
+        ];
+        let merged = merge_sorted_batches(&order, &batches).unwrap();
+        assert_eq!(vec![1, 2, 4, 4, 5, 6, 9], ids(&merged));
+        let names = merged.column(1).as_string::<i32>();
+        assert_eq!("a", names.value(2));
+        assert_eq!("b", names.value(3));
+        assert!(merge_sorted_batches(&order, &[]).is_err());
+    }
+}
@@ -0,0 +1,408 @@
+/*!
+Partition transforms applied to whole Arrow arrays.
+
+[Transform::apply_array] produces the same results as [Transform::apply] applied to every value
+of the array, without converting each value to a [Value](crate::model::values::Value).
+Transforms of dates and timestamps produce `Int32` arrays, except `day` which produces a `Date32`
+array. Bucket transforms produce `Int32` arrays. Identity, truncate and void keep the type of the
+array.
+*/
+use std::sync::Arc;
+
+use arrow_array::{
+    cast::AsArray,
+    make_array, new_null_array,
+    types::{
+        Date32Type, Decimal128Type, Int32Type, Int64Type, Time64MicrosecondType,
+        TimestampMicrosecondType, TimestampNanosecondType,
+    },
+    Array, ArrayRef, BinaryViewArray, Date32Array, GenericBinaryArray, GenericStringArray,
+    Int32Array, OffsetSizeTrait, StringViewArray,
+};
+use arrow_schema::{DataType, TimeUnit};
+
+use crate::error::{Error, Result};
+use crate::model::partition::Transform;
+use crate::model::transform::{
+    months_from_days, murmur3_32, years_from_days, MICROS_PER_DAY, MICROS_PER_HOUR, NANOS_PER_MICRO,
+};
+use crate::model::values::decimal_to_bytes;
+
+impl Transform {
+    /// Apply the transform to every value of `array`. Nulls transform to null.
+    pub fn apply_array(&self, array: &dyn Array) -> Result<ArrayRef> {
+        Ok(match self {
+            Transform::Void => new_null_array(array.data_type(), array.len()),
+            Transform::Identity => make_array(array.to_data()),
+            Transform::Bucket(n) => {
+                let n = *n;
+                if n == 0 {
+                    return Err(Error::Invalid("bucket count must be positive".to_string()));
+                }
+                let hashes = bucket_hashes(array)?;
+                Arc::new(hashes.unary::<_, Int32Type>(|h| ((h & i32::MAX) as u32 % n) as i32))
+            }
+            Transform::Truncate(width) => truncate_array(array, *width)?,
+            Transform::Year => {
+                Arc::new(days_array(array, self)?.try_unary::<_, Int32Type, _>(years_from_days)?)
+            }
+            Transform::Month => {
+                Arc::new(days_array(array, self)?.try_unary::<_, Int32Type, _>(months_from_days)?)
+            }
+            Transform::Day => Arc::new(days_array(array, self)?),
+            Transform::Hour => Arc::new(hours_array(array, self)?),
+        })
+    }
+}
+
+fn unsupported(transform: &Transform, array: &dyn Array) -> Error {
+    Error::Unsupported(format!(
+        "cannot apply {transform:?} to {:?} arrays",
+        array.data_type()
+    ))
+}
+
+fn hash_bytes<'a>(values: impl Iterator<Item = Option<&'a [u8]>>) -> Int32Array {
+    values.map(|v| v.map(murmur3_32)).collect()
+}
+
+/// The bucket hashes of the values of an array, as computed by
+/// [bucket_hash](crate::model::transform::bucket_hash).
+fn bucket_hashes(array: &dyn Array) -> Result<Int32Array> {
+    let hash_long = |v: i64| murmur3_32(&v.to_le_bytes());
+    Ok(match array.data_type() {
+        DataType::Int32 => array
+            .as_primitive::<Int32Type>()
+            .unary(|v| hash_long(i64::from(v))),
+        DataType::Date32 => array
+            .as_primitive::<Date32Type>()
+            .unary(|v| hash_long(i64::from(v))),
+        DataType::Int64 => array.as_primitive::<Int64Type>().unary(hash_long),
+        DataType::Time64(TimeUnit::Microsecond) => array
+            .as_primitive::<Time64MicrosecondType>()
+            .unary(hash_long),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => array
+            .as_primitive::<TimestampMicrosecondType>()
+            .unary(hash_long),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => array
+            .as_primitive::<TimestampNanosecondType>()
+            .unary(|v| hash_long(v.div_euclid(NANOS_PER_MICRO))),
+        DataType::Decimal128(_, _) => array
+            .as_primitive::<Decimal128Type>()
+            .unary(|v| murmur3_32(&decimal_to_bytes(v))),
+        DataType::Utf8 => hash_bytes(
+            array
+                .as_string::<i32>()
+                .iter()
+                .map(|v| v.map(str::as_bytes)),
+        ),
+        DataType::LargeUtf8 => hash_bytes(
+            array
+                .as_string::<i64>()
+                .iter()
+                .map(|v| v.map(str::as_bytes)),
+        ),
+        DataType::Utf8View => {
+            hash_bytes(array.as_string_view().iter().map(|v| v.map(str::as_bytes)))
+        }
+        DataType::Binary => hash_bytes(array.as_binary::<i32>().iter()),
+        DataType::LargeBinary => hash_bytes(array.as_binary::<i64>().iter()),
+        DataType::BinaryView => hash_bytes(array.as_binary_view().iter()),
+        DataType::FixedSizeBinary(_) => hash_bytes(array.as_fixed_size_binary().iter()),
+        _ => return Err(unsupported(&Transform::Bucket(1), array)),
+    })
+}
+
+/// The prefix of `s` with at most `width` code points.
+fn truncate_str(s: &str, width: usize) -> &str {
+    match s.char_indices().nth(width) {
+        Some((end, _)) => &s[..end],
+        None => s,
+    }
+}
+
+fn truncate_strings<O: OffsetSizeTrait>(
+    array: &GenericStringArray<O>,
+    width: usize,
+) -> GenericStringArray<O> {
+    array
+        .iter()
+        .map(|v| v.map(|s| truncate_str(s, width)))
+        .collect()
+}
+
+fn truncate_binaries<O: OffsetSizeTrait>(
+    array: &GenericBinaryArray<O>,
+    width: usize,
+) -> GenericBinaryArray<O> {
+    array
+        .iter()
+        .map(|v| v.map(|b| &b[..b.len().min(width)]))
+        .collect()
+}
+
+fn truncate_array(array: &dyn Array, width: u32) -> Result<ArrayRef> {
+    if width == 0 || width > i32::MAX as u32 {
+        return Err(Error::Invalid(format!(
+            "truncate width must be a positive int, not {width}"
+        )));
+    }
+    let chars = width as usize;
+    Ok(match array.data_type() {
+        DataType::Int32 => {
+            let w = width as i32;
+            Arc::new(
+                array
+                    .as_primitive::<Int32Type>()
+                    .unary::<_, Int32Type>(|v| v.wrapping_sub(v.rem_euclid(w))),
+            )
+        }
+        DataType::Int64 => {
+            let w = i64::from(width);
+            Arc::new(
+                array
+                    .as_primitive::<Int64Type>()
+                    .unary::<_, Int64Type>(|v| v.wrapping_sub(v.rem_euclid(w))),
+            )
+        }
+        DataType::Decimal128(_, _) => {
+            let w = i128::from(width);
+            Arc::new(
+                array
+                    .as_primitive::<Decimal128Type>()
+                    .unary::<_, Decimal128Type>(|v| v.wrapping_sub(v.rem_euclid(w)))
+                    .with_data_type(array.data_type().clone()),
+            )
+        }
+        DataType::Utf8 => Arc::new(truncate_strings(array.as_string::<i32>(), chars)),
+        DataType::LargeUtf8 => Arc::new(truncate_strings(array.as_string::<i64>(), chars)),
+        DataType::Utf8View => Arc::new(
+            array
+                .as_string_view()
+                .iter()
+                .map(|v| v.map(|s| truncate_str(s, chars)))
+                .collect::<StringViewArray>(),
+        ),
+        DataType::Binary => Arc::new(truncate_binaries(array.as_binary::<i32>(), chars)),
+        DataType::LargeBinary => Arc::new(truncate_binaries(array.as_binary::<i64>(), chars)),
+        DataType::BinaryView => Arc::new(
+            array
+                .as_binary_view()
+                .iter()
+                .map(|v| v.map(|b| &b[..b.len().min(chars)]))
+                .collect::<BinaryViewArray>(),
+        ),
+        _ => return Err(unsupported(&Transform::Truncate(width), array)),
+    })
+}
+
+fn to_i32(v: i64) -> Result<i32> {
+    i32::try_from(v).map_err(|_| Error::Invalid(format!("{v} is out of range")))
+}
+
+/// Days from 1970-01-01 of the values of a date or timestamp array.
+fn days_array(array: &dyn Array, transform: &Transform) -> Result<Date32Array> {
+    match array.data_type() {
+        DataType::Date32 => Ok(array.as_primitive::<Date32Type>().clone()),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => array
+            .as_primitive::<TimestampMicrosecondType>()
+            .try_unary(|v| to_i32(v.div_euclid(MICROS_PER_DAY))),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => array
+            .as_primitive::<TimestampNanosecondType>()
+            .try_unary(|v| to_i32(v.div_euclid(MICROS_PER_DAY * NANOS_PER_MICRO))),
+        _ => Err(unsupported(transform, array)),
+    }
+}
+
+/// Hours from 1970-01-01 00:00:00 of the values of a timestamp array.
+fn hours_array(array: &dyn Array, transform: &Transform) -> Result<Int32Array> {
+    match array.data_type() {
+        DataType::Timestamp(TimeUnit::Microsecond, _) => array
+            .as_primitive::<TimestampMicrosecondType>()
+            .try_unary(|v| to_i32(v.div_euclid(MICROS_PER_HOUR))),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => array
+            .as_primitive::<TimestampNanosecondType>()
+            .try_unary(|v| to_i32(v.div_euclid(MICROS_PER_HOUR * NANOS_PER_MICRO))),
+        _ => Err(unsupported(transform, array)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{
+        Date32Array, Decimal128Array, FixedSizeBinaryArray, Int64Array, LargeBinaryArray,
+        StringArray, Time64MicrosecondArray, TimestampMicrosecondArray, TimestampNanosecondArray,
+    };
+
+    use super::*;
+    use crate::arrow::{value_at, SourceColumn};
+    use crate::model::schema::PrimitiveType;
+
+    fn column(array: ArrayRef) -> SourceColumn {
+        SourceColumn {
+            nulls: array.logical_nulls(),
+            array,
+        }
+    }
+
+    fn arrays() -> Vec<(ArrayRef, PrimitiveType)> {
+        let day = MICROS_PER_DAY;
+        vec![
+            (
+                Arc::new(Int32Array::from(vec![
+                    Some(0),
+                    Some(-1),
+                    Some(34),
+                    None,
+                    Some(i32::MAX),
+                    Some(i32::MIN),
+                ])),
+                PrimitiveType::Int,
+            ),
+            (
+                Arc::new(Int64Array::from(vec![
+                    Some(34),
+                    None,
+                    Some(-7),
+                    Some(i64::MIN),
+                ])),
+                PrimitiveType::Long,
+            ),
+            (
+                Arc::new(
+                    Decimal128Array::from(vec![Some(1420), Some(-505), None])
+                        .with_precision_and_scale(9, 2)
+                        .unwrap(),
+                ),
+                PrimitiveType::Decimal {
+                    precision: 9,
+                    scale: 2,
+                },
+            ),
+            (
+                Arc::new(Date32Array::from(vec![
+                    Some(17486),
+                    Some(-1),
+                    None,
+                    Some(0),
+                ])),
+                PrimitiveType::Date,
+            ),
+            (
+                Arc::new(Time64MicrosecondArray::from(vec![
+                    Some(81_068_000_000),
+                    None,
+                ])),
+                PrimitiveType::Time,
+            ),
+            (
+                Arc::new(TimestampMicrosecondArray::from(vec![
+                    Some(1_510_871_468_000_000),
+                    Some(-1),
+                    None,
+                    Some(day * 40),
+                ])),
+                PrimitiveType::Timestamp,
+            ),
+            (
+                Arc::new(
+                    TimestampMicrosecondArray::from(vec![Some(-day - 1), Some(3_600_000_000)])
+                        .with_timezone("UTC"),
+                ),
+                PrimitiveType::Timestampz,
+            ),
+            (
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    Some(1_510_871_468_000_001_001),
+                    Some(-1),
+                    None,
+                ])),
+                PrimitiveType::TimestampNs,
+            ),
+            (
+                Arc::new(StringArray::from(vec![
+                    Some("iceberg"),
+                    Some("ʎǝɹ"),
+                    Some(""),
+                    None,
+                ])),
+                PrimitiveType::String,
+            ),
+            (
+                Arc::new(LargeBinaryArray::from(vec![
+                    Some(&[0u8, 1, 2, 3][..]),
+                    Some(&[][..]),
+                    None,
+                ])),
+                PrimitiveType::Binary,
+            ),
+            (
+                Arc::new(
+                    FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+                        vec![Some([7u8; 16]), None].into_iter(),
+                        16,
+                    )
+                    .unwrap(),
+                ),
+                PrimitiveType::Uuid,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_kernels_match_scalar() {
+        let transforms = [
+            Transform::Identity,
+            Transform::Void,
+            Transform::Bucket(16),
+            Transform::Truncate(3),
+            Transform::Year,
+            Transform::Month,
+            Transform::Day,
+            Transform::Hour,
+        ];
+        for (array, source) in arrays() {
+            let input = column(array.clone());
+            for transform in transforms.iter().filter(|t| t.can_transform(&source)) {
+                let result = column(transform.apply_array(array.as_ref()).unwrap());
+                let result_type = transform.result_type(&source).unwrap();
+                assert_eq!(array.len(), result.array.len());
+                for row in 0..array.len() {
+                    let value = value_at(&input, row, &source).unwrap();
+                    assert_eq!(
+                        transform.apply(value.as_ref()).unwrap(),
+                        value_at(&result, row, &result_type).unwrap(),
+                        "{transform:?} of {value:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_result_types() {
+        let ts: ArrayRef = Arc::new(TimestampMicrosecondArray::from(vec![0]).with_timezone("UTC"));
+        assert_eq!(
+            &DataType::Date32,
+            Transform::Day.apply_array(ts.as_ref()).unwrap().data_type()
+        );
+        assert_eq!(
+            &DataType::Int32,
+            Transform::Hour
+                .apply_array(ts.as_ref())
+                .unwrap()
+                .data_type()
+        );
+        assert_eq!(
+            ts.data_type(),
+            Transform::Void
+                .apply_array(ts.as_ref())
+                .unwrap()
+                .data_type()
+        );
+        assert!(Transform::Truncate(2).apply_array(ts.as_ref()).is_err());
+        assert!(Transform::Bucket(0).apply_array(ts.as_ref()).is_err());
+        let ints = Int32Array::from(vec![i32::MIN]);
+        assert!(Transform::Truncate(u32::MAX).apply_array(&ints).is_err());
+    }
+}
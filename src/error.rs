@@ -0,0 +1,30 @@
+/*!
+The [Error] type returned when an operation on the [model](crate::model) fails.
+*/
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Errors raised when working with Iceberg metadata and values.
+pub enum Error {
+    /// A value, type or specification does not conform to the Iceberg spec.
+    Invalid(String),
+    /// A referenced field, snapshot or other entity does not exist.
+    NotFound(String),
+    /// The operation is not supported for the given type or value.
+    Unsupported(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Invalid(msg) => write!(f, "Invalid: {msg}"),
+            Error::NotFound(msg) => write!(f, "Not found: {msg}"),
+            Error::Unsupported(msg) => write!(f, "Unsupported: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Result type for operations that fail with an [Error].
+pub type Result<T> = std::result::Result<T, Error>;
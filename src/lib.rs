@@ -9,6 +9,9 @@ serialise and deserialise the Iceberg table format.
 
 Currently supported:
 * Parsing table metadata v2.
+* Binary and JSON single-value serialisation of [values](model::values).
+* Generating schemas from Rust structs with `#[derive(IcebergSchema)]` (`derive` feature).
+* Partitioning and sorting Arrow record batches ([arrow] module, `arrow` feature).
 
 Coming soon:
 * Manifest files.
@@ -17,4 +20,13 @@ Coming soon:
 * Validation.
 
 */
+// Lets the `IcebergSchema` derive refer to this crate as `iceberg_rs` in its own tests.
+extern crate self as iceberg_rs;
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod error;
 pub mod model;
+
+#[cfg(feature = "derive")]
+pub use iceberg_rs_derive::IcebergSchema;
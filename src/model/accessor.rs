@@ -0,0 +1,334 @@
+/*!
+Access to the fields of rows by field id.
+
+A row is anything implementing [StructLike], a positional tuple of optional [Value]s in the
+order of the fields of a [Struct]. Nested structs are stored as [Value::Struct].
+
+An [Accessor] is the path of positions from a row to a (possibly nested) field, computed once
+from a schema with [SchemaV2::accessors] and then used to read the field from many rows.
+Fields within lists and maps have no accessor because they do not have a single value per row.
+*/
+use std::collections::HashMap;
+
+use crate::model::schema::{AllType, SchemaV2, Struct, StructField};
+use crate::model::values::Value;
+
+/// A row of positional field values.
+pub trait StructLike {
+    /// The number of fields in the row.
+    fn len(&self) -> usize;
+
+    /// Returns true if the row has no fields.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The value at `pos`, or `None` if it is null or out of range.
+    fn get(&self, pos: usize) -> Option<&Value>;
+}
+
+impl StructLike for [Option<Value>] {
+    fn len(&self) -> usize {
+        <[Option<Value>]>::len(self)
+    }
+
+    fn get(&self, pos: usize) -> Option<&Value> {
+        <[Option<Value>]>::get(self, pos).and_then(Option::as_ref)
+    }
+}
+
+impl StructLike for Vec<Option<Value>> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn get(&self, pos: usize) -> Option<&Value> {
+        StructLike::get(self.as_slice(), pos)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Reads a field from a row by following the positions of the structs that contain it.
+pub struct Accessor {
+    position: usize,
+    field_id: i32,
+    name: String,
+    field_type: AllType,
+    inner: Option<Box<Accessor>>,
+}
+
+impl Accessor {
+    /// The value of the field in `row`, or `None` if it or any containing struct is null.
+    pub fn get<'a, S: StructLike + ?Sized>(&self, row: &'a S) -> Option<&'a Value> {
+        let value = row.get(self.position)?;
+        match &self.inner {
+            None => Some(value),
+            Some(inner) => match value {
+                Value::Struct(fields) => inner.get(fields),
+                _ => None,
+            },
+        }
+    }
+
+    /// The type of the field.
+    pub fn field_type(&self) -> &AllType {
+        match &self.inner {
+            None => &self.field_type,
+            Some(inner) => inner.field_type(),
+        }
+    }
+
+    /// The positions followed from the row to the field.
+    pub fn positions(&self) -> Vec<usize> {
+        let mut positions = vec![self.position];
+        if let Some(inner) = &self.inner {
+            positions.extend(inner.positions());
+        }
+        positions
+    }
+
+    /// The ids of the fields followed from the row to the field.
+    pub fn field_ids(&self) -> Vec<i32> {
+        let mut ids = vec![self.field_id];
+        if let Some(inner) = &self.inner {
+            ids.extend(inner.field_ids());
+        }
+        ids
+    }
+
+    /// The names of the fields followed from the row to the field.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = vec![self.name.as_str()];
+        if let Some(inner) = &self.inner {
+            names.extend(inner.names());
+        }
+        names
+    }
+
+    fn wrap(self, field: &StructField, position: usize) -> Accessor {
+        Accessor {
+            position,
+            field_id: field.id,
+            name: field.name.clone(),
+            field_type: field.field_type.clone(),
+            inner: Some(Box::new(self)),
+        }
+    }
+}
+
+fn build_accessors(struct_type: &Struct, accessors: &mut HashMap<i32, Accessor>) {
+    for (position, field) in struct_type.fields.iter().enumerate() {
+        accessors.insert(
+            field.id,
+            Accessor {
+                position,
+                field_id: field.id,
+                name: field.name.clone(),
+                field_type: field.field_type.clone(),
+                inner: None,
+            },
+        );
+        if let AllType::Struct(nested) = &field.field_type {
+            let mut nested_accessors = HashMap::new();
+            build_accessors(nested, &mut nested_accessors);
+            accessors.extend(
+                nested_accessors
+                    .into_iter()
+                    .map(|(id, accessor)| (id, accessor.wrap(field, position))),
+            );
+        }
+    }
+}
+
+impl SchemaV2 {
+    /// Accessors for every field of the schema that is not within a list or map, by field id.
+    pub fn accessors(&self) -> HashMap<i32, Accessor> {
+        let mut accessors = HashMap::new();
+        build_accessors(&self.struct_fields, &mut accessors);
+        accessors
+    }
+
+    /// The accessor for the field with `field_id`, if it exists and is not within a list or map.
+    /// Only the path to the field is built; use [SchemaV2::accessors] to access many fields.
+    pub fn accessor_for_field(&self, field_id: i32) -> Option<Accessor> {
+        find_accessor(&self.struct_fields, field_id)
+    }
+}
+
+fn find_accessor(struct_type: &Struct, field_id: i32) -> Option<Accessor> {
+    struct_type
+        .fields
+        .iter()
+        .enumerate()
+        .find_map(|(position, field)| {
+            if field.id == field_id {
+                return Some(Accessor {
+                    position,
+                    field_id: field.id,
+                    name: field.name.clone(),
+                    field_type: field.field_type.clone(),
+                    inner: None,
+                });
+            }
+            match &field.field_type {
+                AllType::Struct(nested) => {
+                    find_accessor(nested, field_id).map(|inner| inner.wrap(field, position))
+                }
+                _ => None,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::schema::{List, Map, PrimitiveType};
+
+    fn field(id: i32, name: &str, field_type: AllType) -> StructField {
+        StructField {
+            id,
+            name: name.to_string(),
+            required: false,
+            field_type,
+            doc: None,
+        }
+    }
+
+    fn schema() -> SchemaV2 {
+        SchemaV2 {
+            schema_id: 0,
+            identifier_field_ids: None,
+            name_mapping: None,
+            struct_fields: Struct {
+                fields: vec![
+                    field(1, "id", AllType::Primitive(PrimitiveType::Long)),
+                    field(
+                        2,
+                        "location",
+                        AllType::Struct(Struct {
+                            fields: vec![
+                                field(3, "lat", AllType::Primitive(PrimitiveType::Double)),
+                                field(
+                                    4,
+                                    "address",
+                                    AllType::Struct(Struct {
+                                        fields: vec![field(
+                                            5,
+                                            "city",
+                                            AllType::Primitive(PrimitiveType::String),
+                                        )],
+                                    }),
+                                ),
+                            ],
+                        }),
+                    ),
+                    field(
+                        6,
+                        "points",
+                        AllType::List(List {
+                            element_id: 7,
+                            element_required: true,
+                            element: Box::new(AllType::Struct(Struct {
+                                fields: vec![field(8, "x", AllType::Primitive(PrimitiveType::Int))],
+                            })),
+                        }),
+                    ),
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn test_accessors() {
+        let accessors = schema().accessors();
+        let mut ids: Vec<_> = accessors.keys().copied().collect();
+        ids.sort_unstable();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], ids);
+        assert_eq!(vec![1, 1, 0], accessors[&5].positions());
+        assert_eq!(vec![2, 4, 5], accessors[&5].field_ids());
+        assert_eq!(vec!["location", "address", "city"], accessors[&5].names());
+        assert_eq!(
+            &AllType::Primitive(PrimitiveType::String),
+            accessors[&5].field_type()
+        );
+
+        let row = vec![
+            Some(Value::Long(1)),
+            Some(Value::Struct(vec![
+                Some(Value::Double(1.5)),
+                Some(Value::Struct(vec![Some(Value::String(
+                    "Paris".to_string(),
+                ))])),
+            ])),
+            None,
+        ];
+        assert_eq!(Some(&Value::Long(1)), accessors[&1].get(&row));
+        assert_eq!(Some(&Value::Double(1.5)), accessors[&3].get(&row));
+        assert_eq!(
+            Some(&Value::String("Paris".to_string())),
+            accessors[&5].get(&row)
+        );
+        assert_eq!(None, accessors[&6].get(&row));
+    }
+
+    #[test]
+    fn test_accessor_for_field() {
+        let schema = schema();
+        for (id, accessor) in schema.accessors() {
+            assert_eq!(Some(accessor), schema.accessor_for_field(id));
+        }
+        assert_eq!(None, schema.accessor_for_field(99));
+    }
+
+    #[test]
+    fn test_null_parent() {
+        let schema = schema();
+        assert!(!schema.struct_fields.fields[1].required);
+        let lat = schema.accessor_for_field(3).unwrap();
+        let address = schema.accessor_for_field(4).unwrap();
+        let city = schema.accessor_for_field(5).unwrap();
+
+        // A null parent makes every nested field null.
+        let row = vec![Some(Value::Long(1)), None, None];
+        assert_eq!(None, lat.get(&row));
+        assert_eq!(None, address.get(&row));
+        assert_eq!(None, city.get(&row));
+
+        // Siblings of a null struct are still read.
+        let row = vec![
+            Some(Value::Long(1)),
+            Some(Value::Struct(vec![Some(Value::Double(1.5)), None])),
+        ];
+        assert_eq!(Some(&Value::Double(1.5)), lat.get(row.as_slice()));
+        assert_eq!(None, address.get(row.as_slice()));
+        assert_eq!(None, city.get(row.as_slice()));
+        assert!(schema.accessor_for_field(8).is_none());
+    }
+
+    #[test]
+    fn test_list_and_map_exclusion() {
+        let mut schema = schema();
+        schema.struct_fields.fields.push(field(
+            9,
+            "attributes",
+            AllType::Map(Map {
+                key_id: 10,
+                key: Box::new(AllType::Primitive(PrimitiveType::String)),
+                value_id: 11,
+                value_required: false,
+                value: Box::new(AllType::Struct(Struct {
+                    fields: vec![field(12, "v", AllType::Primitive(PrimitiveType::Int))],
+                })),
+            }),
+        ));
+        let accessors = schema.accessors();
+        let mut ids: Vec<_> = accessors.keys().copied().collect();
+        ids.sort_unstable();
+        // The list and map fields themselves have accessors, their contents do not.
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 9], ids);
+        for id in [7, 8, 10, 11, 12] {
+            assert!(schema.accessor_for_field(id).is_none());
+        }
+        assert_eq!(vec![3], accessors[&9].positions());
+    }
+}
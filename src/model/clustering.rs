@@ -0,0 +1,414 @@
+/*!
+Multi-dimensional clustering keys for rewriting data files, as computed by Spark's `zorder` rewrite.
+
+A [ClusteringKey] maps the values of several columns of a row to a single binary key, so that
+sorting rows by the key clusters rows with close values in every column. Each value is first
+encoded with [ordered_bytes], a fixed length encoding whose unsigned byte order is the order of
+the values. The encodings are then combined along a space filling [Curve]:
+
+* [Curve::ZOrder] interleaves the bits of the encodings with [interleave_bits], as Iceberg's
+  Java `ZOrderByteUtils` does,
+* [Curve::Hilbert] computes the index of the point on a Hilbert curve, which keeps rows with
+  consecutive keys closer together at the cost of a more expensive computation.
+
+Keys compare as unsigned bytes, like [Value::Binary](crate::model::values::Value::Binary) values.
+*/
+use crate::error::{Error, Result};
+use crate::model::accessor::{Accessor, StructLike};
+use crate::model::schema::{AllType, PrimitiveType, SchemaV2};
+use crate::model::values::Value;
+
+/// The length of the encoding of numbers, dates and times.
+pub const PRIMITIVE_LENGTH: usize = 8;
+
+/// The default length that strings and binary values are truncated or padded to.
+pub const DEFAULT_VAR_LENGTH: usize = 8;
+
+/// The length of the encoding of values of `data_type`, with strings and binary values
+/// truncated or padded to `var_length` bytes.
+pub fn ordered_length(data_type: &PrimitiveType, var_length: usize) -> Result<usize> {
+    match data_type {
+        PrimitiveType::Boolean
+        | PrimitiveType::Int
+        | PrimitiveType::Long
+        | PrimitiveType::Float
+        | PrimitiveType::Double
+        | PrimitiveType::Date
+        | PrimitiveType::Time
+        | PrimitiveType::Timestamp
+        | PrimitiveType::Timestampz
+        | PrimitiveType::TimestampNs
+        | PrimitiveType::TimestampzNs => Ok(PRIMITIVE_LENGTH),
+        PrimitiveType::Decimal { .. } | PrimitiveType::Uuid => Ok(16),
+        PrimitiveType::String | PrimitiveType::Fixed(_) | PrimitiveType::Binary => Ok(var_length),
+        _ => Err(Error::Unsupported(format!(
+            "cannot cluster by {data_type:?} values"
+        ))),
+    }
+}
+
+fn truncate_or_fill(bytes: &[u8], length: usize) -> Vec<u8> {
+    let mut result = bytes[..bytes.len().min(length)].to_vec();
+    result.resize(length, 0);
+    result
+}
+
+/// Encode a value of `data_type` so that the unsigned byte order of the encodings is the order
+/// of the values. Integers are widened to 64 bits with their sign bit flipped, floating point
+/// values are widened to 64 bits with the sign bit flipped for positive values and every bit
+/// flipped for negative ones, and strings and binary values are truncated or zero padded to
+/// `var_length` bytes. Null is encoded as zeros.
+pub fn ordered_bytes(
+    value: Option<&Value>,
+    data_type: &PrimitiveType,
+    var_length: usize,
+) -> Result<Vec<u8>> {
+    let length = ordered_length(data_type, var_length)?;
+    let long = |v: i64| (v ^ i64::MIN).to_be_bytes().to_vec();
+    let double = |v: f64| {
+        let bits = v.to_bits() as i64;
+        (bits ^ ((bits >> 63) | i64::MIN)).to_be_bytes().to_vec()
+    };
+    let value = match value {
+        None => return Ok(vec![0; length]),
+        Some(value) => value,
+    };
+    use PrimitiveType as T;
+    Ok(match (value, data_type) {
+        (Value::Boolean(v), T::Boolean) => long(i64::from(*v)),
+        (Value::Int(v), T::Int) | (Value::Date(v), T::Date) => long(i64::from(*v)),
+        (Value::Long(v), T::Long)
+        | (Value::Time(v), T::Time)
+        | (Value::Timestamp(v), T::Timestamp)
+        | (Value::Timestampz(v), T::Timestampz)
+        | (Value::TimestampNs(v), T::TimestampNs)
+        | (Value::TimestampzNs(v), T::TimestampzNs) => long(*v),
+        (Value::Float(v), T::Float) => double(f64::from(*v)),
+        (Value::Double(v), T::Double) => double(*v),
+        (Value::Decimal(v), T::Decimal { .. }) => (v ^ i128::MIN).to_be_bytes().to_vec(),
+        (Value::Uuid(v), T::Uuid) => v.as_bytes().to_vec(),
+        (Value::String(v), T::String) => truncate_or_fill(v.as_bytes(), length),
+        (Value::Fixed(v), T::Fixed(_)) | (Value::Binary(v), T::Binary) => {
+            truncate_or_fill(v, length)
+        }
+        _ => {
+            return Err(Error::Invalid(format!(
+                "{value:?} is not a {data_type:?} value"
+            )))
+        }
+    })
+}
+
+/// Interleave the bits of `columns`, taking one bit of each column in turn starting from the
+/// most significant bit of the first column. Columns that run out of bits are skipped.
+pub fn interleave_bits(columns: &[Vec<u8>]) -> Vec<u8> {
+    let total_bits: usize = columns.iter().map(|c| c.len() * 8).sum();
+    let max_bits = columns.iter().map(|c| c.len() * 8).max().unwrap_or(0);
+    let mut result = vec![0u8; total_bits / 8];
+    let mut position = 0;
+    for bit in 0..max_bits {
+        for column in columns.iter().filter(|c| bit < c.len() * 8) {
+            if column[bit / 8] & (0x80 >> (bit % 8)) != 0 {
+                result[position / 8] |= 0x80 >> (position % 8);
+            }
+            position += 1;
+        }
+    }
+    result
+}
+
+/// Transform the coordinates of a point with `bits` bits per coordinate to the transpose of its
+/// index on a Hilbert curve, using John Skilling's algorithm from
+/// "Programming the Hilbert curve" (2004). Interleaving the bits of the transpose gives the index.
+fn hilbert_transpose(coordinates: &mut [u64], bits: u32) {
+    let n = coordinates.len();
+    if n == 0 || bits == 0 {
+        return;
+    }
+    let m = 1u64 << (bits - 1);
+    let mut q = m;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..n {
+            if coordinates[i] & q != 0 {
+                coordinates[0] ^= p;
+            } else {
+                let t = (coordinates[0] ^ coordinates[i]) & p;
+                coordinates[0] ^= t;
+                coordinates[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+    for i in 1..n {
+        coordinates[i] ^= coordinates[i - 1];
+    }
+    let mut t = 0;
+    let mut q = m;
+    while q > 1 {
+        if coordinates[n - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for coordinate in coordinates.iter_mut() {
+        *coordinate ^= t;
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The space filling curve that combines the columns of a [ClusteringKey].
+pub enum Curve {
+    /// Interleave the bits of the columns.
+    ZOrder,
+    /// The index on a Hilbert curve of the first 8 bytes of each column.
+    Hilbert,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Computes the clustering keys of rows of a schema from some of its columns.
+pub struct ClusteringKey {
+    curve: Curve,
+    var_length: usize,
+    columns: Vec<(PrimitiveType, Accessor)>,
+}
+
+impl ClusteringKey {
+    /// A key of rows of `schema` combining the values of `columns` along `curve`. Returns an
+    /// error if a column is missing, is not a primitive, is within a list or map or has a type
+    /// that cannot be encoded.
+    pub fn new(schema: &SchemaV2, columns: &[&str], curve: Curve) -> Result<ClusteringKey> {
+        let accessors = schema.accessors();
+        let columns = columns
+            .iter()
+            .map(|name| {
+                let field = schema.field_by_name(name).ok_or_else(|| {
+                    Error::NotFound(format!("column {name} is not in the schema"))
+                })?;
+                let data_type = match &field.field_type {
+                    AllType::Primitive(p) => p.clone(),
+                    _ => {
+                        return Err(Error::Invalid(format!(
+                            "cannot cluster by column {name}, which is not a primitive"
+                        )))
+                    }
+                };
+                ordered_length(&data_type, DEFAULT_VAR_LENGTH)?;
+                let accessor = accessors.get(&field.id).cloned().ok_or_else(|| {
+                    Error::Invalid(format!(
+                        "cannot cluster by column {name} within a list or map"
+                    ))
+                })?;
+                Ok((data_type, accessor))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ClusteringKey {
+            curve,
+            var_length: DEFAULT_VAR_LENGTH,
+            columns,
+        })
+    }
+
+    /// Set the length that strings and binary values are truncated or padded to,
+    /// [DEFAULT_VAR_LENGTH] by default.
+    pub fn with_var_length(mut self, var_length: usize) -> Self {
+        self.var_length = var_length;
+        self
+    }
+
+    /// The curve the columns are combined along.
+    pub fn curve(&self) -> Curve {
+        self.curve
+    }
+
+    /// The key of `row`, a row of the schema.
+    pub fn key<S: StructLike + ?Sized>(&self, row: &S) -> Result<Vec<u8>> {
+        let columns = self
+            .columns
+            .iter()
+            .map(|(data_type, accessor)| {
+                ordered_bytes(accessor.get(row), data_type, self.var_length)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(match self.curve {
+            Curve::ZOrder => interleave_bits(&columns),
+            Curve::Hilbert => {
+                let mut coordinates: Vec<u64> = columns
+                    .iter()
+                    .map(|c| {
+                        let mut bytes = [0u8; 8];
+                        let len = c.len().min(8);
+                        bytes[..len].copy_from_slice(&c[..len]);
+                        u64::from_be_bytes(bytes)
+                    })
+                    .collect();
+                hilbert_transpose(&mut coordinates, 64);
+                let transpose: Vec<Vec<u8>> = coordinates
+                    .iter()
+                    .map(|c| c.to_be_bytes().to_vec())
+                    .collect();
+                interleave_bits(&transpose)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordered_bytes() {
+        let sorted = |values: Vec<Value>, data_type: PrimitiveType| {
+            let encoded: Vec<Vec<u8>> = values
+                .iter()
+                .map(|v| ordered_bytes(Some(v), &data_type, 4).unwrap())
+                .collect();
+            encoded.windows(2).all(|w| w[0] < w[1])
+        };
+        assert!(sorted(
+            vec![
+                Value::Int(i32::MIN),
+                Value::Int(-1),
+                Value::Int(0),
+                Value::Int(7)
+            ],
+            PrimitiveType::Int
+        ));
+        assert!(sorted(
+            vec![
+                Value::Double(f64::NEG_INFINITY),
+                Value::Double(-2.5),
+                Value::Double(-0.0),
+                Value::Double(0.0),
+                Value::Double(1e-300),
+                Value::Double(3.0),
+            ],
+            PrimitiveType::Double
+        ));
+        assert!(sorted(
+            vec![Value::Float(-1.5), Value::Float(0.25), Value::Float(2.0)],
+            PrimitiveType::Float
+        ));
+        assert!(sorted(
+            vec![
+                Value::Decimal(-100),
+                Value::Decimal(5),
+                Value::Decimal(i128::MAX)
+            ],
+            PrimitiveType::Decimal {
+                precision: 38,
+                scale: 0
+            }
+        ));
+        assert!(sorted(
+            vec![
+                Value::String("a".to_string()),
+                Value::String("ab".to_string()),
+                Value::String("b".to_string()),
+                Value::String("é".to_string()),
+            ],
+            PrimitiveType::String
+        ));
+        assert_eq!(
+            vec![b'i', b'c', b'e', b'b'],
+            ordered_bytes(
+                Some(&Value::String("iceberg".to_string())),
+                &PrimitiveType::String,
+                4
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            vec![0; 8],
+            ordered_bytes(None, &PrimitiveType::Long, 4).unwrap()
+        );
+        assert!(ordered_bytes(Some(&Value::Int(1)), &PrimitiveType::String, 4).is_err());
+    }
+
+    #[test]
+    fn test_interleave_bits() {
+        assert_eq!(
+            vec![0b1010_1010, 0b1010_1010],
+            interleave_bits(&[vec![0xff], vec![0x00]])
+        );
+        assert_eq!(
+            vec![0b1101_0000, 0b0000_0000, 0b1111_1111],
+            interleave_bits(&[vec![0x80, 0xff], vec![0xc0]])
+        );
+        assert!(interleave_bits(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_hilbert_curve() {
+        // Consecutive indices on a 4x4 Hilbert curve are neighbouring cells.
+        let bits = 2;
+        let mut cells: Vec<(u64, (u64, u64))> = (0..4)
+            .flat_map(|x| (0..4).map(move |y| (x, y)))
+            .map(|(x, y)| {
+                let mut coordinates = [x, y];
+                hilbert_transpose(&mut coordinates, bits);
+                let index = (0..bits).rev().fold(0, |index, bit| {
+                    coordinates
+                        .iter()
+                        .fold(index, |index, c| (index << 1) | ((c >> bit) & 1))
+                });
+                (index, (x, y))
+            })
+            .collect();
+        cells.sort_unstable();
+        assert_eq!(
+            (0..16).collect::<Vec<_>>(),
+            cells.iter().map(|(i, _)| *i).collect::<Vec<_>>()
+        );
+        assert_eq!((0, 0), cells[0].1);
+        for pair in cells.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0].1, pair[1].1);
+            assert_eq!(1, x0.abs_diff(x1) + y0.abs_diff(y1));
+        }
+    }
+
+    #[test]
+    fn test_clustering_key() {
+        let schema: SchemaV2 = serde_json::from_str(
+            r#"
+            {
+                "schema-id": 0,
+                "type": "struct",
+                "fields": [
+                    { "id": 1, "name": "x", "required": true, "field_type": "int" },
+                    { "id": 2, "name": "y", "required": false, "field_type": "int" },
+                    { "id": 3, "name": "doc", "required": false, "field_type": "variant" }
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+        let row = |x: i32, y: Option<i32>| vec![Some(Value::Int(x)), y.map(Value::Int), None];
+
+        let z = ClusteringKey::new(&schema, &["x", "y"], Curve::ZOrder).unwrap();
+        let key = z.key(&row(0, Some(-1))).unwrap();
+        assert_eq!(16, key.len());
+        // The flipped sign bit of x is followed by the cleared sign bit of y.
+        assert_eq!(0b1001_0101, key[0]);
+        assert!(z.key(&row(0, None)).unwrap() < z.key(&row(0, Some(i32::MIN))).unwrap());
+        assert!(z.key(&row(1, Some(1))).unwrap() < z.key(&row(2, Some(2))).unwrap());
+
+        let hilbert = ClusteringKey::new(&schema, &["x", "y"], Curve::Hilbert).unwrap();
+        assert_eq!(Curve::Hilbert, hilbert.curve());
+        assert_eq!(16, hilbert.key(&row(3, Some(4))).unwrap().len());
+        assert_ne!(
+            hilbert.key(&row(3, Some(4))).unwrap(),
+            hilbert.key(&row(4, Some(3))).unwrap()
+        );
+
+        assert!(matches!(
+            ClusteringKey::new(&schema, &["x", "z"], Curve::ZOrder),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            ClusteringKey::new(&schema, &["doc"], Curve::ZOrder),
+            Err(Error::Unsupported(_))
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+/*!
+Helpers for [decimal](crate::model::schema::PrimitiveType::Decimal) values, which are stored
+as an unscaled `i128` with the scale given by the type.
+
+The spec limits decimals to a precision of [MAX_PRECISION] digits. [required_bytes] and
+[max_precision] relate a precision to the size of the fixed length byte array used to store
+it, and [rescale] converts unscaled values between scales without losing digits.
+*/
+use crate::error::{Error, Result};
+
+/// The largest precision of a decimal.
+pub const MAX_PRECISION: u32 = 38;
+
+/// Returns an error unless `precision` and `scale` are valid for a decimal type;
+/// the precision must be between 1 and [MAX_PRECISION] and the scale no larger than the precision.
+pub fn validate(precision: i32, scale: u8) -> Result<()> {
+    if precision < 1 || precision > MAX_PRECISION as i32 {
+        return Err(Error::Invalid(format!(
+            "decimal precision must be between 1 and {MAX_PRECISION}, found {precision}"
+        )));
+    }
+    if i32::from(scale) > precision {
+        return Err(Error::Invalid(format!(
+            "decimal scale {scale} is larger than precision {precision}"
+        )));
+    }
+    Ok(())
+}
+
+/// The number of digits of `unscaled`, ignoring the sign. Zero has one digit.
+pub fn precision_of(unscaled: i128) -> u32 {
+    unscaled.unsigned_abs().checked_ilog10().unwrap_or(0) + 1
+}
+
+/// Returns true if `unscaled` has no more than `precision` digits.
+pub fn fits_precision(unscaled: i128, precision: i32) -> bool {
+    precision_of(unscaled) as i32 <= precision
+}
+
+/// The largest precision that can always be stored in `num_bytes` bytes of two's complement.
+pub fn max_precision(num_bytes: usize) -> Result<u32> {
+    if num_bytes == 0 || num_bytes > 16 {
+        return Err(Error::Invalid(format!(
+            "decimals are stored in 1 to 16 bytes, found {num_bytes}"
+        )));
+    }
+    let max_value = i128::MAX >> (128 - 8 * num_bytes);
+    Ok(max_value.ilog10())
+}
+
+/// The smallest number of bytes that can store any decimal of `precision` digits.
+pub fn required_bytes(precision: u32) -> Result<usize> {
+    (1..=16)
+        .find(|n| max_precision(*n).is_ok_and(|p| p >= precision))
+        .filter(|_| precision >= 1)
+        .ok_or_else(|| {
+            Error::Invalid(format!(
+                "decimal precision must be between 1 and {MAX_PRECISION}, found {precision}"
+            ))
+        })
+}
+
+/// Convert an unscaled value from `from_scale` to `to_scale`. Fails if reducing the
+/// scale would drop non-zero digits or if the result overflows.
+pub fn rescale(unscaled: i128, from_scale: u8, to_scale: u8) -> Result<i128> {
+    let overflow = || {
+        Error::Invalid(format!(
+            "cannot rescale {unscaled} from scale {from_scale} to {to_scale}"
+        ))
+    };
+    if to_scale >= from_scale {
+        10i128
+            .checked_pow(u32::from(to_scale - from_scale))
+            .and_then(|factor| unscaled.checked_mul(factor))
+            .ok_or_else(overflow)
+    } else {
+        let factor = 10i128
+            .checked_pow(u32::from(from_scale - to_scale))
+            .ok_or_else(overflow)?;
+        if unscaled % factor != 0 {
+            return Err(overflow());
+        }
+        Ok(unscaled / factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(1, 0).is_ok());
+        assert!(validate(38, 38).is_ok());
+        assert!(validate(0, 0).is_err());
+        assert!(validate(39, 0).is_err());
+        assert!(validate(5, 6).is_err());
+    }
+
+    #[test]
+    fn test_precision_of() {
+        assert_eq!(1, precision_of(0));
+        assert_eq!(3, precision_of(-999));
+        assert_eq!(4, precision_of(1000));
+        assert_eq!(39, precision_of(i128::MIN));
+        assert!(fits_precision(99, 2));
+        assert!(!fits_precision(100, 2));
+    }
+
+    #[test]
+    fn test_bytes_and_precision() {
+        let expected = [2, 4, 6, 9, 11, 14, 16, 18, 21, 23, 26, 28, 31, 33, 35, 38];
+        for (i, precision) in expected.iter().enumerate() {
+            assert_eq!(*precision, max_precision(i + 1).unwrap());
+        }
+        assert_eq!(1, required_bytes(1).unwrap());
+        assert_eq!(4, required_bytes(9).unwrap());
+        assert_eq!(5, required_bytes(10).unwrap());
+        assert_eq!(16, required_bytes(38).unwrap());
+        assert!(required_bytes(39).is_err());
+        assert!(required_bytes(0).is_err());
+        assert!(max_precision(17).is_err());
+    }
+
+    #[test]
+    fn test_rescale() {
+        assert_eq!(14200, rescale(142, 1, 3).unwrap());
+        assert_eq!(-142, rescale(-14200, 3, 1).unwrap());
+        assert!(rescale(14201, 3, 1).is_err());
+        assert!(rescale(i128::MAX, 0, 1).is_err());
+    }
+}
@@ -0,0 +1,477 @@
+/*!
+Evolution of the [partition spec](https://iceberg.apache.org/spec/#partition-evolution) of a table.
+
+[UpdatePartitionSpec] collects fields to add, remove and rename in the default spec of a
+[TableMetadataV2]. [UpdatePartitionSpec::apply] produces the new [PartitionSpec] and
+[UpdatePartitionSpec::commit] adds it to the table metadata as the default spec.
+
+Unchanged fields keep their field ids. In v2 tables removed fields are dropped and a field added
+back reuses the id it had in an earlier spec. v1 tables require the fields of every spec to keep
+their position, so removed fields are replaced with the `void` transform instead.
+
+```rust
+# use iceberg_rs::model::table::TableMetadataV2;
+# use iceberg_rs::model::partition::Transform;
+# let mut metadata: TableMetadataV2 = serde_json::from_str(r#"{
+#     "format-version": 2, "table-uuid": "fb072c92-a02b-11e9-ae9c-1bb7bc9eca94",
+#     "location": "s3://b/wh/data.db/table", "last-sequence-number": 1,
+#     "last-updated-ms": 1515100955770, "last-column-id": 1,
+#     "schemas": [{"schema-id": 0, "type": "struct", "fields": [
+#         {"id": 1, "name": "ts", "required": true, "field_type": "timestamptz"}]}],
+#     "current-schema-id": 0,
+#     "partition-specs": [{"spec-id": 0, "fields": [
+#         {"source-id": 1, "field-id": 1000, "name": "ts_day", "transform": "day"}]}],
+#     "default-spec-id": 0, "last-partition-id": 1000,
+#     "sort-orders": [], "default-sort-order-id": 0}"#).unwrap();
+let spec_id = metadata
+    .update_partition_spec()
+    .remove_field("ts_day")
+    .add_field("ts", Transform::Hour)
+    .commit(&mut metadata)
+    .unwrap();
+assert_eq!(1, spec_id);
+assert_eq!("ts_hour", metadata.partition_specs[1].fields[0].name);
+```
+*/
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{Error, Result};
+use crate::model::partition::{
+    check_field, default_name, PartitionField, PartitionSpec, Transform,
+};
+use crate::model::schema::{SchemaV2, Struct};
+use crate::model::table::{FormatVersion, TableMetadataV2};
+use crate::model::transform::check_source;
+
+/// Changes to the default partition spec of a table. Errors are reported by
+/// [UpdatePartitionSpec::apply] and [UpdatePartitionSpec::commit].
+pub struct UpdatePartitionSpec {
+    format_version: FormatVersion,
+    schema: SchemaV2,
+    base: PartitionSpec,
+    previous_fields: Vec<PartitionField>,
+    last_partition_id: i32,
+    next_spec_id: i32,
+    adds: Vec<PartitionField>,
+    removes: HashSet<i32>,
+    renames: HashMap<String, String>,
+    error: Option<Error>,
+}
+
+impl TableMetadataV2 {
+    /// Start changing the default partition spec of the table.
+    pub fn update_partition_spec(&self) -> UpdatePartitionSpec {
+        let schema = self
+            .schemas
+            .iter()
+            .find(|s| s.schema_id == self.current_schema_id)
+            .cloned();
+        let base = self
+            .partition_specs
+            .iter()
+            .find(|s| s.spec_id == self.default_spec_id)
+            .cloned();
+        let error = match (&schema, &base) {
+            (None, _) => Some(Error::NotFound(format!(
+                "current schema {} is not in the table metadata",
+                self.current_schema_id
+            ))),
+            (_, None) => Some(Error::NotFound(format!(
+                "default partition spec {} is not in the table metadata",
+                self.default_spec_id
+            ))),
+            _ => None,
+        };
+        UpdatePartitionSpec {
+            format_version: FormatVersion::V2,
+            schema: schema.unwrap_or_else(|| SchemaV2 {
+                schema_id: self.current_schema_id,
+                identifier_field_ids: None,
+                name_mapping: None,
+                struct_fields: Struct { fields: vec![] },
+            }),
+            base: base.unwrap_or(PartitionSpec {
+                spec_id: self.default_spec_id,
+                fields: vec![],
+            }),
+            previous_fields: self
+                .partition_specs
+                .iter()
+                .flat_map(|s| s.fields.iter().cloned())
+                .collect(),
+            last_partition_id: self.last_partition_id,
+            next_spec_id: self
+                .partition_specs
+                .iter()
+                .map(|s| s.spec_id + 1)
+                .max()
+                .unwrap_or(0),
+            adds: vec![],
+            removes: HashSet::new(),
+            renames: HashMap::new(),
+            error,
+        }
+    }
+}
+
+impl UpdatePartitionSpec {
+    /// Apply the rules of `format_version`; the rules of v2 are used by default.
+    pub fn with_format_version(mut self, format_version: FormatVersion) -> Self {
+        self.format_version = format_version;
+        self
+    }
+
+    /// Add a field applying `transform` to the `source` column, with the default name for the transform.
+    pub fn add_field(self, source: &str, transform: Transform) -> Self {
+        self.record(|update| update.try_add(source, None, transform))
+    }
+
+    /// Add a field named `name` applying `transform` to the `source` column.
+    pub fn add_field_with_name(self, source: &str, name: &str, transform: Transform) -> Self {
+        self.record(|update| update.try_add(source, Some(name), transform))
+    }
+
+    /// Remove the field named `name` from the spec.
+    pub fn remove_field(self, name: &str) -> Self {
+        self.record(|update| update.try_remove(name))
+    }
+
+    /// Rename the field named `name` to `new_name`.
+    pub fn rename_field(self, name: &str, new_name: &str) -> Self {
+        self.record(|update| update.try_rename(name, new_name))
+    }
+
+    fn record(mut self, change: impl FnOnce(&mut Self) -> Result<()>) -> Self {
+        if self.error.is_none() {
+            if let Err(error) = change(&mut self) {
+                self.error = Some(error);
+            }
+        }
+        self
+    }
+
+    fn base_field(&self, name: &str) -> Result<&PartitionField> {
+        self.base
+            .fields
+            .iter()
+            .find(|f| f.name == name)
+            .ok_or_else(|| Error::NotFound(format!("partition field {name} is not in the spec")))
+    }
+
+    fn try_add(&mut self, source: &str, name: Option<&str>, transform: Transform) -> Result<()> {
+        let source_id = self
+            .schema
+            .field_by_name(source)
+            .ok_or_else(|| Error::NotFound(format!("source field {source} is not in the schema")))?
+            .id;
+        check_source(&self.schema, source_id, &transform)?;
+
+        let existing = self
+            .base
+            .fields
+            .iter()
+            .find(|f| f.source_id == source_id && f.transform == transform)
+            .cloned();
+        if let Some(existing) = existing {
+            if !self.removes.remove(&existing.field_id) {
+                return Err(Error::Invalid(format!(
+                    "partition field {} already applies {transform:?} to {source}",
+                    existing.name
+                )));
+            }
+            // Adding back a field removed in this update keeps the field as it was.
+            return match name {
+                Some(name) if name != existing.name => self.try_rename(&existing.name, name),
+                _ => Ok(()),
+            };
+        }
+        if self
+            .adds
+            .iter()
+            .any(|f| f.source_id == source_id && f.transform == transform)
+        {
+            return Err(Error::Invalid(format!(
+                "{transform:?} of {source} is already added"
+            )));
+        }
+
+        let name = name
+            .map(str::to_string)
+            .unwrap_or_else(|| default_name(source, &transform));
+        let recycled = match self.format_version {
+            FormatVersion::V1 => None,
+            FormatVersion::V2 => self
+                .previous_fields
+                .iter()
+                .find(|f| f.source_id == source_id && f.transform == transform && f.name == name)
+                .or_else(|| {
+                    self.previous_fields
+                        .iter()
+                        .find(|f| f.source_id == source_id && f.transform == transform)
+                })
+                .map(|f| f.field_id),
+        };
+        let field_id = match recycled {
+            Some(id) => id,
+            None => {
+                self.last_partition_id += 1;
+                self.last_partition_id
+            }
+        };
+        self.adds.push(PartitionField {
+            source_id,
+            field_id,
+            name,
+            transform,
+        });
+        Ok(())
+    }
+
+    fn try_remove(&mut self, name: &str) -> Result<()> {
+        if self.adds.iter().any(|f| f.name == name) {
+            return Err(Error::Invalid(format!(
+                "cannot remove newly added partition field {name}"
+            )));
+        }
+        if self.renames.contains_key(name) {
+            return Err(Error::Invalid(format!(
+                "cannot remove renamed partition field {name}"
+            )));
+        }
+        let field_id = self.base_field(name)?.field_id;
+        self.removes.insert(field_id);
+        Ok(())
+    }
+
+    fn try_rename(&mut self, name: &str, new_name: &str) -> Result<()> {
+        if self.adds.iter().any(|f| f.name == name) {
+            return Err(Error::Invalid(format!(
+                "cannot rename newly added partition field {name}"
+            )));
+        }
+        let field_id = self.base_field(name)?.field_id;
+        if self.removes.contains(&field_id) {
+            return Err(Error::Invalid(format!(
+                "cannot rename removed partition field {name}"
+            )));
+        }
+        self.renames.insert(name.to_string(), new_name.to_string());
+        Ok(())
+    }
+
+    /// The new partition spec.
+    pub fn apply(&self) -> Result<PartitionSpec> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        let mut fields = Vec::with_capacity(self.base.fields.len() + self.adds.len());
+        for field in &self.base.fields {
+            if self.removes.contains(&field.field_id) {
+                if self.format_version == FormatVersion::V1 {
+                    fields.push(PartitionField {
+                        name: format!("{}_{}", field.name, field.field_id),
+                        transform: Transform::Void,
+                        ..field.clone()
+                    });
+                }
+            } else {
+                let name = self.renames.get(&field.name).unwrap_or(&field.name);
+                fields.push(PartitionField {
+                    name: name.clone(),
+                    ..field.clone()
+                });
+            }
+        }
+        fields.extend(self.adds.iter().cloned());
+
+        let mut checked: Vec<PartitionField> = Vec::with_capacity(fields.len());
+        for field in fields {
+            check_field(&self.schema, &checked, &field)?;
+            checked.push(field);
+        }
+        Ok(PartitionSpec {
+            spec_id: self.next_spec_id,
+            fields: checked,
+        })
+    }
+
+    /// Add the new spec to `metadata` and make it the default spec, returning its id.
+    /// If `metadata` already has a spec with the same fields that spec becomes the default instead.
+    pub fn commit(self, metadata: &mut TableMetadataV2) -> Result<i32> {
+        let spec = self.apply()?;
+        let spec_id = match metadata
+            .partition_specs
+            .iter()
+            .find(|s| s.fields == spec.fields)
+        {
+            Some(existing) => existing.spec_id,
+            None => {
+                let spec_id = spec.spec_id;
+                metadata.partition_specs.push(spec);
+                spec_id
+            }
+        };
+        metadata.default_spec_id = spec_id;
+        metadata.last_partition_id = metadata.last_partition_id.max(self.last_partition_id);
+        Ok(spec_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::metadata_with_snapshots;
+
+    fn metadata() -> TableMetadataV2 {
+        let mut metadata = metadata_with_snapshots(&[]);
+        metadata.schemas = vec![serde_json::from_str(
+            r#"
+            {
+                "schema-id": 0,
+                "type": "struct",
+                "fields": [
+                    { "id": 1, "name": "id", "required": true, "field_type": "long" },
+                    { "id": 2, "name": "ts", "required": true, "field_type": "timestamptz" },
+                    { "id": 3, "name": "region", "required": true, "field_type": "string" }
+                ]
+            }
+            "#,
+        )
+        .unwrap()];
+        metadata.last_column_id = 3;
+        metadata.partition_specs = vec![serde_json::from_str(
+            r#"
+            {
+                "spec-id": 0,
+                "fields": [
+                    { "source-id": 3, "field-id": 1000, "name": "region", "transform": "identity" },
+                    { "source-id": 2, "field-id": 1001, "name": "ts_day", "transform": "day" }
+                ]
+            }
+            "#,
+        )
+        .unwrap()];
+        metadata.last_partition_id = 1001;
+        metadata
+    }
+
+    fn summary(spec: &PartitionSpec) -> Vec<(i32, &str, Transform)> {
+        spec.fields
+            .iter()
+            .map(|f| (f.field_id, f.name.as_str(), f.transform.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_daily_to_hourly_v2() {
+        let mut metadata = metadata();
+        let spec_id = metadata
+            .update_partition_spec()
+            .remove_field("ts_day")
+            .add_field("ts", Transform::Hour)
+            .commit(&mut metadata)
+            .unwrap();
+        assert_eq!(1, spec_id);
+        assert_eq!(1, metadata.default_spec_id);
+        assert_eq!(1002, metadata.last_partition_id);
+        assert_eq!(
+            vec![
+                (1000, "region", Transform::Identity),
+                (1002, "ts_hour", Transform::Hour)
+            ],
+            summary(&metadata.partition_specs[1])
+        );
+
+        // Going back to daily partitioning reuses the original field id and spec.
+        let spec_id = metadata
+            .update_partition_spec()
+            .remove_field("ts_hour")
+            .add_field("ts", Transform::Day)
+            .commit(&mut metadata)
+            .unwrap();
+        assert_eq!(0, spec_id);
+        assert_eq!(2, metadata.partition_specs.len());
+        assert_eq!(1002, metadata.last_partition_id);
+    }
+
+    #[test]
+    fn test_daily_to_hourly_v1() {
+        let spec = metadata()
+            .update_partition_spec()
+            .with_format_version(FormatVersion::V1)
+            .remove_field("ts_day")
+            .add_field("ts", Transform::Hour)
+            .apply()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (1000, "region", Transform::Identity),
+                (1001, "ts_day_1001", Transform::Void),
+                (1002, "ts_hour", Transform::Hour)
+            ],
+            summary(&spec)
+        );
+    }
+
+    #[test]
+    fn test_rename_and_add() {
+        let spec = metadata()
+            .update_partition_spec()
+            .rename_field("ts_day", "day")
+            .add_field_with_name("id", "shard", Transform::Bucket(8))
+            .apply()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (1000, "region", Transform::Identity),
+                (1001, "day", Transform::Day),
+                (1002, "shard", Transform::Bucket(8))
+            ],
+            summary(&spec)
+        );
+    }
+
+    #[test]
+    fn test_remove_and_add_back() {
+        let spec = metadata()
+            .update_partition_spec()
+            .remove_field("ts_day")
+            .add_field_with_name("ts", "day", Transform::Day)
+            .apply()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (1000, "region", Transform::Identity),
+                (1001, "day", Transform::Day)
+            ],
+            summary(&spec)
+        );
+    }
+
+    #[test]
+    fn test_invalid_updates() {
+        let update = || metadata().update_partition_spec();
+        assert!(matches!(
+            update().remove_field("missing").apply(),
+            Err(Error::NotFound(_))
+        ));
+        // Hourly and daily partitions of the same column are redundant.
+        assert!(update().add_field("ts", Transform::Hour).apply().is_err());
+        assert!(update().add_field("ts", Transform::Day).apply().is_err());
+        assert!(update().add_field("id", Transform::Day).apply().is_err());
+        assert!(update()
+            .add_field_with_name("id", "region", Transform::Bucket(2))
+            .apply()
+            .is_err());
+        assert!(update()
+            .remove_field("ts_day")
+            .rename_field("ts_day", "day")
+            .apply()
+            .is_err());
+        assert!(update()
+            .add_field("id", Transform::Bucket(2))
+            .remove_field("id_bucket_2")
+            .apply()
+            .is_err());
+    }
+}
@@ -0,0 +1,292 @@
+/*!
+Planning the [expiration](https://iceberg.apache.org/spec/#snapshot-retention-policy) of snapshots
+and refs.
+
+Each branch keeps its head, the snapshots at its head that are younger than its
+`max_snapshot_age_ms`, and at least its `min_snapshots_to_keep` newest snapshots. Each tag keeps
+the snapshot it points to.
+Refs other than `main` whose snapshot is older than their `max_ref_age_ms` are removed. Snapshots
+that are not reachable from any ref are kept while they are younger than the table's maximum
+snapshot age.
+
+Retention settings of a ref that are not positive are taken from the table properties
+([MAX_SNAPSHOT_AGE_MS], [MIN_SNAPSHOTS_TO_KEEP] and [MAX_REF_AGE_MS]), as are the settings of the
+`main` branch if the table has no ref for it.
+*/
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{Error, Result};
+use crate::model::history::MAIN_BRANCH;
+use crate::model::snapshot::Retention;
+use crate::model::table::TableMetadataV2;
+
+/// Table property of the default maximum age of snapshots to keep.
+pub const MAX_SNAPSHOT_AGE_MS: &str = "history.expire.max-snapshot-age-ms";
+/// The default maximum age of snapshots to keep, 5 days.
+pub const MAX_SNAPSHOT_AGE_MS_DEFAULT: i64 = 5 * 24 * 60 * 60 * 1000;
+/// Table property of the default minimum number of snapshots to keep in a branch.
+pub const MIN_SNAPSHOTS_TO_KEEP: &str = "history.expire.min-snapshots-to-keep";
+/// The default minimum number of snapshots to keep in a branch.
+pub const MIN_SNAPSHOTS_TO_KEEP_DEFAULT: i32 = 1;
+/// Table property of the default maximum age of refs to keep.
+pub const MAX_REF_AGE_MS: &str = "history.expire.max-ref-age-ms";
+/// The default maximum age of refs to keep, forever.
+pub const MAX_REF_AGE_MS_DEFAULT: i64 = i64::MAX;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The snapshots and refs to remove from a table.
+pub struct ExpirationPlan {
+    /// Ids of the snapshots to remove, in the order of the table's snapshots.
+    pub expired_snapshot_ids: Vec<i64>,
+    /// Names of the refs to remove, sorted.
+    pub removed_refs: Vec<String>,
+}
+
+struct Defaults {
+    max_snapshot_age_ms: i64,
+    min_snapshots_to_keep: i32,
+    max_ref_age_ms: i64,
+}
+
+fn property<T: std::str::FromStr>(metadata: &TableMetadataV2, key: &str, default: T) -> Result<T> {
+    match metadata.properties.as_ref().and_then(|p| p.get(key)) {
+        None => Ok(default),
+        Some(value) => value
+            .parse()
+            .map_err(|_| Error::Invalid(format!("table property {key} is not a number: {value}"))),
+    }
+}
+
+/// `value` if it is set, that is positive, or `default` otherwise.
+fn or_default<T: Default + PartialOrd>(value: T, default: T) -> T {
+    if value > T::default() {
+        value
+    } else {
+        default
+    }
+}
+
+impl TableMetadataV2 {
+    /// Plan the expiration of snapshots and refs at `now_ms`, in milliseconds from the unix epoch.
+    pub fn plan_expiration(&self, now_ms: i64) -> Result<ExpirationPlan> {
+        let defaults = Defaults {
+            max_snapshot_age_ms: property(self, MAX_SNAPSHOT_AGE_MS, MAX_SNAPSHOT_AGE_MS_DEFAULT)?,
+            min_snapshots_to_keep: property(
+                self,
+                MIN_SNAPSHOTS_TO_KEEP,
+                MIN_SNAPSHOTS_TO_KEEP_DEFAULT,
+            )?,
+            max_ref_age_ms: property(self, MAX_REF_AGE_MS, MAX_REF_AGE_MS_DEFAULT)?,
+        };
+        let main = Retention::Branch {
+            min_snapshots_to_keep: 0,
+            max_snapshot_age_ms: 0,
+            max_ref_age_ms: 0,
+        };
+        let mut refs: HashMap<&str, (i64, &Retention)> = self
+            .refs
+            .iter()
+            .flatten()
+            .map(|(name, r)| (name.as_str(), (r.snapshot_id, &r.retention)))
+            .collect();
+        if let Some(current) = self.current_snapshot_id {
+            refs.entry(MAIN_BRANCH).or_insert((current, &main));
+        }
+
+        let mut removed_refs = vec![];
+        let mut retained = HashSet::new();
+        let mut referenced = HashSet::new();
+        for (name, (snapshot_id, retention)) in refs {
+            let max_ref_age_ms = match retention {
+                Retention::Branch { max_ref_age_ms, .. } | Retention::Tag { max_ref_age_ms } => {
+                    or_default(*max_ref_age_ms, defaults.max_ref_age_ms)
+                }
+            };
+            let expired = match self.snapshot(snapshot_id) {
+                Some(snapshot) => now_ms.saturating_sub(snapshot.timestamp_ms) > max_ref_age_ms,
+                None => true,
+            };
+            if name != MAIN_BRANCH && expired {
+                removed_refs.push(name.to_string());
+                continue;
+            }
+            referenced.extend(self.ancestors(snapshot_id).map(|s| s.snapshot_id));
+            // The snapshot a ref points to is always kept, whatever its retention settings.
+            retained.insert(snapshot_id);
+            match retention {
+                Retention::Tag { .. } => (),
+                Retention::Branch {
+                    min_snapshots_to_keep,
+                    max_snapshot_age_ms,
+                    ..
+                } => {
+                    let min_snapshots_to_keep =
+                        or_default(*min_snapshots_to_keep, defaults.min_snapshots_to_keep);
+                    let expire_older_than = now_ms.saturating_sub(or_default(
+                        *max_snapshot_age_ms,
+                        defaults.max_snapshot_age_ms,
+                    ));
+                    retained.extend(
+                        self.ancestors(snapshot_id)
+                            .enumerate()
+                            .take_while(|(kept, snapshot)| {
+                                (*kept as i64) < min_snapshots_to_keep as i64
+                                    || snapshot.timestamp_ms >= expire_older_than
+                            })
+                            .map(|(_, snapshot)| snapshot.snapshot_id),
+                    );
+                }
+            }
+        }
+        removed_refs.sort();
+
+        let expire_older_than = now_ms.saturating_sub(defaults.max_snapshot_age_ms);
+        let expired_snapshot_ids = self
+            .snapshots
+            .iter()
+            .flatten()
+            .filter(|s| {
+                !retained.contains(&s.snapshot_id)
+                    && (referenced.contains(&s.snapshot_id) || s.timestamp_ms < expire_older_than)
+            })
+            .map(|s| s.snapshot_id)
+            .collect();
+        Ok(ExpirationPlan {
+            expired_snapshot_ids,
+            removed_refs,
+        })
+    }
+}
+
+impl ExpirationPlan {
+    /// Remove the expired snapshots and refs from `metadata`. Entries of the snapshot log up
+    /// to the last expired snapshot are removed as well.
+    pub fn apply(&self, mut metadata: TableMetadataV2) -> TableMetadataV2 {
+        let expired: HashSet<i64> = self.expired_snapshot_ids.iter().copied().collect();
+        if let Some(snapshots) = metadata.snapshots.as_mut() {
+            snapshots.retain(|s| !expired.contains(&s.snapshot_id));
+        }
+        if let Some(refs) = metadata.refs.as_mut() {
+            refs.retain(|name, _| !self.removed_refs.contains(name));
+        }
+        if let Some(log) = metadata.snapshot_log.as_mut() {
+            if let Some(last) = log.iter().rposition(|e| expired.contains(&e.snapshot_id)) {
+                log.drain(..=last);
+            }
+        }
+        metadata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::tests::{branch, metadata_with_snapshots, snapshot_log, tag};
+
+    /// Snapshots 1 <- 2 <- 3 <- 5 with main at 3, the tag v1 at 1 and the branch old at 4,
+    /// a child of 2. Snapshot 6 has no parent and no ref.
+    fn metadata() -> TableMetadataV2 {
+        let mut metadata = metadata_with_snapshots(&[
+            (1, None, 1000),
+            (2, Some(1), 2000),
+            (3, Some(2), 10_000),
+            (4, Some(2), 3000),
+            (5, Some(3), 19_000),
+            (6, None, 1500),
+        ]);
+        metadata.current_snapshot_id = Some(3);
+        metadata.properties = Some(HashMap::from([(
+            MAX_SNAPSHOT_AGE_MS.to_string(),
+            "5000".to_string(),
+        )]));
+        metadata.snapshot_log = snapshot_log(&[(1, 1000), (2, 2000), (6, 2500), (3, 10_000)]);
+        metadata.refs = Some(HashMap::from([
+            (MAIN_BRANCH.to_string(), branch(3, (2, 0, 0))),
+            ("old".to_string(), branch(4, (1, 100000, 5000))),
+            ("v1".to_string(), tag(1, 100000)),
+        ]));
+        metadata
+    }
+
+    #[test]
+    fn test_plan_expiration() {
+        let metadata = metadata();
+        let plan = metadata.plan_expiration(20_000).unwrap();
+        assert_eq!(
+            ExpirationPlan {
+                expired_snapshot_ids: vec![4, 6],
+                removed_refs: vec!["old".to_string()],
+            },
+            plan
+        );
+
+        let metadata = plan.apply(metadata);
+        let ids: Vec<i64> = metadata
+            .snapshots
+            .iter()
+            .flatten()
+            .map(|s| s.snapshot_id)
+            .collect();
+        assert_eq!(vec![1, 2, 3, 5], ids);
+        let mut refs: Vec<&String> = metadata.refs.iter().flatten().map(|(n, _)| n).collect();
+        refs.sort();
+        assert_eq!(vec!["main", "v1"], refs);
+        let log: Vec<i64> = metadata
+            .snapshot_log
+            .iter()
+            .flatten()
+            .map(|e| e.snapshot_id)
+            .collect();
+        assert_eq!(vec![3], log);
+    }
+
+    #[test]
+    fn test_table_defaults() {
+        let mut metadata = metadata();
+        metadata.refs = None;
+        metadata.properties = Some(HashMap::from([
+            (MAX_SNAPSHOT_AGE_MS.to_string(), "1000".to_string()),
+            (MIN_SNAPSHOTS_TO_KEEP.to_string(), "3".to_string()),
+        ]));
+        // Without refs the main branch keeps three snapshots, and the other snapshots are old.
+        assert_eq!(
+            vec![4, 6],
+            metadata
+                .plan_expiration(20_000)
+                .unwrap()
+                .expired_snapshot_ids
+        );
+        // Snapshots reachable from main but too old are expired.
+        metadata.properties = Some(HashMap::from([(
+            MAX_SNAPSHOT_AGE_MS.to_string(),
+            "1000".to_string(),
+        )]));
+        assert_eq!(
+            vec![1, 2, 4, 6],
+            metadata
+                .plan_expiration(20_000)
+                .unwrap()
+                .expired_snapshot_ids
+        );
+
+        // The head of a branch is kept even if no snapshots have to be kept.
+        let mut two = metadata_with_snapshots(&[(1, None, 1000), (2, Some(1), 2000)]);
+        two.properties = Some(HashMap::from([
+            (MAX_SNAPSHOT_AGE_MS.to_string(), "1000".to_string()),
+            (MIN_SNAPSHOTS_TO_KEEP.to_string(), "0".to_string()),
+        ]));
+        let plan = two.plan_expiration(20_000).unwrap();
+        assert_eq!(vec![1], plan.expired_snapshot_ids);
+        let two = plan.apply(two);
+        assert_eq!(Some(2), two.current_snapshot().map(|s| s.snapshot_id));
+
+        metadata.properties = Some(HashMap::from([(
+            MIN_SNAPSHOTS_TO_KEEP.to_string(),
+            "many".to_string(),
+        )]));
+        assert!(matches!(
+            metadata.plan_expiration(20_000),
+            Err(Error::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+/*!
+Boolean expressions over the fields of a table, used to filter rows and to prune partitions.
+
+An [Expression] combines [Predicate]s with `and`, `or` and `not`. Predicates refer to fields by
+id, so an expression over the columns of a schema can be projected to an expression over the
+partition fields of a spec (see [projection](crate::model::projection)).
+*/
+use crate::model::values::Value;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Operators that test a field without a literal.
+pub enum UnaryOperator {
+    /// The value is null.
+    IsNull,
+    /// The value is not null.
+    NotNull,
+    /// The value is NaN.
+    IsNan,
+    /// The value is not NaN.
+    NotNan,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Operators that compare a field to a literal.
+pub enum BinaryOperator {
+    /// The value is less than the literal.
+    LessThan,
+    /// The value is less than or equal to the literal.
+    LessThanOrEq,
+    /// The value is greater than the literal.
+    GreaterThan,
+    /// The value is greater than or equal to the literal.
+    GreaterThanOrEq,
+    /// The value is equal to the literal.
+    Eq,
+    /// The value is not equal to the literal.
+    NotEq,
+    /// The value starts with the literal.
+    StartsWith,
+    /// The value does not start with the literal.
+    NotStartsWith,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Operators that compare a field to a set of literals.
+pub enum SetOperator {
+    /// The value is one of the literals.
+    In,
+    /// The value is none of the literals.
+    NotIn,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A test of the value of a single field.
+pub enum Predicate {
+    /// A test without a literal.
+    Unary {
+        /// The test.
+        op: UnaryOperator,
+        /// The id of the tested field.
+        field_id: i32,
+    },
+    /// A comparison to a literal.
+    Binary {
+        /// The comparison.
+        op: BinaryOperator,
+        /// The id of the tested field.
+        field_id: i32,
+        /// The literal the value is compared to.
+        literal: Value,
+    },
+    /// A comparison to a set of literals.
+    Set {
+        /// The comparison.
+        op: SetOperator,
+        /// The id of the tested field.
+        field_id: i32,
+        /// The literals the value is compared to.
+        literals: Vec<Value>,
+    },
+}
+
+impl Predicate {
+    /// The id of the tested field.
+    pub fn field_id(&self) -> i32 {
+        match self {
+            Predicate::Unary { field_id, .. }
+            | Predicate::Binary { field_id, .. }
+            | Predicate::Set { field_id, .. } => *field_id,
+        }
+    }
+
+    /// The predicate that is true exactly when this one is false.
+    pub fn negate(&self) -> Predicate {
+        match self.clone() {
+            Predicate::Unary { op, field_id } => Predicate::Unary {
+                op: match op {
+                    UnaryOperator::IsNull => UnaryOperator::NotNull,
+                    UnaryOperator::NotNull => UnaryOperator::IsNull,
+                    UnaryOperator::IsNan => UnaryOperator::NotNan,
+                    UnaryOperator::NotNan => UnaryOperator::IsNan,
+                },
+                field_id,
+            },
+            Predicate::Binary {
+                op,
+                field_id,
+                literal,
+            } => Predicate::Binary {
+                op: match op {
+                    BinaryOperator::LessThan => BinaryOperator::GreaterThanOrEq,
+                    BinaryOperator::LessThanOrEq => BinaryOperator::GreaterThan,
+                    BinaryOperator::GreaterThan => BinaryOperator::LessThanOrEq,
+                    BinaryOperator::GreaterThanOrEq => BinaryOperator::LessThan,
+                    BinaryOperator::Eq => BinaryOperator::NotEq,
+                    BinaryOperator::NotEq => BinaryOperator::Eq,
+                    BinaryOperator::StartsWith => BinaryOperator::NotStartsWith,
+                    BinaryOperator::NotStartsWith => BinaryOperator::StartsWith,
+                },
+                field_id,
+                literal,
+            },
+            Predicate::Set {
+                op,
+                field_id,
+                literals,
+            } => Predicate::Set {
+                op: match op {
+                    SetOperator::In => SetOperator::NotIn,
+                    SetOperator::NotIn => SetOperator::In,
+                },
+                field_id,
+                literals,
+            },
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A boolean expression of predicates.
+pub enum Expression {
+    /// True for every row.
+    AlwaysTrue,
+    /// False for every row.
+    AlwaysFalse,
+    /// True when both expressions are true.
+    And(Box<Expression>, Box<Expression>),
+    /// True when either expression is true.
+    Or(Box<Expression>, Box<Expression>),
+    /// True when the expression is false.
+    Not(Box<Expression>),
+    /// A test of a single field.
+    Predicate(Predicate),
+}
+
+impl From<Predicate> for Expression {
+    fn from(predicate: Predicate) -> Self {
+        Expression::Predicate(predicate)
+    }
+}
+
+impl Expression {
+    /// The conjunction of two expressions, simplified if either is always true or false.
+    pub fn and(self, other: Expression) -> Expression {
+        match (self, other) {
+            (Expression::AlwaysFalse, _) | (_, Expression::AlwaysFalse) => Expression::AlwaysFalse,
+            (Expression::AlwaysTrue, e) | (e, Expression::AlwaysTrue) => e,
+            (left, right) => Expression::And(Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// The disjunction of two expressions, simplified if either is always true or false.
+    pub fn or(self, other: Expression) -> Expression {
+        match (self, other) {
+            (Expression::AlwaysTrue, _) | (_, Expression::AlwaysTrue) => Expression::AlwaysTrue,
+            (Expression::AlwaysFalse, e) | (e, Expression::AlwaysFalse) => e,
+            (left, right) => Expression::Or(Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// The negation of the expression, simplified if it is always true or false.
+    pub fn negate(self) -> Expression {
+        match self {
+            Expression::AlwaysTrue => Expression::AlwaysFalse,
+            Expression::AlwaysFalse => Expression::AlwaysTrue,
+            Expression::Not(e) => *e,
+            e => Expression::Not(Box::new(e)),
+        }
+    }
+
+    /// An equivalent expression without `not`, found by negating predicates.
+    pub fn rewrite_not(self) -> Expression {
+        match self {
+            Expression::And(left, right) => left.rewrite_not().and(right.rewrite_not()),
+            Expression::Or(left, right) => left.rewrite_not().or(right.rewrite_not()),
+            Expression::Not(e) => match *e {
+                Expression::AlwaysTrue => Expression::AlwaysFalse,
+                Expression::AlwaysFalse => Expression::AlwaysTrue,
+                Expression::And(left, right) => left.negate().or(right.negate()).rewrite_not(),
+                Expression::Or(left, right) => left.negate().and(right.negate()).rewrite_not(),
+                Expression::Not(e) => e.rewrite_not(),
+                Expression::Predicate(p) => Expression::Predicate(p.negate()),
+            },
+            e => e,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn less_than(field_id: i32, v: i32) -> Expression {
+        Predicate::Binary {
+            op: BinaryOperator::LessThan,
+            field_id,
+            literal: Value::Int(v),
+        }
+        .into()
+    }
+
+    fn greater_than_or_eq(field_id: i32, v: i32) -> Expression {
+        Predicate::Binary {
+            op: BinaryOperator::GreaterThanOrEq,
+            field_id,
+            literal: Value::Int(v),
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_simplify() {
+        let e = less_than(1, 5);
+        assert_eq!(e, e.clone().and(Expression::AlwaysTrue));
+        assert_eq!(
+            Expression::AlwaysFalse,
+            e.clone().and(Expression::AlwaysFalse)
+        );
+        assert_eq!(e, Expression::AlwaysFalse.or(e.clone()));
+        assert_eq!(Expression::AlwaysTrue, e.clone().or(Expression::AlwaysTrue));
+        assert_eq!(e, e.clone().negate().negate());
+    }
+
+    #[test]
+    fn test_rewrite_not() {
+        let e = less_than(1, 5).and(less_than(2, 3)).negate();
+        assert_eq!(
+            greater_than_or_eq(1, 5).or(greater_than_or_eq(2, 3)),
+            e.rewrite_not()
+        );
+        let in_set = Predicate::Set {
+            op: SetOperator::In,
+            field_id: 1,
+            literals: vec![Value::Int(1)],
+        };
+        assert_eq!(
+            Expression::Predicate(Predicate::Set {
+                op: SetOperator::NotIn,
+                field_id: 1,
+                literals: vec![Value::Int(1)],
+            }),
+            Expression::from(in_set).negate().rewrite_not()
+        );
+    }
+}
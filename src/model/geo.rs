@@ -0,0 +1,832 @@
+/*!
+Handling of [geospatial](https://iceberg.apache.org/spec/#primitive-types) values.
+
+Values of the [Geometry](crate::model::schema::PrimitiveType::Geometry) and
+[Geography](crate::model::schema::PrimitiveType::Geography) types are stored as
+[well-known binary](https://libgeos.org/specifications/wkb/) (WKB), which [Geometry::from_wkb]
+reads and [Geometry::to_wkb] writes.
+
+Column lower and upper bounds of geospatial types are the corners of a [BoundingBox], each
+serialised as a point of concatenated little-endian coordinates. File pruning compares the
+bounding box of a query with the box described by the bounds using [BoundingBox::intersects].
+*/
+use crate::error::{Error, Result};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+/// A position with optional elevation (`z`) and measure (`m`) values.
+pub struct Coordinate {
+    /// Easting or longitude
+    pub x: f64,
+    /// Northing or latitude
+    pub y: f64,
+    /// Elevation
+    pub z: Option<f64>,
+    /// Measure
+    pub m: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// A geometry from the OGC Simple Feature Access model.
+pub enum Geometry {
+    /// A single position, `None` for the empty point.
+    Point(Option<Coordinate>),
+    /// A sequence of positions joined by edges.
+    LineString(Vec<Coordinate>),
+    /// An exterior ring followed by any interior rings.
+    Polygon(Vec<Vec<Coordinate>>),
+    /// A collection of points.
+    MultiPoint(Vec<Geometry>),
+    /// A collection of line strings.
+    MultiLineString(Vec<Geometry>),
+    /// A collection of polygons.
+    MultiPolygon(Vec<Geometry>),
+    /// A collection of any geometries.
+    GeometryCollection(Vec<Geometry>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Dimensions {
+    z: bool,
+    m: bool,
+}
+
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WkbReader<'a> {
+    fn read<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + N)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| Error::Invalid("WKB buffer too short".to_string()))?;
+        self.pos += N;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self, little_endian: bool) -> Result<u32> {
+        let bytes = self.read::<4>()?;
+        Ok(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_f64(&mut self, little_endian: bool) -> Result<f64> {
+        let bytes = self.read::<8>()?;
+        Ok(if little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_coordinate(&mut self, little_endian: bool, dims: Dimensions) -> Result<Coordinate> {
+        Ok(Coordinate {
+            x: self.read_f64(little_endian)?,
+            y: self.read_f64(little_endian)?,
+            z: if dims.z {
+                Some(self.read_f64(little_endian)?)
+            } else {
+                None
+            },
+            m: if dims.m {
+                Some(self.read_f64(little_endian)?)
+            } else {
+                None
+            },
+        })
+    }
+
+    fn read_coordinates(
+        &mut self,
+        little_endian: bool,
+        dims: Dimensions,
+    ) -> Result<Vec<Coordinate>> {
+        let count = self.read_u32(little_endian)?;
+        (0..count)
+            .map(|_| self.read_coordinate(little_endian, dims))
+            .collect()
+    }
+
+    fn read_geometry(&mut self) -> Result<Geometry> {
+        let little_endian = match self.read::<1>()?[0] {
+            0 => false,
+            1 => true,
+            b => return Err(Error::Invalid(format!("invalid WKB byte order {b}"))),
+        };
+        let raw_type = self.read_u32(little_endian)?;
+        if raw_type & EWKB_SRID != 0 {
+            // The SRID of extended WKB is not part of the value.
+            self.read_u32(little_endian)?;
+        }
+        let iso_type = raw_type & 0x0fff_ffff;
+        let dims = Dimensions {
+            z: raw_type & EWKB_Z != 0 || matches!(iso_type / 1000, 1 | 3),
+            m: raw_type & EWKB_M != 0 || matches!(iso_type / 1000, 2 | 3),
+        };
+        let read_collection = |reader: &mut Self| -> Result<Vec<Geometry>> {
+            let count = reader.read_u32(little_endian)?;
+            (0..count).map(|_| reader.read_geometry()).collect()
+        };
+        Ok(match iso_type % 1000 {
+            1 => {
+                let coordinate = self.read_coordinate(little_endian, dims)?;
+                // Empty points are written with NaN coordinates.
+                if coordinate.x.is_nan() && coordinate.y.is_nan() {
+                    Geometry::Point(None)
+                } else {
+                    Geometry::Point(Some(coordinate))
+                }
+            }
+            2 => Geometry::LineString(self.read_coordinates(little_endian, dims)?),
+            3 => {
+                let rings = self.read_u32(little_endian)?;
+                Geometry::Polygon(
+                    (0..rings)
+                        .map(|_| self.read_coordinates(little_endian, dims))
+                        .collect::<Result<_>>()?,
+                )
+            }
+            4 => Geometry::MultiPoint(read_collection(self)?),
+            5 => Geometry::MultiLineString(read_collection(self)?),
+            6 => Geometry::MultiPolygon(read_collection(self)?),
+            7 => Geometry::GeometryCollection(read_collection(self)?),
+            t => return Err(Error::Unsupported(format!("WKB geometry type {t}"))),
+        })
+    }
+}
+
+impl Geometry {
+    /// Read a geometry from ISO or extended WKB.
+    pub fn from_wkb(wkb: &[u8]) -> Result<Geometry> {
+        let mut reader = WkbReader { bytes: wkb, pos: 0 };
+        let geometry = reader.read_geometry()?;
+        if reader.pos != wkb.len() {
+            return Err(Error::Invalid(
+                "trailing bytes after WKB geometry".to_string(),
+            ));
+        }
+        Ok(geometry)
+    }
+
+    /// Write the geometry as little-endian ISO WKB.
+    pub fn to_wkb(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.write_wkb(&mut out);
+        out
+    }
+
+    fn dimensions(&self) -> Dimensions {
+        let mut dims = Dimensions { z: false, m: false };
+        self.for_each_coordinate(&mut |c| {
+            dims.z |= c.z.is_some();
+            dims.m |= c.m.is_some();
+        });
+        dims
+    }
+
+    fn write_wkb(&self, out: &mut Vec<u8>) {
+        let dims = self.dimensions();
+        let base = match self {
+            Geometry::Point(_) => 1,
+            Geometry::LineString(_) => 2,
+            Geometry::Polygon(_) => 3,
+            Geometry::MultiPoint(_) => 4,
+            Geometry::MultiLineString(_) => 5,
+            Geometry::MultiPolygon(_) => 6,
+            Geometry::GeometryCollection(_) => 7,
+        };
+        let offset = match (dims.z, dims.m) {
+            (false, false) => 0,
+            (true, false) => 1000,
+            (false, true) => 2000,
+            (true, true) => 3000,
+        };
+        out.push(1);
+        out.extend_from_slice(&(base + offset as u32).to_le_bytes());
+        let write_coordinate = |out: &mut Vec<u8>, c: &Coordinate| {
+            out.extend_from_slice(&c.x.to_le_bytes());
+            out.extend_from_slice(&c.y.to_le_bytes());
+            if dims.z {
+                out.extend_from_slice(&c.z.unwrap_or(f64::NAN).to_le_bytes());
+            }
+            if dims.m {
+                out.extend_from_slice(&c.m.unwrap_or(f64::NAN).to_le_bytes());
+            }
+        };
+        let write_coordinates = |out: &mut Vec<u8>, cs: &[Coordinate]| {
+            out.extend_from_slice(&(cs.len() as u32).to_le_bytes());
+            for c in cs {
+                write_coordinate(out, c);
+            }
+        };
+        match self {
+            Geometry::Point(Some(c)) => write_coordinate(out, c),
+            Geometry::Point(None) => {
+                let empty = Coordinate {
+                    x: f64::NAN,
+                    y: f64::NAN,
+                    z: None,
+                    m: None,
+                };
+                write_coordinate(out, &empty)
+            }
+            Geometry::LineString(cs) => write_coordinates(out, cs),
+            Geometry::Polygon(rings) => {
+                out.extend_from_slice(&(rings.len() as u32).to_le_bytes());
+                for ring in rings {
+                    write_coordinates(out, ring);
+                }
+            }
+            Geometry::MultiPoint(gs)
+            | Geometry::MultiLineString(gs)
+            | Geometry::MultiPolygon(gs)
+            | Geometry::GeometryCollection(gs) => {
+                out.extend_from_slice(&(gs.len() as u32).to_le_bytes());
+                for g in gs {
+                    g.write_wkb(out);
+                }
+            }
+        }
+    }
+
+    fn for_each_coordinate(&self, f: &mut impl FnMut(&Coordinate)) {
+        match self {
+            Geometry::Point(c) => c.iter().for_each(f),
+            Geometry::LineString(cs) => cs.iter().for_each(f),
+            Geometry::Polygon(rings) => rings.iter().flatten().for_each(f),
+            Geometry::MultiPoint(gs)
+            | Geometry::MultiLineString(gs)
+            | Geometry::MultiPolygon(gs)
+            | Geometry::GeometryCollection(gs) => {
+                for g in gs {
+                    g.for_each_coordinate(f)
+                }
+            }
+        }
+    }
+
+    /// The smallest box containing every coordinate of the geometry, or `None` if it is empty.
+    /// Geography values use [Geometry::geography_bounding_box] instead.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut bbox: Option<BoundingBox> = None;
+        self.for_each_coordinate(&mut |c| match &mut bbox {
+            Some(b) => b.expand(c),
+            None => bbox = Some(BoundingBox::from_coordinate(c)),
+        });
+        bbox
+    }
+
+    /// Calls `f` with each point of the geometry as a zero length edge and with each pair of
+    /// consecutive coordinates of its lines and rings.
+    fn for_each_edge(&self, f: &mut impl FnMut(&Coordinate, &Coordinate)) {
+        let mut edges = |cs: &[Coordinate]| match cs {
+            [c] => f(c, c),
+            _ => cs.windows(2).for_each(|w| f(&w[0], &w[1])),
+        };
+        match self {
+            Geometry::Point(c) => c.iter().for_each(|c| edges(std::slice::from_ref(c))),
+            Geometry::LineString(cs) => edges(cs),
+            Geometry::Polygon(rings) => rings.iter().for_each(|ring| edges(ring)),
+            Geometry::MultiPoint(gs)
+            | Geometry::MultiLineString(gs)
+            | Geometry::MultiPolygon(gs)
+            | Geometry::GeometryCollection(gs) => {
+                for g in gs {
+                    g.for_each_edge(f)
+                }
+            }
+        }
+    }
+
+    /// Whether a polygon of the geometry contains the north and the south pole. A pole is
+    /// inside a polygon when its exterior ring winds around it with the interior on the left.
+    fn enclosed_poles(&self, north: &mut bool, south: &mut bool) {
+        match self {
+            Geometry::Polygon(rings) => {
+                let winding: f64 = rings.first().map_or(0.0, |ring| {
+                    ring.windows(2)
+                        .map(|w| longitude_difference(w[0].x, w[1].x))
+                        .sum()
+                });
+                *north |= winding > 180.0;
+                *south |= winding < -180.0;
+            }
+            Geometry::MultiPolygon(gs) | Geometry::GeometryCollection(gs) => {
+                for g in gs {
+                    g.enclosed_poles(north, south)
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// The bounding box of the geometry as a geography, with longitudes in degrees from -180
+    /// to 180 and edges following great circles.
+    ///
+    /// The box covers the whole of every edge, which may reach further from the equator than
+    /// its ends, and of every polygon containing a pole. When the geometry is closer together
+    /// across the antimeridian than within `[-180, 180]`, the box wraps around it and `x_min`
+    /// is greater than `x_max`.
+    pub fn geography_bounding_box(&self) -> Option<BoundingBox> {
+        let mut bbox = self.bounding_box()?;
+        let mut arcs = vec![];
+        self.for_each_edge(&mut |a, b| {
+            let (y_min, y_max) = arc_latitudes(a, b);
+            bbox.y_min = bbox.y_min.min(y_min);
+            bbox.y_max = bbox.y_max.max(y_max);
+            // Each edge covers the shorter way between the longitudes of its ends.
+            let d = longitude_difference(a.x, b.x);
+            let (start, end) = if d >= 0.0 {
+                (a.x, a.x + d)
+            } else {
+                (b.x, b.x - d)
+            };
+            if end > 180.0 {
+                arcs.push((start, 180.0));
+                arcs.push((-180.0, end - 360.0));
+            } else {
+                arcs.push((start, end));
+            }
+        });
+        let (mut north, mut south) = (false, false);
+        self.enclosed_poles(&mut north, &mut south);
+        if north || south {
+            bbox.y_max = if north { 90.0 } else { bbox.y_max };
+            bbox.y_min = if south { -90.0 } else { bbox.y_min };
+            bbox.x_min = -180.0;
+            bbox.x_max = 180.0;
+            return Some(bbox);
+        }
+
+        arcs.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut merged: Vec<(f64, f64)> = vec![];
+        for (start, end) in arcs {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        // The widest gap between the covered longitudes is left out of the box. The box only
+        // wraps if that gap is wider than the one across the antimeridian.
+        let wrapping_gap = merged[0].0 + 360.0 - merged[merged.len() - 1].1;
+        let widest = merged
+            .windows(2)
+            .map(|w| (w[1].0 - w[0].1, w[0].1, w[1].0))
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        match widest {
+            Some((gap, before, after)) if gap > wrapping_gap => {
+                bbox.x_min = after;
+                bbox.x_max = before;
+            }
+            _ => {
+                bbox.x_min = merged[0].0;
+                bbox.x_max = merged[merged.len() - 1].1;
+            }
+        }
+        Some(bbox)
+    }
+}
+
+/// The difference from longitude `from` to `to` the shorter way round, in `(-180, 180]`.
+fn longitude_difference(from: f64, to: f64) -> f64 {
+    let d = (to - from).rem_euclid(360.0);
+    if d > 180.0 {
+        d - 360.0
+    } else {
+        d
+    }
+}
+
+/// The lowest and highest latitude of the great circle arc from `a` to `b`.
+fn arc_latitudes(a: &Coordinate, b: &Coordinate) -> (f64, f64) {
+    let (mut low, mut high) = (a.y.min(b.y), a.y.max(b.y));
+    let unit = |c: &Coordinate| {
+        let (x, y) = (c.x.to_radians(), c.y.to_radians());
+        [y.cos() * x.cos(), y.cos() * x.sin(), y.sin()]
+    };
+    let cross = |u: [f64; 3], v: [f64; 3]| {
+        [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ]
+    };
+    let dot = |u: [f64; 3], v: [f64; 3]| u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+    let (p, q) = (unit(a), unit(b));
+    let n = cross(p, q);
+    // The great circle is highest at the point nearest the north pole and lowest at the
+    // opposite point. Either only bounds the arc if it lies between `p` and `q`.
+    let top = [-n[0] * n[2], -n[1] * n[2], n[0] * n[0] + n[1] * n[1]];
+    if dot(top, top) < 1e-24 {
+        return (low, high);
+    }
+    let latitude = top[2].atan2(top[0].hypot(top[1])).to_degrees();
+    let between = |v: [f64; 3]| dot(cross(p, v), n) > 0.0 && dot(cross(v, q), n) > 0.0;
+    if between(top) {
+        high = high.max(latitude);
+    }
+    if between(top.map(|t| -t)) {
+        low = low.min(-latitude);
+    }
+    (low, high)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+/// An axis aligned box used for the lower and upper bounds of geospatial columns.
+///
+/// For geography columns `x_min` may be greater than `x_max`, in which case the box
+/// wraps around the antimeridian and covers `[x_min, 180]` and `[-180, x_max]`.
+pub struct BoundingBox {
+    /// Lowest x
+    pub x_min: f64,
+    /// Lowest y
+    pub y_min: f64,
+    /// Lowest z, if the values have elevation.
+    pub z_min: Option<f64>,
+    /// Lowest m, if the values have measures.
+    pub m_min: Option<f64>,
+    /// Highest x
+    pub x_max: f64,
+    /// Highest y
+    pub y_max: f64,
+    /// Highest z, if the values have elevation.
+    pub z_max: Option<f64>,
+    /// Highest m, if the values have measures.
+    pub m_max: Option<f64>,
+}
+
+impl BoundingBox {
+    fn from_coordinate(c: &Coordinate) -> BoundingBox {
+        BoundingBox {
+            x_min: c.x,
+            y_min: c.y,
+            z_min: c.z,
+            m_min: c.m,
+            x_max: c.x,
+            y_max: c.y,
+            z_max: c.z,
+            m_max: c.m,
+        }
+    }
+
+    fn expand(&mut self, c: &Coordinate) {
+        let min = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let max = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.x_min = self.x_min.min(c.x);
+        self.y_min = self.y_min.min(c.y);
+        self.x_max = self.x_max.max(c.x);
+        self.y_max = self.y_max.max(c.y);
+        self.z_min = min(self.z_min, c.z);
+        self.z_max = max(self.z_max, c.z);
+        self.m_min = min(self.m_min, c.m);
+        self.m_max = max(self.m_max, c.m);
+    }
+
+    /// Extend the box to contain `other`.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let mut result = *self;
+        result.expand(&other.lower());
+        result.expand(&other.upper());
+        result
+    }
+
+    fn lower(&self) -> Coordinate {
+        Coordinate {
+            x: self.x_min,
+            y: self.y_min,
+            z: self.z_min,
+            m: self.m_min,
+        }
+    }
+
+    fn upper(&self) -> Coordinate {
+        Coordinate {
+            x: self.x_max,
+            y: self.y_max,
+            z: self.z_max,
+            m: self.m_max,
+        }
+    }
+
+    /// The serialised lower bound of the box.
+    pub fn lower_bound(&self) -> Vec<u8> {
+        point_to_bound(&self.lower())
+    }
+
+    /// The serialised upper bound of the box.
+    pub fn upper_bound(&self) -> Vec<u8> {
+        point_to_bound(&self.upper())
+    }
+
+    /// Read a box from serialised lower and upper bounds.
+    pub fn from_bounds(lower: &[u8], upper: &[u8]) -> Result<BoundingBox> {
+        let lower = bound_to_point(lower)?;
+        let upper = bound_to_point(upper)?;
+        Ok(BoundingBox {
+            x_min: lower.x,
+            y_min: lower.y,
+            z_min: lower.z,
+            m_min: lower.m,
+            x_max: upper.x,
+            y_max: upper.y,
+            z_max: upper.z,
+            m_max: upper.m,
+        })
+    }
+
+    /// Returns true if the boxes overlap. The `z` and `m` ranges are only compared when
+    /// both boxes have them.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        let overlaps =
+            |a_min: f64, a_max: f64, b_min: f64, b_max: f64| a_min <= b_max && b_min <= a_max;
+        let optional_overlaps = |a_min: Option<f64>, a_max, b_min: Option<f64>, b_max| match (
+            a_min, a_max, b_min, b_max,
+        ) {
+            (Some(a_min), Some(a_max), Some(b_min), Some(b_max)) => {
+                overlaps(a_min, a_max, b_min, b_max)
+            }
+            _ => true,
+        };
+        let x_ranges = |b: &BoundingBox| {
+            if b.x_min <= b.x_max {
+                vec![(b.x_min, b.x_max)]
+            } else {
+                vec![(b.x_min, f64::INFINITY), (f64::NEG_INFINITY, b.x_max)]
+            }
+        };
+        let x_overlaps = x_ranges(self).iter().any(|(a_min, a_max)| {
+            x_ranges(other)
+                .iter()
+                .any(|(b_min, b_max)| overlaps(*a_min, *a_max, *b_min, *b_max))
+        });
+        x_overlaps
+            && overlaps(self.y_min, self.y_max, other.y_min, other.y_max)
+            && optional_overlaps(self.z_min, self.z_max, other.z_min, other.z_max)
+            && optional_overlaps(self.m_min, self.m_max, other.m_min, other.m_max)
+    }
+}
+
+/// Serialise a bound point as `x:y`, `x:y:z`, `x:y:NaN:m` or `x:y:z:m` little-endian doubles.
+fn point_to_bound(c: &Coordinate) -> Vec<u8> {
+    let mut out = Vec::with_capacity(32);
+    out.extend_from_slice(&c.x.to_le_bytes());
+    out.extend_from_slice(&c.y.to_le_bytes());
+    match (c.z, c.m) {
+        (None, None) => (),
+        (Some(z), None) => out.extend_from_slice(&z.to_le_bytes()),
+        (z, Some(m)) => {
+            out.extend_from_slice(&z.unwrap_or(f64::NAN).to_le_bytes());
+            out.extend_from_slice(&m.to_le_bytes());
+        }
+    }
+    out
+}
+
+fn bound_to_point(bytes: &[u8]) -> Result<Coordinate> {
+    let coordinates: Vec<f64> = bytes
+        .chunks(8)
+        .map(|c| c.try_into().map(f64::from_le_bytes))
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| Error::Invalid(format!("invalid geospatial bound {bytes:?}")))?;
+    match coordinates[..] {
+        [x, y] => Ok(Coordinate {
+            x,
+            y,
+            z: None,
+            m: None,
+        }),
+        [x, y, z] => Ok(Coordinate {
+            x,
+            y,
+            z: Some(z),
+            m: None,
+        }),
+        [x, y, z, m] => Ok(Coordinate {
+            x,
+            y,
+            z: (!z.is_nan()).then_some(z),
+            m: Some(m),
+        }),
+        _ => Err(Error::Invalid(format!(
+            "geospatial bound must have 2 to 4 coordinates, found {}",
+            coordinates.len()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xy(x: f64, y: f64) -> Coordinate {
+        Coordinate {
+            x,
+            y,
+            z: None,
+            m: None,
+        }
+    }
+
+    #[test]
+    fn test_read_wkb_point() {
+        // POINT (30 10), big-endian
+        let wkb = [
+            0x00, 0x00, 0x00, 0x00, 0x01, 0x40, 0x3e, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+            0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(
+            Geometry::Point(Some(xy(30.0, 10.0))),
+            Geometry::from_wkb(&wkb).unwrap()
+        );
+        assert!(Geometry::from_wkb(&wkb[..10]).is_err());
+    }
+
+    #[test]
+    fn test_read_ewkb() {
+        // SRID=4326;POINT Z (1 2 3), little-endian extended WKB
+        let mut wkb = vec![0x01];
+        wkb.extend_from_slice(&(1 | EWKB_Z | EWKB_SRID).to_le_bytes());
+        wkb.extend_from_slice(&4326u32.to_le_bytes());
+        for v in [1.0f64, 2.0, 3.0] {
+            wkb.extend_from_slice(&v.to_le_bytes());
+        }
+        assert_eq!(
+            Geometry::Point(Some(Coordinate {
+                x: 1.0,
+                y: 2.0,
+                z: Some(3.0),
+                m: None
+            })),
+            Geometry::from_wkb(&wkb).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_wkb_round_trip() {
+        let geometries = vec![
+            Geometry::Point(None),
+            Geometry::LineString(vec![xy(0.0, 0.0), xy(1.0, 1.0)]),
+            Geometry::Polygon(vec![vec![
+                xy(0.0, 0.0),
+                xy(4.0, 0.0),
+                xy(4.0, 4.0),
+                xy(0.0, 0.0),
+            ]]),
+            Geometry::MultiPoint(vec![Geometry::Point(Some(Coordinate {
+                x: 1.0,
+                y: 2.0,
+                z: None,
+                m: Some(7.0),
+            }))]),
+            Geometry::GeometryCollection(vec![
+                Geometry::Point(Some(xy(5.0, 5.0))),
+                Geometry::MultiLineString(vec![Geometry::LineString(vec![xy(1.0, 2.0)])]),
+            ]),
+        ];
+        for geometry in geometries {
+            assert_eq!(geometry, Geometry::from_wkb(&geometry.to_wkb()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let geometry = Geometry::GeometryCollection(vec![
+            Geometry::Point(Some(xy(5.0, -5.0))),
+            Geometry::LineString(vec![xy(-1.0, 2.0), xy(3.0, 8.0)]),
+            Geometry::Point(None),
+        ]);
+        let bbox = geometry.bounding_box().unwrap();
+        assert_eq!(
+            (-1.0, -5.0, 5.0, 8.0),
+            (bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max)
+        );
+        assert_eq!(None, bbox.z_min);
+        assert_eq!(None, Geometry::Point(None).bounding_box());
+    }
+
+    #[test]
+    fn test_geography_bounding_box() {
+        let crossing = Geometry::LineString(vec![xy(170.0, 0.0), xy(-175.0, 10.0), xy(179.0, 5.0)]);
+        let bbox = crossing.geography_bounding_box().unwrap();
+        assert_eq!(
+            (170.0, 0.0, -175.0, 10.0),
+            (bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max)
+        );
+        assert!(bbox.intersects(
+            &Geometry::Point(Some(xy(-178.0, 5.0)))
+                .bounding_box()
+                .unwrap()
+        ));
+        assert!(!bbox.intersects(&Geometry::Point(Some(xy(0.0, 5.0))).bounding_box().unwrap()));
+
+        let geometry = Geometry::LineString(vec![xy(-10.0, 0.0), xy(20.0, 10.0)]);
+        assert_eq!(geometry.bounding_box(), geometry.geography_bounding_box());
+
+        // The edge from 100 to -100 crosses the antimeridian.
+        let crossing = Geometry::LineString(vec![xy(0.0, 0.0), xy(100.0, 0.0), xy(-100.0, 0.0)]);
+        let bbox = crossing.geography_bounding_box().unwrap();
+        assert_eq!((0.0, -100.0), (bbox.x_min, bbox.x_max));
+        assert_eq!(None, Geometry::Point(None).geography_bounding_box());
+    }
+
+    #[test]
+    fn test_geography_edges() {
+        // A great circle between two points at 45N bulges towards the pole.
+        let edge = Geometry::LineString(vec![xy(0.0, 45.0), xy(90.0, 45.0)]);
+        let bbox = edge.geography_bounding_box().unwrap();
+        assert_eq!(45.0, bbox.y_min);
+        assert!((bbox.y_max - 2f64.sqrt().atan().to_degrees()).abs() < 1e-9);
+        let query = Geometry::Point(Some(xy(45.0, 54.0)));
+        assert!(bbox.intersects(&query.bounding_box().unwrap()));
+
+        let mirrored = Geometry::LineString(vec![xy(0.0, -45.0), xy(90.0, -45.0)]);
+        let bbox = mirrored.geography_bounding_box().unwrap();
+        assert!((bbox.y_min + 2f64.sqrt().atan().to_degrees()).abs() < 1e-9);
+        assert_eq!(-45.0, bbox.y_max);
+
+        // A ring around the north pole, with the interior on the left.
+        let ring = [0.0, 90.0, 180.0, -90.0, 0.0].map(|x| xy(x, 80.0)).to_vec();
+        let bbox = Geometry::Polygon(vec![ring.clone()])
+            .geography_bounding_box()
+            .unwrap();
+        assert_eq!(
+            (-180.0, 80.0, 180.0, 90.0),
+            (bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max)
+        );
+        let reversed = ring.into_iter().rev().collect();
+        let bbox = Geometry::MultiPolygon(vec![Geometry::Polygon(vec![reversed])])
+            .geography_bounding_box()
+            .unwrap();
+        // Reversed, the polygon is everything south of the ring.
+        assert_eq!(-90.0, bbox.y_min);
+        assert!(bbox.y_max > 80.0 && bbox.y_max < 90.0);
+    }
+
+    #[test]
+    fn test_bound_serialisation() {
+        let bbox = BoundingBox {
+            x_min: 1.0,
+            y_min: 2.0,
+            z_min: None,
+            m_min: Some(0.0),
+            x_max: 3.0,
+            y_max: 4.0,
+            z_max: None,
+            m_max: Some(10.0),
+        };
+        assert_eq!(
+            16,
+            BoundingBox::from_coordinate(&xy(1.0, 2.0))
+                .lower_bound()
+                .len()
+        );
+        let lower = bbox.lower_bound();
+        assert_eq!(32, lower.len());
+        assert!(f64::from_le_bytes(lower[16..24].try_into().unwrap()).is_nan());
+        assert_eq!(
+            bbox,
+            BoundingBox::from_bounds(&lower, &bbox.upper_bound()).unwrap()
+        );
+        assert!(BoundingBox::from_bounds(&[0; 8], &[0; 16]).is_err());
+    }
+
+    #[test]
+    fn test_intersects() {
+        let bbox = |x_min, y_min, x_max, y_max| BoundingBox {
+            x_min,
+            y_min,
+            z_min: None,
+            m_min: None,
+            x_max,
+            y_max,
+            z_max: None,
+            m_max: None,
+        };
+        let file = bbox(0.0, 0.0, 10.0, 10.0);
+        assert!(file.intersects(&bbox(5.0, 5.0, 20.0, 20.0)));
+        assert!(file.intersects(&bbox(10.0, 10.0, 20.0, 20.0)));
+        assert!(!file.intersects(&bbox(11.0, 0.0, 20.0, 10.0)));
+        assert!(!file.intersects(&bbox(0.0, -5.0, 10.0, -1.0)));
+
+        // Wraps around the antimeridian, covering [170, 180] and [-180, -170].
+        let wrapping = bbox(170.0, 0.0, -170.0, 10.0);
+        assert!(wrapping.intersects(&bbox(175.0, 5.0, 176.0, 6.0)));
+        assert!(wrapping.intersects(&bbox(-175.0, 5.0, -174.0, 6.0)));
+        assert!(!wrapping.intersects(&bbox(0.0, 5.0, 10.0, 6.0)));
+    }
+}
@@ -0,0 +1,395 @@
+/*!
+Navigation of the snapshot history of a table.
+
+Each [SnapshotV2] links to its parent by id, so the snapshots of a table form a tree whose
+branches are named by the [refs](crate::model::table::TableMetadataV2#structfield.refs) of the
+table. [TableMetadataV2::ancestors] walks from a snapshot to the root of the tree.
+
+Expired snapshots are removed from the metadata while their children may remain, so the parent
+of a snapshot is not always in the table. Walks stop at the first parent that is missing, and at
+the first snapshot seen twice if malformed metadata links snapshots in a cycle.
+
+[TableMetadataV2::snapshot_as_of] resolves the snapshot to read for time travel queries, and
+[TableMetadataV2::schema_as_of] the schema to read it with.
+*/
+use std::collections::HashSet;
+
+use crate::error::{Error, Result};
+use crate::model::schema::SchemaV2;
+use crate::model::snapshot::{Retention, SnapshotV2};
+use crate::model::table::TableMetadataV2;
+
+/// The name of the branch that always tracks the current snapshot of the table.
+pub const MAIN_BRANCH: &str = "main";
+
+/// Iterator over a snapshot and its ancestors, from newest to oldest.
+pub struct Ancestors<'a> {
+    metadata: &'a TableMetadataV2,
+    next: Option<i64>,
+    visited: HashSet<i64>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = &'a SnapshotV2;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let snapshot_id = self.next.take()?;
+        if !self.visited.insert(snapshot_id) {
+            return None;
+        }
+        let snapshot = self.metadata.snapshot(snapshot_id)?;
+        self.next = snapshot.parent_snapshot_id;
+        Some(snapshot)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The state of a table to read.
+pub enum AsOf {
+    /// The snapshot with the id.
+    SnapshotId(i64),
+    /// The current snapshot of the table at the time, in milliseconds from the unix epoch.
+    TimestampMs(i64),
+    /// The snapshot a branch or tag points to.
+    Ref(String),
+}
+
+impl TableMetadataV2 {
+    /// The snapshot with `snapshot_id`, if it is in the table.
+    pub fn snapshot(&self, snapshot_id: i64) -> Option<&SnapshotV2> {
+        self.snapshots
+            .as_ref()?
+            .iter()
+            .find(|s| s.snapshot_id == snapshot_id)
+    }
+
+    /// The current snapshot of the table, if it has one.
+    pub fn current_snapshot(&self) -> Option<&SnapshotV2> {
+        self.snapshot(self.current_snapshot_id?)
+    }
+
+    /// The id of the snapshot the ref `name` points to. The `main` branch points to the
+    /// current snapshot even when the table has no refs.
+    pub fn ref_snapshot_id(&self, name: &str) -> Option<i64> {
+        match self.refs.as_ref().and_then(|refs| refs.get(name)) {
+            Some(reference) => Some(reference.snapshot_id),
+            None if name == MAIN_BRANCH => self.current_snapshot_id,
+            None => None,
+        }
+    }
+
+    /// The current schema of the table.
+    pub fn current_schema(&self) -> Result<&SchemaV2> {
+        self.schema(i64::from(self.current_schema_id))
+    }
+
+    fn schema(&self, schema_id: i64) -> Result<&SchemaV2> {
+        self.schemas
+            .iter()
+            .find(|s| i64::from(s.schema_id) == schema_id)
+            .ok_or_else(|| Error::NotFound(format!("schema {schema_id} is not in the table")))
+    }
+
+    /// The snapshot that was current at `timestamp_ms`, according to the snapshot log of the
+    /// table, or the ancestry of the current snapshot if the table has no log. Returns an
+    /// error if the table had no snapshot at that time or the snapshot has expired.
+    pub fn snapshot_as_of_time(&self, timestamp_ms: i64) -> Result<&SnapshotV2> {
+        let snapshot_id = match &self.snapshot_log {
+            Some(log) => log
+                .iter()
+                .rev()
+                .find(|entry| entry.timestamp_ms <= timestamp_ms)
+                .map(|entry| entry.snapshot_id),
+            None => self
+                .current_snapshot_id
+                .and_then(|id| self.ancestors(id).find(|s| s.timestamp_ms <= timestamp_ms))
+                .map(|s| s.snapshot_id),
+        }
+        .ok_or_else(|| Error::NotFound(format!("table has no snapshot as of {timestamp_ms}")))?;
+        self.snapshot(snapshot_id).ok_or_else(|| {
+            Error::NotFound(format!(
+                "snapshot {snapshot_id} as of {timestamp_ms} has expired"
+            ))
+        })
+    }
+
+    /// The snapshot to read the table as of `as_of`.
+    pub fn snapshot_as_of(&self, as_of: &AsOf) -> Result<&SnapshotV2> {
+        let snapshot_id = match as_of {
+            AsOf::SnapshotId(id) => *id,
+            AsOf::TimestampMs(timestamp_ms) => return self.snapshot_as_of_time(*timestamp_ms),
+            AsOf::Ref(name) => self
+                .ref_snapshot_id(name)
+                .ok_or_else(|| Error::NotFound(format!("ref {name} is not in the table")))?,
+        };
+        self.snapshot(snapshot_id)
+            .ok_or_else(|| Error::NotFound(format!("snapshot {snapshot_id} is not in the table")))
+    }
+
+    /// The schema of the table when the snapshot with `snapshot_id` was created, or the current
+    /// schema if the snapshot does not record its schema.
+    pub fn schema_for_snapshot(&self, snapshot_id: i64) -> Result<&SchemaV2> {
+        let snapshot = self.snapshot(snapshot_id).ok_or_else(|| {
+            Error::NotFound(format!("snapshot {snapshot_id} is not in the table"))
+        })?;
+        match snapshot.schema_id {
+            Some(schema_id) => self.schema(schema_id),
+            None => self.current_schema(),
+        }
+    }
+
+    /// The schema to read the table as of `as_of` with. Branches are read with the current
+    /// schema of the table, as they may still be written to, while snapshots and tags are read
+    /// with the schema of their snapshot.
+    pub fn schema_as_of(&self, as_of: &AsOf) -> Result<&SchemaV2> {
+        let snapshot = self.snapshot_as_of(as_of)?;
+        let is_branch = match as_of {
+            AsOf::Ref(name) => match self.refs.as_ref().and_then(|refs| refs.get(name)) {
+                Some(reference) => matches!(reference.retention, Retention::Branch { .. }),
+                None => name == MAIN_BRANCH,
+            },
+            _ => false,
+        };
+        if is_branch {
+            self.current_schema()
+        } else {
+            self.schema_for_snapshot(snapshot.snapshot_id)
+        }
+    }
+
+    /// The snapshot with `snapshot_id` followed by its ancestors, newest first.
+    pub fn ancestors(&self, snapshot_id: i64) -> Ancestors<'_> {
+        Ancestors {
+            metadata: self,
+            next: Some(snapshot_id),
+            visited: HashSet::new(),
+        }
+    }
+
+    /// The snapshot the ref `name` points to followed by its ancestors, newest first.
+    pub fn ref_ancestors(&self, name: &str) -> Result<Ancestors<'_>> {
+        let snapshot_id = self
+            .ref_snapshot_id(name)
+            .ok_or_else(|| Error::NotFound(format!("ref {name} is not in the table")))?;
+        Ok(self.ancestors(snapshot_id))
+    }
+
+    /// The ids of the snapshot with `snapshot_id` and its ancestors, newest first, including
+    /// the id of the last parent if it has expired.
+    fn lineage(&self, snapshot_id: i64) -> Vec<i64> {
+        let mut ids = vec![];
+        let mut visited = HashSet::new();
+        let mut next = Some(snapshot_id);
+        while let Some(id) = next.filter(|id| visited.insert(*id)) {
+            ids.push(id);
+            next = self.snapshot(id).and_then(|s| s.parent_snapshot_id);
+        }
+        ids
+    }
+
+    /// Returns true if the snapshot with `ancestor_id` is the snapshot with `snapshot_id` or
+    /// one of its ancestors.
+    pub fn is_ancestor_of(&self, snapshot_id: i64, ancestor_id: i64) -> bool {
+        self.lineage(snapshot_id).contains(&ancestor_id)
+    }
+
+    /// The id of the newest snapshot that is an ancestor of both snapshots, if they have one.
+    pub fn common_ancestor(&self, first_id: i64, second_id: i64) -> Option<i64> {
+        let first: HashSet<i64> = self.lineage(first_id).into_iter().collect();
+        self.lineage(second_id)
+            .into_iter()
+            .find(|id| first.contains(id))
+    }
+
+    /// The snapshots after the snapshot with `from_id` up to and including the snapshot with
+    /// `to_id`, newest first. All ancestors of `to_id` are returned if `from_id` is `None`.
+    /// Returns an error if `from_id` is not an ancestor of `to_id`.
+    pub fn snapshots_between(&self, from_id: Option<i64>, to_id: i64) -> Result<Vec<&SnapshotV2>> {
+        if let Some(from_id) = from_id {
+            if !self.is_ancestor_of(to_id, from_id) {
+                return Err(Error::Invalid(format!(
+                    "snapshot {from_id} is not an ancestor of snapshot {to_id}"
+                )));
+            }
+        }
+        Ok(self
+            .ancestors(to_id)
+            .take_while(|s| Some(s.snapshot_id) != from_id)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::model::tests::{branch, metadata_with_snapshots, snapshot_log, tag};
+
+    /// Snapshots 1 <- 2 <- 3 on main and 2 <- 4 <- 5 on the audit branch. Snapshot 6 is the
+    /// child of the expired snapshot 0.
+    fn metadata() -> TableMetadataV2 {
+        let mut metadata = metadata_with_snapshots(&[
+            (1, None, 1000),
+            (2, Some(1), 2000),
+            (3, Some(2), 3000),
+            (4, Some(2), 4000),
+            (5, Some(4), 5000),
+            (6, Some(0), 6000),
+        ]);
+        // Snapshots from 3 on were written with schema 1.
+        for snapshot in metadata.snapshots.iter_mut().flatten() {
+            snapshot.schema_id = Some(if snapshot.snapshot_id < 3 { 0 } else { 1 });
+        }
+        metadata.schemas.push(
+            serde_json::from_str(
+                r#"
+                {
+                    "schema-id": 1,
+                    "type": "struct",
+                    "fields": [
+                        { "id": 1, "name": "id", "required": true, "field_type": "long" },
+                        { "id": 2, "name": "data", "required": false, "field_type": "string" }
+                    ]
+                }
+                "#,
+            )
+            .unwrap(),
+        );
+        metadata.current_schema_id = 1;
+        metadata.last_column_id = 2;
+        metadata.current_snapshot_id = Some(3);
+        metadata.snapshot_log = snapshot_log(&[(1, 1000), (2, 2000), (3, 3000)]);
+        let retention = (1, 100000, 100000);
+        metadata.refs = Some(HashMap::from([
+            (MAIN_BRANCH.to_string(), branch(3, retention)),
+            ("audit".to_string(), branch(5, retention)),
+            ("v1".to_string(), tag(1, 100000)),
+        ]));
+        metadata
+    }
+
+    fn ids<'a>(snapshots: impl IntoIterator<Item = &'a SnapshotV2>) -> Vec<i64> {
+        snapshots.into_iter().map(|s| s.snapshot_id).collect()
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let metadata = metadata();
+        assert_eq!(vec![3, 2, 1], ids(metadata.ancestors(3)));
+        assert_eq!(
+            vec![5, 4, 2, 1],
+            ids(metadata.ref_ancestors("audit").unwrap())
+        );
+        assert_eq!(vec![6], ids(metadata.ancestors(6)));
+        assert!(ids(metadata.ancestors(9)).is_empty());
+        assert!(metadata.ref_ancestors("missing").is_err());
+        assert_eq!(Some(3), metadata.current_snapshot().map(|s| s.snapshot_id));
+
+        let mut without_refs = metadata;
+        without_refs.refs = None;
+        assert_eq!(Some(3), without_refs.ref_snapshot_id(MAIN_BRANCH));
+        assert_eq!(None, without_refs.ref_snapshot_id("audit"));
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut metadata = metadata();
+        for snapshot in metadata.snapshots.iter_mut().flatten() {
+            match snapshot.snapshot_id {
+                1 => snapshot.parent_snapshot_id = Some(3),
+                6 => snapshot.parent_snapshot_id = Some(6),
+                _ => (),
+            }
+        }
+        // Walks stop instead of looping forever.
+        assert_eq!(vec![3, 2, 1], ids(metadata.ancestors(3)));
+        assert_eq!(vec![6], ids(metadata.ancestors(6)));
+        assert!(!metadata.is_ancestor_of(6, 0));
+        assert!(!metadata.is_ancestor_of(3, 4));
+        assert_eq!(None, metadata.common_ancestor(6, 3));
+        assert_eq!(
+            vec![3, 2, 1],
+            ids(metadata.snapshots_between(None, 3).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_is_ancestor_of() {
+        let metadata = metadata();
+        assert!(metadata.is_ancestor_of(5, 2));
+        assert!(metadata.is_ancestor_of(5, 5));
+        assert!(!metadata.is_ancestor_of(5, 3));
+        // The expired parent is still known to be an ancestor.
+        assert!(metadata.is_ancestor_of(6, 0));
+        assert!(!metadata.is_ancestor_of(6, 1));
+    }
+
+    #[test]
+    fn test_common_ancestor() {
+        let metadata = metadata();
+        assert_eq!(Some(2), metadata.common_ancestor(3, 5));
+        assert_eq!(Some(4), metadata.common_ancestor(4, 5));
+        assert_eq!(Some(1), metadata.common_ancestor(1, 5));
+        assert_eq!(None, metadata.common_ancestor(3, 6));
+    }
+
+    #[test]
+    fn test_snapshot_as_of() {
+        let metadata = metadata();
+        let as_of = |as_of: AsOf| metadata.snapshot_as_of(&as_of).map(|s| s.snapshot_id);
+        assert_eq!(2, as_of(AsOf::TimestampMs(2500)).unwrap());
+        assert_eq!(3, as_of(AsOf::TimestampMs(3000)).unwrap());
+        assert!(matches!(
+            as_of(AsOf::TimestampMs(999)),
+            Err(Error::NotFound(_))
+        ));
+        assert_eq!(5, as_of(AsOf::SnapshotId(5)).unwrap());
+        assert!(as_of(AsOf::SnapshotId(0)).is_err());
+        assert_eq!(1, as_of(AsOf::Ref("v1".to_string())).unwrap());
+        assert_eq!(5, as_of(AsOf::Ref("audit".to_string())).unwrap());
+        assert!(as_of(AsOf::Ref("missing".to_string())).is_err());
+
+        let mut without_log = self::metadata();
+        without_log.snapshot_log = None;
+        assert_eq!(
+            2,
+            without_log.snapshot_as_of_time(2999).unwrap().snapshot_id
+        );
+    }
+
+    #[test]
+    fn test_schema_as_of() {
+        let metadata = metadata();
+        let schema_id = |as_of: AsOf| metadata.schema_as_of(&as_of).unwrap().schema_id;
+        assert_eq!(0, schema_id(AsOf::SnapshotId(2)));
+        assert_eq!(1, schema_id(AsOf::SnapshotId(3)));
+        assert_eq!(0, schema_id(AsOf::TimestampMs(2500)));
+        assert_eq!(0, schema_id(AsOf::Ref("v1".to_string())));
+        assert_eq!(1, schema_id(AsOf::Ref("audit".to_string())));
+        assert_eq!(0, metadata.schema_for_snapshot(1).unwrap().schema_id);
+        assert!(metadata.schema_for_snapshot(0).is_err());
+    }
+
+    #[test]
+    fn test_snapshots_between() {
+        let metadata = metadata();
+        assert_eq!(
+            vec![5, 4],
+            ids(metadata.snapshots_between(Some(2), 5).unwrap())
+        );
+        assert_eq!(
+            vec![3, 2, 1],
+            ids(metadata.snapshots_between(None, 3).unwrap())
+        );
+        assert!(metadata.snapshots_between(Some(3), 3).unwrap().is_empty());
+        assert!(matches!(
+            metadata.snapshots_between(Some(4), 3),
+            Err(Error::Invalid(_))
+        ));
+        assert_eq!(
+            vec![6],
+            ids(metadata.snapshots_between(Some(0), 6).unwrap())
+        );
+    }
+}
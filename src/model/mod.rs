@@ -63,8 +63,127 @@ let data = r#"
 
 */
 
+pub mod accessor;
+pub mod clustering;
+pub mod decimal;
+pub mod evolution;
+pub mod expiration;
+pub mod expression;
+pub mod geo;
+pub mod history;
 pub mod partition;
+pub mod projection;
+pub mod refs;
 pub mod schema;
 pub mod snapshot;
 pub mod sort;
+pub mod summary;
 pub mod table;
+pub mod transform;
+pub mod values;
+pub mod variant;
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::model::snapshot::{Operation, Reference, Retention, SnapshotV2, Summary};
+    use crate::model::table::{SnapshotLog, TableMetadataV2};
+
+    /// Metadata of a table with a required long column `id`, no partitioning, the unsorted
+    /// order and the snapshots `(id, parent, timestamp_ms)`. The last snapshot is current.
+    pub(crate) fn metadata_with_snapshots(
+        snapshots: &[(i64, Option<i64>, i64)],
+    ) -> TableMetadataV2 {
+        let mut metadata: TableMetadataV2 = serde_json::from_str(
+            r#"
+            {
+                "format-version": 2,
+                "table-uuid": "fb072c92-a02b-11e9-ae9c-1bb7bc9eca94",
+                "location": "s3://b/wh/data.db/table",
+                "last-sequence-number": 0,
+                "last-updated-ms": 1515100955770,
+                "last-column-id": 1,
+                "schemas": [
+                    {
+                        "schema-id": 0,
+                        "type": "struct",
+                        "fields": [
+                            { "id": 1, "name": "id", "required": true, "field_type": "long" }
+                        ]
+                    }
+                ],
+                "current-schema-id": 0,
+                "partition-specs": [{ "spec-id": 0, "fields": [] }],
+                "default-spec-id": 0,
+                "last-partition-id": 999,
+                "sort-orders": [{ "order-id": 0, "fields": [] }],
+                "default-sort-order-id": 0
+            }
+            "#,
+        )
+        .unwrap();
+        if let Some((last_id, _, last_timestamp_ms)) = snapshots.last() {
+            metadata.current_snapshot_id = Some(*last_id);
+            metadata.last_updated_ms = *last_timestamp_ms;
+            metadata.last_sequence_number = snapshots.len() as i64;
+            metadata.snapshots = Some(
+                snapshots
+                    .iter()
+                    .zip(1..)
+                    .map(
+                        |(&(snapshot_id, parent_snapshot_id, timestamp_ms), sequence_number)| {
+                            SnapshotV2 {
+                                snapshot_id,
+                                parent_snapshot_id,
+                                sequence_number,
+                                timestamp_ms,
+                                manifest_list: format!(
+                                    "s3://b/wh/data.db/table/metadata/snap-{snapshot_id}.avro"
+                                ),
+                                summary: Summary {
+                                    operation: Some(Operation::Append),
+                                    other: Default::default(),
+                                },
+                                schema_id: None,
+                            }
+                        },
+                    )
+                    .collect(),
+            );
+        }
+        metadata
+    }
+
+    /// A snapshot log of `(snapshot_id, timestamp_ms)` entries.
+    pub(crate) fn snapshot_log(entries: &[(i64, i64)]) -> Option<Vec<SnapshotLog>> {
+        Some(
+            entries
+                .iter()
+                .map(|&(snapshot_id, timestamp_ms)| SnapshotLog {
+                    snapshot_id,
+                    timestamp_ms,
+                })
+                .collect(),
+        )
+    }
+
+    /// A branch ref with the retention settings
+    /// `(min_snapshots_to_keep, max_snapshot_age_ms, max_ref_age_ms)`.
+    pub(crate) fn branch(snapshot_id: i64, retention: (i32, i64, i64)) -> Reference {
+        Reference {
+            snapshot_id,
+            retention: Retention::Branch {
+                min_snapshots_to_keep: retention.0,
+                max_snapshot_age_ms: retention.1,
+                max_ref_age_ms: retention.2,
+            },
+        }
+    }
+
+    /// A tag ref.
+    pub(crate) fn tag(snapshot_id: i64, max_ref_age_ms: i64) -> Reference {
+        Reference {
+            snapshot_id,
+            retention: Retention::Tag { max_ref_age_ms },
+        }
+    }
+}
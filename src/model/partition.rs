@@ -4,7 +4,36 @@ from the source fields.
 
 The [PartitionSpec] is composed of multiple [PartitionField] each of which together define how
 the [TableMetadataV2](crate::model::table::TableMetadataV2) is partitioned.
+
+A spec only refers to its source columns by id. [PartitionSpec::bind] resolves them against a
+[SchemaV2], producing a [BoundPartitionSpec] with the source types and the [Struct] type of
+the partition tuples, which is the type of the `partition` field of manifest entries.
+
+New specs are best created with [PartitionSpec::builder], which resolves source columns by name,
+names partition fields and assigns their ids:
+
+```rust
+# use iceberg_rs::model::partition::PartitionSpec;
+# use iceberg_rs::model::schema::SchemaV2;
+# let schema: SchemaV2 = serde_json::from_str(r#"{"schema-id": 0, "type": "struct", "fields": [
+#     {"id": 1, "name": "id", "required": true, "field_type": "long"},
+#     {"id": 2, "name": "ts", "required": true, "field_type": "timestamptz"},
+#     {"id": 3, "name": "region", "required": true, "field_type": "string"}]}"#).unwrap();
+let spec = PartitionSpec::builder(&schema)
+    .identity("region")
+    .day("ts")
+    .bucket("id", 16)
+    .build()
+    .unwrap();
+assert_eq!(vec!["region", "ts_day", "id_bucket_16"],
+    spec.fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>());
+```
 */
+use crate::error::Error;
+use crate::model::accessor::{Accessor, StructLike};
+use crate::model::schema::{AllType, PrimitiveType, SchemaV2, Struct, StructField};
+use crate::model::transform::check_source;
+use crate::model::values::Value;
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{
@@ -112,7 +141,7 @@ where
     Ok(Transform::Truncate(width))
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Tables are configured with a partition spec that defines how to produce a tuple of partition values from a record.
 pub struct PartitionField {
@@ -127,7 +156,7 @@ pub struct PartitionField {
     pub transform: Transform,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// A definition of how partition values are derived from data fields.
 pub struct PartitionSpec {
@@ -137,6 +166,468 @@ pub struct PartitionSpec {
     pub fields: Vec<PartitionField>,
 }
 
+impl PartitionSpec {
+    /// Returns an error unless every field's source is a primitive field of `schema`, outside
+    /// any list or map, that the field's transform can be applied to.
+    pub fn check_compatible(&self, schema: &SchemaV2) -> Result<(), Error> {
+        self.fields
+            .iter()
+            .try_for_each(|f| check_source(schema, f.source_id, &f.transform).map(|_| ()))
+    }
+
+    /// The type of the partition tuples of this spec for data written with `schema`.
+    pub fn partition_type(&self, schema: &SchemaV2) -> Result<Struct, Error> {
+        self.bind(schema).map(|bound| bound.partition_type)
+    }
+
+    /// Resolve the source of every field against `schema`. Fails if a source is missing,
+    /// is not a primitive, is within a list or map, or cannot be transformed by its field's transform.
+    /// Void fields whose source cannot be resolved are bound with the
+    /// [Unknown](PrimitiveType::Unknown) type, as their values are always null.
+    pub fn bind(&self, schema: &SchemaV2) -> Result<BoundPartitionSpec, Error> {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                let accessor = match check_source(schema, field.source_id, &field.transform) {
+                    Ok(accessor) => Some(accessor),
+                    // Void fields replace removed fields of v1 specs, whose source may be gone.
+                    Err(_) if field.transform == Transform::Void => None,
+                    Err(error) => return Err(error),
+                };
+                let source_type = match accessor.as_ref().map(Accessor::field_type) {
+                    Some(AllType::Primitive(p)) => p.clone(),
+                    Some(_) => unreachable!("check_source only accepts primitive sources"),
+                    None => PrimitiveType::Unknown,
+                };
+                let result_type = field.transform.result_type(&source_type)?;
+                Ok(BoundPartitionField {
+                    field: field.clone(),
+                    source_type,
+                    result_type,
+                    accessor,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let partition_type = Struct {
+            fields: fields
+                .iter()
+                .map(|f| StructField {
+                    id: f.field.field_id,
+                    name: f.field.name.clone(),
+                    required: false,
+                    field_type: AllType::Primitive(f.result_type.clone()),
+                    doc: None,
+                })
+                .collect(),
+        };
+        Ok(BoundPartitionSpec {
+            spec: self.clone(),
+            schema_id: schema.schema_id,
+            fields,
+            partition_type,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A [PartitionField] with its source resolved against a schema.
+pub struct BoundPartitionField {
+    field: PartitionField,
+    source_type: PrimitiveType,
+    result_type: PrimitiveType,
+    accessor: Option<Accessor>,
+}
+
+impl BoundPartitionField {
+    /// The unbound partition field.
+    pub fn field(&self) -> &PartitionField {
+        &self.field
+    }
+
+    /// The type of the source column.
+    pub fn source_type(&self) -> &PrimitiveType {
+        &self.source_type
+    }
+
+    /// The type of the partition values produced by the field's transform.
+    pub fn result_type(&self) -> &PrimitiveType {
+        &self.result_type
+    }
+
+    /// Reads the source column from rows of the schema, or `None` for a void field whose
+    /// source is not in the schema.
+    pub fn accessor(&self) -> Option<&Accessor> {
+        self.accessor.as_ref()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A [PartitionSpec] bound to the schema of the data it partitions.
+pub struct BoundPartitionSpec {
+    spec: PartitionSpec,
+    schema_id: i32,
+    fields: Vec<BoundPartitionField>,
+    partition_type: Struct,
+}
+
+impl BoundPartitionSpec {
+    /// The unbound spec.
+    pub fn spec(&self) -> &PartitionSpec {
+        &self.spec
+    }
+
+    /// The id of the schema the spec is bound to.
+    pub fn schema_id(&self) -> i32 {
+        self.schema_id
+    }
+
+    /// The bound fields, in the order of the spec.
+    pub fn fields(&self) -> &[BoundPartitionField] {
+        &self.fields
+    }
+
+    /// The type of the partition tuples. Partition fields are always optional.
+    pub fn partition_type(&self) -> &Struct {
+        &self.partition_type
+    }
+
+    /// Returns true if the spec has no fields.
+    pub fn is_unpartitioned(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The partition key of `row`, a row of the schema the spec is bound to.
+    pub fn partition_key<S: StructLike + ?Sized>(&self, row: &S) -> Result<PartitionKey, Error> {
+        self.fields
+            .iter()
+            .map(|f| {
+                let source = f.accessor.as_ref().and_then(|a| a.get(row));
+                f.field.transform.apply(source)
+            })
+            .collect::<Result<_, _>>()
+            .map(PartitionKey)
+    }
+
+    /// The Hive style path of a partition tuple, see [PartitionSpec::partition_to_path].
+    pub fn partition_to_path(&self, partition: &[Option<Value>]) -> Result<String, Error> {
+        if partition.len() != self.fields.len() {
+            return Err(Error::Invalid(format!(
+                "partition has {} values but the spec has {} fields",
+                partition.len(),
+                self.fields.len()
+            )));
+        }
+        let segments = self
+            .fields
+            .iter()
+            .zip(partition)
+            .map(|(f, value)| {
+                let human = f
+                    .field
+                    .transform
+                    .to_human_string(&f.source_type, value.as_ref())?;
+                Ok(format!("{}={}", escape(&f.field.name), escape(&human)))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(segments.join("/"))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+/// The partition values of a row, the results of the transforms of a spec in the order of its
+/// fields. Rows with equal keys belong to the same partition.
+pub struct PartitionKey(pub(crate) Vec<Option<Value>>);
+
+impl PartitionKey {
+    /// The partition values, in the order of the fields of the spec.
+    pub fn values(&self) -> &[Option<Value>] {
+        &self.0
+    }
+
+    /// The key as a value of the spec's [partition type](BoundPartitionSpec::partition_type),
+    /// as stored in the `partition` field of a data file.
+    pub fn to_struct(&self) -> Value {
+        Value::Struct(self.0.clone())
+    }
+}
+
+impl From<Vec<Option<Value>>> for PartitionKey {
+    fn from(values: Vec<Option<Value>>) -> Self {
+        PartitionKey(values)
+    }
+}
+
+impl StructLike for PartitionKey {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn get(&self, pos: usize) -> Option<&Value> {
+        StructLike::get(self.0.as_slice(), pos)
+    }
+}
+
+/// Hive's name for a null partition value, accepted when parsing partition paths.
+const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+fn escape(s: &str) -> String {
+    form_urlencoded::byte_serialize(s.as_bytes()).collect()
+}
+
+impl PartitionSpec {
+    /// The Hive style path of a partition tuple of this spec, such as `ts_day=2024-01-01/region=eu`.
+    /// Names and values are URL encoded; values use the human readable form of their transform.
+    pub fn partition_to_path(
+        &self,
+        schema: &SchemaV2,
+        partition: &[Option<Value>],
+    ) -> Result<String, Error> {
+        self.bind(schema)?.partition_to_path(partition)
+    }
+
+    /// Parse a Hive style partition path, the inverse of [PartitionSpec::partition_to_path].
+    /// The path must have one `name=value` segment for each field of the spec, in order.
+    pub fn parse_partition_path(
+        &self,
+        schema: &SchemaV2,
+        path: &str,
+    ) -> Result<Vec<Option<Value>>, Error> {
+        let bound = self.bind(schema)?;
+        let segments: Vec<_> = path
+            .trim_matches('/')
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+        if segments.len() != bound.fields.len() {
+            return Err(Error::Invalid(format!(
+                "partition path {path} does not have {} segments",
+                bound.fields.len()
+            )));
+        }
+        bound
+            .fields
+            .iter()
+            .zip(segments)
+            .map(|(f, segment)| {
+                let (name, value) = form_urlencoded::parse(segment.as_bytes())
+                    .next()
+                    .filter(|_| segment.contains('='))
+                    .ok_or_else(|| {
+                        Error::Invalid(format!("invalid partition path segment {segment}"))
+                    })?;
+                if name != f.field.name {
+                    return Err(Error::Invalid(format!(
+                        "expected partition field {} but found {name}",
+                        f.field.name
+                    )));
+                }
+                if value == HIVE_DEFAULT_PARTITION {
+                    return Ok(None);
+                }
+                f.field.transform.parse_human_string(&f.source_type, &value)
+            })
+            .collect()
+    }
+}
+
+/// The id of the first partition field of a table; ids are assigned after `last_partition_id`.
+pub const PARTITION_DATA_ID_START: i32 = 1000;
+
+impl PartitionSpec {
+    /// A builder for a spec partitioning data of `schema`.
+    pub fn builder(schema: &SchemaV2) -> PartitionSpecBuilder<'_> {
+        PartitionSpecBuilder {
+            schema,
+            spec_id: 0,
+            last_partition_id: PARTITION_DATA_ID_START - 1,
+            fields: vec![],
+            error: None,
+        }
+    }
+}
+
+/// Builds a [PartitionSpec] from source column names. Errors are reported by [PartitionSpecBuilder::build].
+pub struct PartitionSpecBuilder<'a> {
+    schema: &'a SchemaV2,
+    spec_id: i32,
+    last_partition_id: i32,
+    fields: Vec<PartitionField>,
+    error: Option<Error>,
+}
+
+/// Transforms that produce the same partitions as, or a coarser grouping of, each other
+/// share a name; a spec should not have two fields with the same source and name.
+fn dedup_name(transform: &Transform) -> String {
+    match transform {
+        Transform::Year | Transform::Month | Transform::Day | Transform::Hour => "time".to_string(),
+        Transform::Bucket(n) => format!("bucket[{n}]"),
+        Transform::Truncate(w) => format!("truncate[{w}]"),
+        other => format!("{other:?}").to_lowercase(),
+    }
+}
+
+/// The default name of a partition field with `transform` applied to `source`.
+pub(crate) fn default_name(source: &str, transform: &Transform) -> String {
+    match transform {
+        Transform::Identity => source.to_string(),
+        Transform::Void => format!("{source}_null"),
+        Transform::Year => format!("{source}_year"),
+        Transform::Month => format!("{source}_month"),
+        Transform::Day => format!("{source}_day"),
+        Transform::Hour => format!("{source}_hour"),
+        Transform::Bucket(n) => format!("{source}_bucket_{n}"),
+        Transform::Truncate(w) => format!("{source}_trunc_{w}"),
+    }
+}
+
+/// Returns an error unless `field` can be added to a spec with `fields`: its source must be
+/// compatible with its transform, its name unique and not redundant with another field.
+pub(crate) fn check_field(
+    schema: &SchemaV2,
+    fields: &[PartitionField],
+    field: &PartitionField,
+) -> Result<(), Error> {
+    let name = &field.name;
+    if name.is_empty() {
+        return Err(Error::Invalid(
+            "partition field names cannot be empty".to_string(),
+        ));
+    }
+    if fields.iter().any(|f| &f.name == name) {
+        return Err(Error::Invalid(format!(
+            "duplicate partition field name {name}"
+        )));
+    }
+    // Void fields replace removed fields of v1 specs and may have any source.
+    if field.transform == Transform::Void {
+        return Ok(());
+    }
+    check_source(schema, field.source_id, &field.transform)?;
+    if let Some(schema_field) = schema.field_by_name(name) {
+        if schema_field.id != field.source_id || field.transform != Transform::Identity {
+            return Err(Error::Invalid(format!(
+                "partition field {name} conflicts with a schema field; only an identity partition can share a source column's name"
+            )));
+        }
+    }
+    if let Some(existing) = fields.iter().find(|f| {
+        f.source_id == field.source_id && dedup_name(&f.transform) == dedup_name(&field.transform)
+    }) {
+        return Err(Error::Invalid(format!(
+            "partition field {name} is redundant with {}",
+            existing.name
+        )));
+    }
+    Ok(())
+}
+
+impl<'a> PartitionSpecBuilder<'a> {
+    /// Set the id of the spec, 0 by default.
+    pub fn with_spec_id(mut self, spec_id: i32) -> Self {
+        self.spec_id = spec_id;
+        self
+    }
+
+    /// Set the largest partition field id already assigned by the table, such as
+    /// [TableMetadataV2::last_partition_id](crate::model::table::TableMetadataV2::last_partition_id).
+    /// Fields are assigned ids after it when the spec is built, whether they were added before
+    /// or after it was set.
+    pub fn with_last_partition_id(mut self, last_partition_id: i32) -> Self {
+        self.last_partition_id = last_partition_id;
+        self
+    }
+
+    /// Partition by the value of `source`.
+    pub fn identity(self, source: &str) -> Self {
+        self.add(source, Transform::Identity)
+    }
+
+    /// Partition by the year of `source`.
+    pub fn year(self, source: &str) -> Self {
+        self.add(source, Transform::Year)
+    }
+
+    /// Partition by the month of `source`.
+    pub fn month(self, source: &str) -> Self {
+        self.add(source, Transform::Month)
+    }
+
+    /// Partition by the day of `source`.
+    pub fn day(self, source: &str) -> Self {
+        self.add(source, Transform::Day)
+    }
+
+    /// Partition by the hour of `source`.
+    pub fn hour(self, source: &str) -> Self {
+        self.add(source, Transform::Hour)
+    }
+
+    /// Partition by `source` hashed into `num_buckets` buckets.
+    pub fn bucket(self, source: &str, num_buckets: u32) -> Self {
+        self.add(source, Transform::Bucket(num_buckets))
+    }
+
+    /// Partition by `source` truncated to `width`.
+    pub fn truncate(self, source: &str, width: u32) -> Self {
+        self.add(source, Transform::Truncate(width))
+    }
+
+    /// Add a field applying `transform` to `source`, with the default name for the transform.
+    pub fn add(self, source: &str, transform: Transform) -> Self {
+        let name = default_name(source, &transform);
+        self.add_with_name(source, &name, transform)
+    }
+
+    /// Add a field named `name` applying `transform` to `source`.
+    pub fn add_with_name(mut self, source: &str, name: &str, transform: Transform) -> Self {
+        if self.error.is_none() {
+            if let Err(error) = self.try_add(source, name, transform) {
+                self.error = Some(error);
+            }
+        }
+        self
+    }
+
+    fn try_add(&mut self, source: &str, name: &str, transform: Transform) -> Result<(), Error> {
+        let source_field = self.schema.field_by_name(source).ok_or_else(|| {
+            Error::NotFound(format!("source field {source} is not in the schema"))
+        })?;
+        // Field ids are assigned by build.
+        let field = PartitionField {
+            source_id: source_field.id,
+            field_id: 0,
+            name: name.to_string(),
+            transform,
+        };
+        check_field(self.schema, &self.fields, &field)?;
+        self.fields.push(field);
+        Ok(())
+    }
+
+    /// The spec, or the first error from adding a field.
+    pub fn build(self) -> Result<PartitionSpec, Error> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let mut field_id = self.last_partition_id;
+        let fields = self
+            .fields
+            .into_iter()
+            .map(|field| {
+                field_id = field_id.checked_add(1).ok_or_else(|| {
+                    Error::Invalid("partition field ids are exhausted".to_string())
+                })?;
+                Ok(PartitionField { field_id, ..field })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(PartitionSpec {
+            spec_id: self.spec_id,
+            fields,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,4 +678,383 @@ mod tests {
             assert_eq!(transform, partition_field.transform);
         }
     }
+
+    fn schema() -> SchemaV2 {
+        serde_json::from_str(
+            r#"
+            {
+                "schema-id": 0,
+                "type": "struct",
+                "fields": [
+                    { "id": 1, "name": "id", "required": true, "field_type": "long" },
+                    { "id": 2, "name": "ts", "required": true, "field_type": "date" },
+                    { "id": 3, "name": "score", "required": false, "field_type": "float" },
+                    {
+                        "id": 4,
+                        "name": "location",
+                        "required": false,
+                        "field_type": {
+                            "type": "struct",
+                            "fields": [
+                                { "id": 5, "name": "city", "required": false, "field_type": "string" }
+                            ]
+                        }
+                    },
+                    {
+                        "id": 6,
+                        "name": "tags",
+                        "required": false,
+                        "field_type": {
+                            "type": "list",
+                            "element-id": 7,
+                            "element-required": true,
+                            "element": {
+                                "type": "struct",
+                                "fields": [
+                                    { "id": 8, "name": "tag", "required": true, "field_type": "string" }
+                                ]
+                            }
+                        }
+                    }
+                ]
+            }
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_compatible() {
+        let schema = schema();
+        let spec = |source_id, transform| PartitionSpec {
+            spec_id: 0,
+            fields: vec![PartitionField {
+                source_id,
+                field_id: 1000,
+                name: "p".to_string(),
+                transform,
+            }],
+        };
+        assert!(spec(1, Transform::Bucket(16))
+            .check_compatible(&schema)
+            .is_ok());
+        assert!(spec(2, Transform::Day).check_compatible(&schema).is_ok());
+        assert!(spec(2, Transform::Hour).check_compatible(&schema).is_err());
+        assert!(spec(3, Transform::Bucket(16))
+            .check_compatible(&schema)
+            .is_err());
+        assert!(matches!(
+            spec(9, Transform::Identity).check_compatible(&schema),
+            Err(Error::NotFound(_))
+        ));
+        // Sources may be in structs but not in lists or maps, as for bind.
+        assert!(spec(5, Transform::Identity)
+            .check_compatible(&schema)
+            .is_ok());
+        assert!(matches!(
+            spec(8, Transform::Identity).check_compatible(&schema),
+            Err(Error::Invalid(_))
+        ));
+        assert!(spec(8, Transform::Identity).bind(&schema).is_err());
+    }
+
+    #[test]
+    fn test_partition_type() {
+        let spec: PartitionSpec = serde_json::from_str(
+            r#"
+            {
+                "spec-id": 1,
+                "fields": [
+                    { "source-id": 2, "field-id": 1000, "name": "ts_day", "transform": "day" },
+                    { "source-id": 1, "field-id": 1001, "name": "id_bucket", "transform": "bucket[16]" },
+                    { "source-id": 5, "field-id": 1002, "name": "city", "transform": "identity" }
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+        let partition_type = spec.partition_type(&schema()).unwrap();
+        let fields: Vec<_> = partition_type
+            .fields
+            .iter()
+            .map(|f| (f.id, f.name.as_str(), f.required, f.field_type.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    1000,
+                    "ts_day",
+                    false,
+                    AllType::Primitive(PrimitiveType::Date)
+                ),
+                (
+                    1001,
+                    "id_bucket",
+                    false,
+                    AllType::Primitive(PrimitiveType::Int)
+                ),
+                (
+                    1002,
+                    "city",
+                    false,
+                    AllType::Primitive(PrimitiveType::String)
+                ),
+            ],
+            fields
+        );
+
+        let bound = spec.bind(&schema()).unwrap();
+        assert_eq!(3, bound.fields().len());
+        assert_eq!(&PrimitiveType::Long, bound.fields()[1].source_type());
+        assert_eq!(
+            vec![3, 0],
+            bound.fields()[2].accessor().unwrap().positions()
+        );
+        assert!(!bound.is_unpartitioned());
+    }
+
+    #[test]
+    fn test_builder() {
+        let spec = PartitionSpec::builder(&schema())
+            .with_spec_id(2)
+            .with_last_partition_id(1001)
+            .identity("location.city")
+            .day("ts")
+            .bucket("id", 16)
+            .truncate("location.city", 2)
+            .add_with_name("id", "shard", Transform::Bucket(4))
+            .build()
+            .unwrap();
+        assert_eq!(2, spec.spec_id);
+        let fields: Vec<_> = spec
+            .fields
+            .iter()
+            .map(|f| {
+                (
+                    f.source_id,
+                    f.field_id,
+                    f.name.as_str(),
+                    f.transform.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (5, 1002, "location.city", Transform::Identity),
+                (2, 1003, "ts_day", Transform::Day),
+                (1, 1004, "id_bucket_16", Transform::Bucket(16)),
+                (5, 1005, "location.city_trunc_2", Transform::Truncate(2)),
+                (1, 1006, "shard", Transform::Bucket(4)),
+            ],
+            fields
+        );
+        let spec = PartitionSpec::builder(&schema())
+            .year("ts")
+            .build()
+            .unwrap();
+        assert_eq!(PARTITION_DATA_ID_START, spec.fields[0].field_id);
+
+        // Ids are assigned after the last partition id wherever it is set.
+        let spec = PartitionSpec::builder(&schema())
+            .identity("id")
+            .with_last_partition_id(1005)
+            .day("ts")
+            .build()
+            .unwrap();
+        let ids: Vec<_> = spec.fields.iter().map(|f| f.field_id).collect();
+        assert_eq!(vec![1006, 1007], ids);
+        assert!(matches!(
+            PartitionSpec::builder(&schema())
+                .with_last_partition_id(i32::MAX)
+                .identity("id")
+                .build(),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        let build = |builder: PartitionSpecBuilder| builder.build().unwrap_err();
+        assert!(matches!(
+            build(PartitionSpec::builder(&schema()).identity("missing")),
+            Error::NotFound(_)
+        ));
+        assert!(matches!(
+            build(PartitionSpec::builder(&schema()).hour("ts")),
+            Error::Invalid(_)
+        ));
+        // Time transforms of the same source are redundant.
+        assert!(matches!(
+            build(PartitionSpec::builder(&schema()).day("ts").month("ts")),
+            Error::Invalid(_)
+        ));
+        assert!(matches!(
+            build(
+                PartitionSpec::builder(&schema())
+                    .bucket("id", 4)
+                    .bucket("id", 4)
+            ),
+            Error::Invalid(_)
+        ));
+        assert!(matches!(
+            build(
+                PartitionSpec::builder(&schema())
+                    .identity("id")
+                    .add_with_name("ts", "id", Transform::Day)
+            ),
+            Error::Invalid(_)
+        ));
+        // Only identity partitions may share a name with their source column.
+        assert!(matches!(
+            build(PartitionSpec::builder(&schema()).add_with_name(
+                "id",
+                "id",
+                Transform::Bucket(2)
+            )),
+            Error::Invalid(_)
+        ));
+        // The first error is reported.
+        assert!(matches!(
+            build(
+                PartitionSpec::builder(&schema())
+                    .identity("missing")
+                    .hour("ts")
+            ),
+            Error::NotFound(_)
+        ));
+    }
+
+    #[test]
+    fn test_partition_path() {
+        let spec = PartitionSpec::builder(&schema())
+            .day("ts")
+            .identity("location.city")
+            .bucket("id", 16)
+            .build()
+            .unwrap();
+        let partition = vec![
+            Some(Value::Date(19723)),
+            Some(Value::String("São Paulo/1+1".to_string())),
+            None,
+        ];
+        let path = spec.partition_to_path(&schema(), &partition).unwrap();
+        assert_eq!(
+            "ts_day=2024-01-01/location.city=S%C3%A3o+Paulo%2F1%2B1/id_bucket_16=null",
+            path
+        );
+        assert_eq!(
+            partition,
+            spec.parse_partition_path(&schema(), &path).unwrap()
+        );
+        assert_eq!(
+            partition,
+            spec.parse_partition_path(
+                &schema(),
+                "/ts_day=2024-01-01/location.city=S%C3%A3o%20Paulo%2F1%2B1/id_bucket_16=__HIVE_DEFAULT_PARTITION__/"
+            )
+            .unwrap()
+        );
+        assert!(spec
+            .parse_partition_path(&schema(), "ts_day=2024-01-01/city=x/id_bucket_16=1")
+            .is_err());
+        assert!(spec
+            .parse_partition_path(&schema(), "ts_day=2024-01-01")
+            .is_err());
+        assert!(spec.partition_to_path(&schema(), &partition[..2]).is_err());
+    }
+
+    #[test]
+    fn test_partition_key() {
+        let spec = PartitionSpec::builder(&schema())
+            .bucket("id", 16)
+            .identity("location.city")
+            .build()
+            .unwrap()
+            .bind(&schema())
+            .unwrap();
+        let row = |id: i64, city: Option<&str>| {
+            vec![
+                Some(Value::Long(id)),
+                Some(Value::Date(0)),
+                None,
+                Some(Value::Struct(vec![
+                    city.map(|c| Value::String(c.to_string()))
+                ])),
+                None,
+            ]
+        };
+        let key = spec.partition_key(&row(34, Some("Paris"))).unwrap();
+        let bucket = Transform::Bucket(16).apply(Some(&Value::Long(34))).unwrap();
+        assert_eq!(
+            &[bucket, Some(Value::String("Paris".to_string()))],
+            key.values()
+        );
+        assert_eq!(key, spec.partition_key(&row(34, Some("Paris"))).unwrap());
+        assert_ne!(key, spec.partition_key(&row(34, None)).unwrap());
+        assert_eq!(
+            Some(&Value::String("Paris".to_string())),
+            StructLike::get(&key, 1)
+        );
+        assert!(spec
+            .partition_to_path(key.values())
+            .unwrap()
+            .ends_with("/location.city=Paris"));
+    }
+
+    #[test]
+    fn test_bind_errors() {
+        let spec = |source_id| PartitionSpec {
+            spec_id: 0,
+            fields: vec![PartitionField {
+                source_id,
+                field_id: 1000,
+                name: "p".to_string(),
+                transform: Transform::Identity,
+            }],
+        };
+        assert!(matches!(spec(9).bind(&schema()), Err(Error::NotFound(_))));
+        assert!(matches!(spec(4).bind(&schema()), Err(Error::Invalid(_))));
+        assert!(matches!(spec(8).bind(&schema()), Err(Error::Invalid(_))));
+    }
+
+    #[test]
+    fn test_bind_void_without_source() {
+        // A v1 spec whose second field was removed after its source column was dropped.
+        let spec: PartitionSpec = serde_json::from_str(
+            r#"
+            {
+                "spec-id": 1,
+                "fields": [
+                    { "source-id": 1, "field-id": 1000, "name": "id_bucket", "transform": "bucket[16]" },
+                    { "source-id": 9, "field-id": 1001, "name": "dropped", "transform": "void" }
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+        let bound = spec.bind(&schema()).unwrap();
+        let void = &bound.fields()[1];
+        assert_eq!(None, void.accessor());
+        assert_eq!(&PrimitiveType::Unknown, void.source_type());
+        assert_eq!(
+            AllType::Primitive(PrimitiveType::Unknown),
+            bound.partition_type().fields[1].field_type
+        );
+
+        let row = vec![
+            Some(Value::Long(34)),
+            Some(Value::Date(0)),
+            None,
+            None,
+            None,
+        ];
+        let key = bound.partition_key(&row).unwrap();
+        assert_eq!(None, key.values()[1]);
+        let path = bound.partition_to_path(key.values()).unwrap();
+        assert!(path.ends_with("/dropped=null"));
+        assert_eq!(
+            key.values(),
+            spec.parse_partition_path(&schema(), &path).unwrap()
+        );
+    }
 }
@@ -0,0 +1,533 @@
+/*!
+Projection of row filters onto partition fields, used to prune partitions.
+
+An inclusive projection of a predicate is true for a partition whenever the predicate may be true
+for a row in the partition, so partitions for which it is false can be skipped. A strict
+projection is only true for a partition when the predicate is true for every row in it.
+
+[Transform::project] and [Transform::project_strict] project a single predicate through a
+transform; [BoundPartitionSpec::project_inclusive] and [BoundPartitionSpec::project_strict]
+project whole expressions through every field of a spec.
+
+For example with a `day` partition field `ts_day` of `ts`, `ts > X` projects inclusively to
+`ts_day >= day(X + 1)`, and with `truncate[2]` of `name`, `name startsWith 'abc'` projects to
+`name_trunc startsWith 'ab'`.
+*/
+use crate::error::{Error, Result};
+use crate::model::expression::{BinaryOperator, Expression, Predicate, SetOperator, UnaryOperator};
+use crate::model::partition::{BoundPartitionSpec, Transform};
+use crate::model::values::Value;
+
+impl Transform {
+    /// Project `predicate` on the source of a partition field to a predicate on the partition
+    /// field with id `partition_field_id` that is true for any partition that may contain
+    /// matching rows. `None` means the partition field cannot be used to filter partitions.
+    pub fn project(
+        &self,
+        partition_field_id: i32,
+        predicate: &Predicate,
+    ) -> Result<Option<Predicate>> {
+        self.project_with(partition_field_id, predicate, false)
+    }
+
+    /// Project `predicate` on the source of a partition field to a predicate on the partition
+    /// field with id `partition_field_id` that is only true for partitions where every row
+    /// matches. `None` means no partition can be shown to only contain matching rows.
+    pub fn project_strict(
+        &self,
+        partition_field_id: i32,
+        predicate: &Predicate,
+    ) -> Result<Option<Predicate>> {
+        self.project_with(partition_field_id, predicate, true)
+    }
+
+    fn project_with(
+        &self,
+        field_id: i32,
+        predicate: &Predicate,
+        strict: bool,
+    ) -> Result<Option<Predicate>> {
+        if *self == Transform::Void {
+            return Ok(None);
+        }
+        match predicate {
+            Predicate::Unary { op, .. } => Ok(match (self, op) {
+                (_, UnaryOperator::IsNull | UnaryOperator::NotNull) | (Transform::Identity, _) => {
+                    Some(Predicate::Unary { op: *op, field_id })
+                }
+                _ => None,
+            }),
+            Predicate::Binary { op, literal, .. } => match self {
+                Transform::Identity => Ok(Some(Predicate::Binary {
+                    op: *op,
+                    field_id,
+                    literal: literal.clone(),
+                })),
+                Transform::Bucket(_) => Ok(match (op, strict) {
+                    (BinaryOperator::Eq, false) | (BinaryOperator::NotEq, true) => {
+                        Some(self.binary(*op, field_id, literal)?)
+                    }
+                    _ => None,
+                }),
+                Transform::Truncate(width)
+                    if matches!(literal, Value::String(_) | Value::Binary(_)) =>
+                {
+                    self.project_array(*width, field_id, *op, literal, strict)
+                }
+                _ => self.project_ordered(field_id, *op, literal, strict),
+            },
+            Predicate::Set { op, literals, .. } => Ok(match (self, op, strict) {
+                (Transform::Identity, _, _) => Some(Predicate::Set {
+                    op: *op,
+                    field_id,
+                    literals: literals.clone(),
+                }),
+                (_, SetOperator::In, false) | (_, SetOperator::NotIn, true) => {
+                    let mut transformed = Vec::with_capacity(literals.len());
+                    for literal in literals {
+                        let value = self.apply_literal(literal)?;
+                        if !transformed.contains(&value) {
+                            transformed.push(value);
+                        }
+                    }
+                    Some(Predicate::Set {
+                        op: *op,
+                        field_id,
+                        literals: transformed,
+                    })
+                }
+                _ => None,
+            }),
+        }
+    }
+
+    fn apply_literal(&self, literal: &Value) -> Result<Value> {
+        self.apply(Some(literal))?
+            .ok_or_else(|| Error::Invalid(format!("{self:?} of {literal:?} is null")))
+    }
+
+    fn binary(&self, op: BinaryOperator, field_id: i32, literal: &Value) -> Result<Predicate> {
+        Ok(Predicate::Binary {
+            op,
+            field_id,
+            literal: self.apply_literal(literal)?,
+        })
+    }
+
+    /// Projection through transforms that preserve the order of integers, decimals, dates and
+    /// times: truncating numbers and the date and time transforms.
+    fn project_ordered(
+        &self,
+        field_id: i32,
+        op: BinaryOperator,
+        literal: &Value,
+        strict: bool,
+    ) -> Result<Option<Predicate>> {
+        use BinaryOperator::*;
+        let (op, boundary) = match (op, strict) {
+            (LessThan, false) => (LessThanOrEq, adjacent(literal, -1)),
+            (LessThanOrEq, false) => (LessThanOrEq, Some(literal.clone())),
+            (GreaterThan, false) => (GreaterThanOrEq, adjacent(literal, 1)),
+            (GreaterThanOrEq, false) => (GreaterThanOrEq, Some(literal.clone())),
+            (Eq, false) => (Eq, Some(literal.clone())),
+            (LessThan, true) => (LessThan, Some(literal.clone())),
+            (LessThanOrEq, true) => (LessThan, adjacent(literal, 1)),
+            (GreaterThan, true) => (GreaterThan, Some(literal.clone())),
+            (GreaterThanOrEq, true) => (GreaterThan, adjacent(literal, -1)),
+            (NotEq, true) => (NotEq, Some(literal.clone())),
+            _ => return Ok(None),
+        };
+        boundary
+            .map(|boundary| self.binary(op, field_id, &boundary))
+            .transpose()
+    }
+
+    /// Projection through truncating strings and binary, where the partition value is a prefix.
+    fn project_array(
+        &self,
+        width: u32,
+        field_id: i32,
+        op: BinaryOperator,
+        literal: &Value,
+        strict: bool,
+    ) -> Result<Option<Predicate>> {
+        use BinaryOperator::*;
+        let length = match literal {
+            Value::String(s) => s.chars().count(),
+            Value::Binary(b) => b.len(),
+            _ => unreachable!(),
+        };
+        let width = width as usize;
+        let untransformed = |op| {
+            Ok(Some(Predicate::Binary {
+                op,
+                field_id,
+                literal: literal.clone(),
+            }))
+        };
+        match (op, strict) {
+            (LessThan | LessThanOrEq, false) => {
+                self.binary(LessThanOrEq, field_id, literal).map(Some)
+            }
+            (GreaterThan | GreaterThanOrEq, false) => {
+                self.binary(GreaterThanOrEq, field_id, literal).map(Some)
+            }
+            (Eq, false) | (StartsWith, false) | (NotEq, true) | (NotStartsWith, true) => {
+                self.binary(op, field_id, literal).map(Some)
+            }
+            (LessThan | LessThanOrEq, true) => self.binary(LessThan, field_id, literal).map(Some),
+            (GreaterThan | GreaterThanOrEq, true) => {
+                self.binary(GreaterThan, field_id, literal).map(Some)
+            }
+            // The literal is only comparable to partition values when it is not truncated.
+            (NotStartsWith, false) if length < width => untransformed(NotStartsWith),
+            (NotStartsWith, false) if length == width => untransformed(NotEq),
+            (StartsWith, true) if length < width => untransformed(StartsWith),
+            (StartsWith, true) if length == width => untransformed(Eq),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The value next to `literal` in the order of its type, or `None` if there is none.
+fn adjacent(literal: &Value, delta: i32) -> Option<Value> {
+    Some(match literal {
+        Value::Int(v) => Value::Int(v.checked_add(delta)?),
+        Value::Date(v) => Value::Date(v.checked_add(delta)?),
+        Value::Long(v) => Value::Long(v.checked_add(i64::from(delta))?),
+        Value::Time(v) => Value::Time(v.checked_add(i64::from(delta))?),
+        Value::Timestamp(v) => Value::Timestamp(v.checked_add(i64::from(delta))?),
+        Value::Timestampz(v) => Value::Timestampz(v.checked_add(i64::from(delta))?),
+        Value::TimestampNs(v) => Value::TimestampNs(v.checked_add(i64::from(delta))?),
+        Value::TimestampzNs(v) => Value::TimestampzNs(v.checked_add(i64::from(delta))?),
+        Value::Decimal(v) => Value::Decimal(v.checked_add(i128::from(delta))?),
+        _ => return None,
+    })
+}
+
+impl BoundPartitionSpec {
+    /// Project a filter on the rows of a table to a filter on partitions that is true for every
+    /// partition that may contain matching rows.
+    pub fn project_inclusive(&self, expression: &Expression) -> Result<Expression> {
+        self.project_expression(expression.clone().rewrite_not(), false)
+    }
+
+    /// Project a filter on the rows of a table to a filter on partitions that is only true for
+    /// partitions in which every row matches.
+    pub fn project_strict(&self, expression: &Expression) -> Result<Expression> {
+        self.project_expression(expression.clone().rewrite_not(), true)
+    }
+
+    fn project_expression(&self, expression: Expression, strict: bool) -> Result<Expression> {
+        Ok(match expression {
+            Expression::And(left, right) => self
+                .project_expression(*left, strict)?
+                .and(self.project_expression(*right, strict)?),
+            Expression::Or(left, right) => self
+                .project_expression(*left, strict)?
+                .or(self.project_expression(*right, strict)?),
+            Expression::Predicate(predicate) => {
+                let fields = self
+                    .fields()
+                    .iter()
+                    .filter(|f| f.field().source_id == predicate.field_id());
+                // Every partition field of the source constrains the partitions that may match,
+                // while any one of them can show that all rows of a partition match.
+                if strict {
+                    let mut projected = Expression::AlwaysFalse;
+                    for f in fields {
+                        if let Some(p) = f
+                            .field()
+                            .transform
+                            .project_strict(f.field().field_id, &predicate)?
+                        {
+                            projected = projected.or(p.into());
+                        }
+                    }
+                    projected
+                } else {
+                    let mut projected = Expression::AlwaysTrue;
+                    for f in fields {
+                        if let Some(p) = f
+                            .field()
+                            .transform
+                            .project(f.field().field_id, &predicate)?
+                        {
+                            projected = projected.and(p.into());
+                        }
+                    }
+                    projected
+                }
+            }
+            e => e,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::partition::PartitionSpec;
+    use crate::model::schema::SchemaV2;
+
+    fn binary(op: BinaryOperator, field_id: i32, literal: Value) -> Predicate {
+        Predicate::Binary {
+            op,
+            field_id,
+            literal,
+        }
+    }
+
+    #[test]
+    fn test_project_temporal() {
+        use BinaryOperator::*;
+        // 2017-11-16T22:31:08
+        let ts = 1_510_871_468_000_000;
+        let day = Transform::Day;
+        let cases = vec![
+            (
+                LessThan,
+                Some((LessThanOrEq, 17486)),
+                Some((LessThan, 17486)),
+            ),
+            (
+                LessThanOrEq,
+                Some((LessThanOrEq, 17486)),
+                Some((LessThan, 17486)),
+            ),
+            (
+                GreaterThan,
+                Some((GreaterThanOrEq, 17486)),
+                Some((GreaterThan, 17486)),
+            ),
+            (
+                GreaterThanOrEq,
+                Some((GreaterThanOrEq, 17486)),
+                Some((GreaterThan, 17486)),
+            ),
+            (Eq, Some((Eq, 17486)), None),
+            (NotEq, None, Some((NotEq, 17486))),
+        ];
+        for (op, inclusive, strict) in cases {
+            let predicate = binary(op, 1, Value::Timestamp(ts));
+            let expected = |p: Option<(BinaryOperator, i32)>| {
+                p.map(|(op, v)| binary(op, 1000, Value::Date(v)))
+            };
+            assert_eq!(
+                expected(inclusive),
+                day.project(1000, &predicate).unwrap(),
+                "{op:?}"
+            );
+            assert_eq!(
+                expected(strict),
+                day.project_strict(1000, &predicate).unwrap(),
+                "{op:?}"
+            );
+        }
+
+        // At a day boundary the adjacent value is in another partition.
+        let midnight = binary(LessThan, 1, Value::Timestamp(17486 * 86_400_000_000));
+        assert_eq!(
+            Some(binary(LessThanOrEq, 1000, Value::Date(17485))),
+            day.project(1000, &midnight).unwrap()
+        );
+        let midnight = binary(GreaterThanOrEq, 1, Value::Timestamp(17486 * 86_400_000_000));
+        assert_eq!(
+            Some(binary(GreaterThan, 1000, Value::Date(17485))),
+            day.project_strict(1000, &midnight).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_project_bucket() {
+        let bucket = Transform::Bucket(16);
+        let eq = binary(BinaryOperator::Eq, 1, Value::Int(34));
+        let expected = bucket.apply(Some(&Value::Int(34))).unwrap().unwrap();
+        assert_eq!(
+            Some(binary(BinaryOperator::Eq, 1000, expected.clone())),
+            bucket.project(1000, &eq).unwrap()
+        );
+        assert_eq!(None, bucket.project_strict(1000, &eq).unwrap());
+        let lt = binary(BinaryOperator::LessThan, 1, Value::Int(34));
+        assert_eq!(None, bucket.project(1000, &lt).unwrap());
+        let not_eq = binary(BinaryOperator::NotEq, 1, Value::Int(34));
+        assert_eq!(
+            Some(binary(BinaryOperator::NotEq, 1000, expected)),
+            bucket.project_strict(1000, &not_eq).unwrap()
+        );
+
+        let in_set = Predicate::Set {
+            op: SetOperator::In,
+            field_id: 1,
+            literals: vec![Value::Int(34), Value::Int(34), Value::Int(35)],
+        };
+        match bucket.project(1000, &in_set).unwrap() {
+            Some(Predicate::Set { op, literals, .. }) => {
+                assert_eq!(SetOperator::In, op);
+                assert_eq!(2, literals.len());
+            }
+            other => panic!("unexpected projection {other:?}"),
+        }
+        let is_null = Predicate::Unary {
+            op: UnaryOperator::IsNull,
+            field_id: 1,
+        };
+        assert_eq!(
+            Some(Predicate::Unary {
+                op: UnaryOperator::IsNull,
+                field_id: 1000
+            }),
+            bucket.project_strict(1000, &is_null).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_project_truncate() {
+        use BinaryOperator::*;
+        let truncate = Transform::Truncate(10);
+        assert_eq!(
+            Some(binary(LessThanOrEq, 1000, Value::Int(0))),
+            truncate
+                .project(1000, &binary(LessThan, 1, Value::Int(10)))
+                .unwrap()
+        );
+        assert_eq!(
+            Some(binary(LessThan, 1000, Value::Int(20))),
+            truncate
+                .project_strict(1000, &binary(LessThanOrEq, 1, Value::Int(19)))
+                .unwrap()
+        );
+        assert_eq!(
+            Some(binary(GreaterThanOrEq, 1000, Value::Decimal(1070))),
+            truncate
+                .project(1000, &binary(GreaterThan, 1, Value::Decimal(1069)))
+                .unwrap()
+        );
+
+        let string = |s: &str| Value::String(s.to_string());
+        let truncate = Transform::Truncate(2);
+        assert_eq!(
+            Some(binary(StartsWith, 1000, string("ab"))),
+            truncate
+                .project(1000, &binary(StartsWith, 1, string("abc")))
+                .unwrap()
+        );
+        assert_eq!(
+            None,
+            truncate
+                .project_strict(1000, &binary(StartsWith, 1, string("abc")))
+                .unwrap()
+        );
+        assert_eq!(
+            Some(binary(Eq, 1000, string("ab"))),
+            truncate
+                .project_strict(1000, &binary(StartsWith, 1, string("ab")))
+                .unwrap()
+        );
+        assert_eq!(
+            Some(binary(StartsWith, 1000, string("a"))),
+            truncate
+                .project_strict(1000, &binary(StartsWith, 1, string("a")))
+                .unwrap()
+        );
+        assert_eq!(
+            Some(binary(NotEq, 1000, string("ab"))),
+            truncate
+                .project(1000, &binary(NotStartsWith, 1, string("ab")))
+                .unwrap()
+        );
+        assert_eq!(
+            None,
+            truncate
+                .project(1000, &binary(NotStartsWith, 1, string("abc")))
+                .unwrap()
+        );
+        assert_eq!(
+            Some(binary(LessThanOrEq, 1000, string("ab"))),
+            truncate
+                .project(1000, &binary(LessThan, 1, string("abc")))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_project_identity_and_void() {
+        let predicate = Predicate::Unary {
+            op: UnaryOperator::IsNan,
+            field_id: 1,
+        };
+        assert_eq!(
+            Some(Predicate::Unary {
+                op: UnaryOperator::IsNan,
+                field_id: 1000
+            }),
+            Transform::Identity
+                .project_strict(1000, &predicate)
+                .unwrap()
+        );
+        assert_eq!(None, Transform::Void.project(1000, &predicate).unwrap());
+        assert_eq!(None, Transform::Day.project(1000, &predicate).unwrap());
+    }
+
+    #[test]
+    fn test_project_expression() {
+        let schema: SchemaV2 = serde_json::from_str(
+            r#"
+            {
+                "schema-id": 0,
+                "type": "struct",
+                "fields": [
+                    { "id": 1, "name": "id", "required": true, "field_type": "long" },
+                    { "id": 2, "name": "ts", "required": true, "field_type": "timestamp" },
+                    { "id": 3, "name": "data", "required": false, "field_type": "string" }
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+        let spec = PartitionSpec::builder(&schema)
+            .day("ts")
+            .bucket("id", 16)
+            .build()
+            .unwrap()
+            .bind(&schema)
+            .unwrap();
+        let ts = 1_510_871_468_000_000;
+        let filter = Expression::from(binary(BinaryOperator::GreaterThan, 2, Value::Timestamp(ts)))
+            .and(binary(BinaryOperator::Eq, 1, Value::Long(5)).into())
+            .and(binary(BinaryOperator::Eq, 3, Value::String("a".to_string())).into());
+        let bucket = Transform::Bucket(16)
+            .apply(Some(&Value::Long(5)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Expression::from(binary(
+                BinaryOperator::GreaterThanOrEq,
+                1000,
+                Value::Date(17486)
+            ))
+            .and(binary(BinaryOperator::Eq, 1001, bucket).into()),
+            spec.project_inclusive(&filter).unwrap()
+        );
+        assert_eq!(
+            Expression::AlwaysFalse,
+            spec.project_strict(&filter).unwrap()
+        );
+
+        // Negations are pushed into predicates before projecting.
+        let filter = Expression::from(binary(
+            BinaryOperator::LessThanOrEq,
+            2,
+            Value::Timestamp(ts),
+        ))
+        .negate();
+        assert_eq!(
+            Expression::from(binary(
+                BinaryOperator::GreaterThan,
+                1000,
+                Value::Date(17486)
+            )),
+            spec.project_strict(&filter).unwrap()
+        );
+    }
+}
@@ -0,0 +1,387 @@
+/*!
+Management of the branches and tags in the [refs](crate::model::table::TableMetadataV2#structfield.refs)
+of a table.
+
+Tags are immutable: they can be renamed or removed, but never point to another snapshot. Branches
+can be replaced by any snapshot of the table, or fast-forwarded to a descendant of their head.
+The `main` branch tracks the current snapshot of the table, so moving it changes the
+`current_snapshot_id` and is recorded in the snapshot log for time travel, at the commit
+timestamp passed by the caller. It can be neither renamed nor removed.
+
+New refs have retention settings of 0, so the table defaults apply when the snapshots of the
+table are [expired](crate::model::expiration).
+*/
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::model::history::MAIN_BRANCH;
+use crate::model::snapshot::{Reference, Retention};
+use crate::model::table::{SnapshotLog, TableMetadataV2};
+
+impl TableMetadataV2 {
+    /// Create the branch `name` pointing to `snapshot_id`. Creating `main` makes the snapshot
+    /// current at `timestamp_ms`, in milliseconds from the unix epoch.
+    pub fn create_branch(&mut self, name: &str, snapshot_id: i64, timestamp_ms: i64) -> Result<()> {
+        self.create_ref(
+            name,
+            snapshot_id,
+            Retention::Branch {
+                min_snapshots_to_keep: 0,
+                max_snapshot_age_ms: 0,
+                max_ref_age_ms: 0,
+            },
+            timestamp_ms,
+        )
+    }
+
+    /// Create the tag `name` pointing to `snapshot_id`.
+    pub fn create_tag(&mut self, name: &str, snapshot_id: i64) -> Result<()> {
+        if name == MAIN_BRANCH {
+            return Err(Error::Invalid(format!("{MAIN_BRANCH} must be a branch")));
+        }
+        // Tags are never main, so the timestamp is not used.
+        self.create_ref(name, snapshot_id, Retention::Tag { max_ref_age_ms: 0 }, 0)
+    }
+
+    fn create_ref(
+        &mut self,
+        name: &str,
+        snapshot_id: i64,
+        retention: Retention,
+        timestamp_ms: i64,
+    ) -> Result<()> {
+        if self.ref_snapshot_id(name).is_some() {
+            return Err(Error::Invalid(format!("ref {name} already exists")));
+        }
+        self.check_snapshot(snapshot_id)?;
+        if name == MAIN_BRANCH {
+            self.set_current_snapshot(snapshot_id, timestamp_ms)?;
+        }
+        self.refs.get_or_insert_with(HashMap::new).insert(
+            name.to_string(),
+            Reference {
+                snapshot_id,
+                retention,
+            },
+        );
+        Ok(())
+    }
+
+    /// Point the branch `name` to `snapshot_id`, which can be any snapshot of the table.
+    /// Moving `main` makes the snapshot current at `timestamp_ms`.
+    pub fn replace_branch(
+        &mut self,
+        name: &str,
+        snapshot_id: i64,
+        timestamp_ms: i64,
+    ) -> Result<()> {
+        self.check_snapshot(snapshot_id)?;
+        self.set_branch(name, snapshot_id, timestamp_ms)
+    }
+
+    /// Point the branch `name` to the snapshot the ref `to` points to. The head of the branch
+    /// must be an ancestor of that snapshot. Moving `main` makes the snapshot current at
+    /// `timestamp_ms`.
+    pub fn fast_forward_branch(&mut self, name: &str, to: &str, timestamp_ms: i64) -> Result<()> {
+        let snapshot_id = self
+            .ref_snapshot_id(to)
+            .ok_or_else(|| Error::NotFound(format!("ref {to} is not in the table")))?;
+        let head = self
+            .ref_snapshot_id(name)
+            .ok_or_else(|| Error::NotFound(format!("ref {name} is not in the table")))?;
+        if !self.is_ancestor_of(snapshot_id, head) {
+            return Err(Error::Invalid(format!(
+                "cannot fast-forward {name}: snapshot {head} is not an ancestor of {to}"
+            )));
+        }
+        self.set_branch(name, snapshot_id, timestamp_ms)
+    }
+
+    fn set_branch(&mut self, name: &str, snapshot_id: i64, timestamp_ms: i64) -> Result<()> {
+        if matches!(self.ref_mut(name)?.retention, Retention::Tag { .. }) {
+            return Err(Error::Invalid(format!("tag {name} cannot be changed")));
+        }
+        if name == MAIN_BRANCH {
+            self.set_current_snapshot(snapshot_id, timestamp_ms)?;
+        }
+        self.ref_mut(name)?.snapshot_id = snapshot_id;
+        Ok(())
+    }
+
+    /// Make `snapshot_id` the current snapshot at `timestamp_ms`, adding it to the snapshot
+    /// log. The timestamp cannot be before the last update of the table, so that the log stays
+    /// in order.
+    fn set_current_snapshot(&mut self, snapshot_id: i64, timestamp_ms: i64) -> Result<()> {
+        if self.current_snapshot_id == Some(snapshot_id) {
+            return Ok(());
+        }
+        if timestamp_ms < self.last_updated_ms {
+            return Err(Error::Invalid(format!(
+                "cannot move {MAIN_BRANCH} at {timestamp_ms}, before the last update of the table at {}",
+                self.last_updated_ms
+            )));
+        }
+        if self.snapshot_log.is_none() {
+            // Time travel without a log follows the ancestry of the current snapshot, so the
+            // log starts from that ancestry.
+            let mut log: Vec<_> = self
+                .current_snapshot_id
+                .into_iter()
+                .flat_map(|id| self.ancestors(id))
+                .map(|s| SnapshotLog {
+                    snapshot_id: s.snapshot_id,
+                    timestamp_ms: s.timestamp_ms,
+                })
+                .collect();
+            log.reverse();
+            self.snapshot_log = Some(log);
+        }
+        self.current_snapshot_id = Some(snapshot_id);
+        self.last_updated_ms = timestamp_ms;
+        self.snapshot_log
+            .get_or_insert_with(Vec::new)
+            .push(SnapshotLog {
+                snapshot_id,
+                timestamp_ms,
+            });
+        Ok(())
+    }
+
+    /// Rename the branch or tag `from` to `to`.
+    pub fn rename_ref(&mut self, from: &str, to: &str) -> Result<()> {
+        if from == MAIN_BRANCH || to == MAIN_BRANCH {
+            return Err(Error::Invalid(format!("cannot rename {from} to {to}")));
+        }
+        if self.ref_snapshot_id(to).is_some() {
+            return Err(Error::Invalid(format!("ref {to} already exists")));
+        }
+        let reference = self.take_ref(from)?;
+        self.refs
+            .get_or_insert_with(HashMap::new)
+            .insert(to.to_string(), reference);
+        Ok(())
+    }
+
+    /// Remove the branch or tag `name`. The snapshots it points to are left to expire.
+    pub fn remove_ref(&mut self, name: &str) -> Result<()> {
+        if name == MAIN_BRANCH {
+            return Err(Error::Invalid(format!("cannot remove {MAIN_BRANCH}")));
+        }
+        self.take_ref(name).map(|_| ())
+    }
+
+    /// Set the retention settings of the ref `name`. Settings of 0 use the table defaults.
+    pub fn set_retention(&mut self, name: &str, retention: Retention) -> Result<()> {
+        let invalid = match retention {
+            Retention::Branch {
+                min_snapshots_to_keep,
+                max_snapshot_age_ms,
+                max_ref_age_ms,
+            } => min_snapshots_to_keep < 0 || max_snapshot_age_ms < 0 || max_ref_age_ms < 0,
+            Retention::Tag { max_ref_age_ms } => max_ref_age_ms < 0,
+        };
+        if invalid {
+            return Err(Error::Invalid(format!(
+                "retention settings of {name} must not be negative"
+            )));
+        }
+        let reference = self.ref_mut(name)?;
+        if matches!(reference.retention, Retention::Tag { .. })
+            != matches!(retention, Retention::Tag { .. })
+        {
+            return Err(Error::Invalid(format!(
+                "cannot change the type of ref {name}"
+            )));
+        }
+        reference.retention = retention;
+        Ok(())
+    }
+
+    fn check_snapshot(&self, snapshot_id: i64) -> Result<()> {
+        self.snapshot(snapshot_id)
+            .map(|_| ())
+            .ok_or_else(|| Error::NotFound(format!("snapshot {snapshot_id} is not in the table")))
+    }
+
+    /// The ref `name`, adding the `main` branch if the table only has a current snapshot.
+    fn ref_mut(&mut self, name: &str) -> Result<&mut Reference> {
+        match self.current_snapshot_id {
+            Some(snapshot_id) if name == MAIN_BRANCH => Ok(self
+                .refs
+                .get_or_insert_with(HashMap::new)
+                .entry(name.to_string())
+                .or_insert_with(|| Reference {
+                    snapshot_id,
+                    retention: Retention::Branch {
+                        min_snapshots_to_keep: 0,
+                        max_snapshot_age_ms: 0,
+                        max_ref_age_ms: 0,
+                    },
+                })),
+            _ => self
+                .refs
+                .as_mut()
+                .and_then(|refs| refs.get_mut(name))
+                .ok_or_else(|| Error::NotFound(format!("ref {name} is not in the table"))),
+        }
+    }
+
+    fn take_ref(&mut self, name: &str) -> Result<Reference> {
+        self.refs
+            .as_mut()
+            .and_then(|refs| refs.remove(name))
+            .ok_or_else(|| Error::NotFound(format!("ref {name} is not in the table")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::history::AsOf;
+    use crate::model::tests::metadata_with_snapshots;
+
+    /// Snapshots 1 <- 2 <- 3 and 2 <- 4, with main at 3 and no refs.
+    fn metadata() -> TableMetadataV2 {
+        let mut metadata = metadata_with_snapshots(&[
+            (1, None, 1000),
+            (2, Some(1), 2000),
+            (3, Some(2), 3000),
+            (4, Some(2), 4000),
+        ]);
+        metadata.current_snapshot_id = Some(3);
+        metadata
+    }
+
+    #[test]
+    fn test_branches() {
+        let mut metadata = metadata();
+        metadata.create_branch("audit", 2, 0).unwrap();
+        assert!(matches!(
+            metadata.create_branch("audit", 2, 0),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            metadata.create_branch(MAIN_BRANCH, 2, 5000),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            metadata.create_branch("other", 9, 0),
+            Err(Error::NotFound(_))
+        ));
+
+        // Write, audit, publish.
+        metadata.replace_branch("audit", 4, 0).unwrap();
+        assert!(matches!(
+            metadata.fast_forward_branch(MAIN_BRANCH, "audit", 5000),
+            Err(Error::Invalid(_))
+        ));
+        metadata.replace_branch(MAIN_BRANCH, 2, 5000).unwrap();
+        assert_eq!(Some(2), metadata.current_snapshot_id);
+        metadata
+            .fast_forward_branch(MAIN_BRANCH, "audit", 6000)
+            .unwrap();
+        assert_eq!(Some(4), metadata.current_snapshot_id);
+        assert_eq!(Some(4), metadata.ref_snapshot_id(MAIN_BRANCH));
+        assert_eq!(6000, metadata.last_updated_ms);
+
+        // The log starts from the ancestry of the snapshot that was current.
+        let log: Vec<_> = metadata
+            .snapshot_log
+            .iter()
+            .flatten()
+            .map(|e| (e.snapshot_id, e.timestamp_ms))
+            .collect();
+        assert_eq!(
+            vec![(1, 1000), (2, 2000), (3, 3000), (2, 5000), (4, 6000)],
+            log
+        );
+        let as_of = |timestamp_ms| {
+            metadata
+                .snapshot_as_of(&AsOf::TimestampMs(timestamp_ms))
+                .unwrap()
+                .snapshot_id
+        };
+        assert_eq!(1, as_of(1500));
+        assert_eq!(3, as_of(4999));
+        assert_eq!(2, as_of(5999));
+        assert_eq!(4, as_of(6000));
+
+        // Moves of main cannot go back in time, and leave the table unchanged when they fail.
+        assert!(matches!(
+            metadata.replace_branch(MAIN_BRANCH, 1, 5999),
+            Err(Error::Invalid(_))
+        ));
+        assert_eq!(Some(4), metadata.ref_snapshot_id(MAIN_BRANCH));
+        assert_eq!(Some(4), metadata.current_snapshot_id);
+
+        metadata.rename_ref("audit", "staging").unwrap();
+        assert_eq!(None, metadata.ref_snapshot_id("audit"));
+        assert_eq!(Some(4), metadata.ref_snapshot_id("staging"));
+        assert!(metadata.rename_ref(MAIN_BRANCH, "trunk").is_err());
+        metadata.remove_ref("staging").unwrap();
+        assert!(matches!(
+            metadata.remove_ref("staging"),
+            Err(Error::NotFound(_))
+        ));
+        assert!(metadata.remove_ref(MAIN_BRANCH).is_err());
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut metadata = metadata();
+        metadata.create_tag("v1", 1).unwrap();
+        assert!(matches!(
+            metadata.replace_branch("v1", 2, 0),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            metadata.fast_forward_branch("v1", MAIN_BRANCH, 0),
+            Err(Error::Invalid(_))
+        ));
+        assert!(metadata.create_tag(MAIN_BRANCH, 1).is_err());
+        // Branches can be fast-forwarded to tags.
+        metadata.create_branch("audit", 1, 0).unwrap();
+        metadata.fast_forward_branch("audit", "v1", 0).unwrap();
+
+        metadata
+            .set_retention("v1", Retention::Tag { max_ref_age_ms: 10 })
+            .unwrap();
+        assert!(matches!(
+            metadata.set_retention(
+                "v1",
+                Retention::Branch {
+                    min_snapshots_to_keep: 1,
+                    max_snapshot_age_ms: 10,
+                    max_ref_age_ms: 10,
+                }
+            ),
+            Err(Error::Invalid(_))
+        ));
+        assert!(metadata
+            .set_retention("v1", Retention::Tag { max_ref_age_ms: -1 })
+            .is_err());
+        metadata.rename_ref("v1", "v1.0").unwrap();
+        assert_eq!(
+            Some(&Retention::Tag { max_ref_age_ms: 10 }),
+            metadata
+                .refs
+                .as_ref()
+                .and_then(|refs| refs.get("v1.0"))
+                .map(|r| &r.retention)
+        );
+    }
+
+    #[test]
+    fn test_main_retention() {
+        let mut metadata = metadata();
+        let retention = || Retention::Branch {
+            min_snapshots_to_keep: 2,
+            max_snapshot_age_ms: 0,
+            max_ref_age_ms: 0,
+        };
+        metadata.set_retention(MAIN_BRANCH, retention()).unwrap();
+        let main = &metadata.refs.as_ref().unwrap()[MAIN_BRANCH];
+        assert_eq!(3, main.snapshot_id);
+        assert_eq!(retention(), main.retention);
+    }
+}
@@ -2,12 +2,18 @@
 A table’s [schema](https://iceberg.apache.org/spec/#schemas-and-data-types) is a list of named columns, represented by [SchemaV2].
 All data types are either [primitives](PrimitiveType) or nested types, which are [Map], [List], or [Struct]. A table [SchemaV2] is also a [Struct] type.
 */
+use std::collections::HashSet;
+
+use crate::error::Error;
+use crate::model::decimal;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{
     de::{self, IntoDeserializer},
     Deserialize, Deserializer, Serialize,
 };
+use uuid::Uuid;
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -38,7 +44,14 @@ pub enum PrimitiveType {
     /// Timestamp without timezone
     Timestamp,
     /// Timestamp with timezone
+    #[serde(rename = "timestamptz", alias = "timestampz")]
     Timestampz,
+    /// Timestamp with nanosecond precision, without timezone
+    #[serde(rename = "timestamp_ns")]
+    TimestampNs,
+    /// Timestamp with nanosecond precision, with timezone
+    #[serde(rename = "timestamptz_ns")]
+    TimestampzNs,
     /// Arbitrary-length character sequences
     String,
     /// Universally Unique Identifiers
@@ -47,6 +60,99 @@ pub enum PrimitiveType {
     Fixed(u64),
     /// Arbitrary-length byte array.
     Binary,
+    /// Default / null column type used when a more specific type is not known.
+    /// Values of this type are always null.
+    Unknown,
+    /// Semi-structured object, stored using the variant encoding.
+    Variant,
+    /// Geospatial features from the OGC Simple Feature Access model, with
+    /// edges interpolated as straight lines in the coordinate reference system.
+    Geometry {
+        /// The coordinate reference system, `OGC:CRS84` unless specified.
+        crs: String,
+    },
+    /// Geospatial features from the OGC Simple Feature Access model, with
+    /// edges interpolated on a spheroid.
+    Geography {
+        /// The coordinate reference system, `OGC:CRS84` unless specified.
+        crs: String,
+        /// The algorithm used to interpolate edges.
+        algorithm: EdgeAlgorithm,
+    },
+}
+
+/// The coordinate reference system of geospatial types that do not specify one.
+pub const DEFAULT_CRS: &str = "OGC:CRS84";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Algorithm for interpolating edges of a [Geography](PrimitiveType::Geography).
+pub enum EdgeAlgorithm {
+    /// Edges are the shortest path on a sphere.
+    #[default]
+    Spherical,
+    /// Edges are geodesics on an ellipsoid, using Vincenty's formula.
+    Vincenty,
+    /// Edges are geodesics on an ellipsoid, using Thomas' formula.
+    Thomas,
+    /// Edges are geodesics on an ellipsoid, using Andoyer-Lambert's formula.
+    Andoyer,
+    /// Edges are geodesics on an ellipsoid, using Karney's method.
+    Karney,
+}
+
+impl std::fmt::Display for EdgeAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EdgeAlgorithm::Spherical => "spherical",
+            EdgeAlgorithm::Vincenty => "vincenty",
+            EdgeAlgorithm::Thomas => "thomas",
+            EdgeAlgorithm::Andoyer => "andoyer",
+            EdgeAlgorithm::Karney => "karney",
+        };
+        f.write_str(name)
+    }
+}
+
+impl PrimitiveType {
+    /// A decimal type, with a precision between 1 and 38 and a scale no larger than the precision.
+    pub fn decimal(precision: i32, scale: u8) -> Result<PrimitiveType, Error> {
+        decimal::validate(precision, scale)?;
+        Ok(PrimitiveType::Decimal { precision, scale })
+    }
+
+    /// A fixed type of a positive `length` bytes.
+    pub fn fixed(length: u64) -> Result<PrimitiveType, Error> {
+        if length == 0 {
+            return Err(Error::Invalid("fixed length must be positive".to_string()));
+        }
+        Ok(PrimitiveType::Fixed(length))
+    }
+
+    /// Returns true if a column of this type can be promoted to the `target` type
+    /// without rewriting data files, according to the
+    /// [schema evolution](https://iceberg.apache.org/spec/#schema-evolution) rules.
+    /// A type can always be "promoted" to itself.
+    pub fn can_promote_to(&self, target: &PrimitiveType) -> bool {
+        use PrimitiveType::*;
+        match (self, target) {
+            (a, b) if a == b => true,
+            (Unknown, _) => true,
+            (Int, Long) | (Float, Double) => true,
+            (Date, Timestamp) | (Date, TimestampNs) => true,
+            (
+                Decimal {
+                    precision: p1,
+                    scale: s1,
+                },
+                Decimal {
+                    precision: p2,
+                    scale: s2,
+                },
+            ) => s1 == s2 && p2 > p1,
+            _ => false,
+        }
+    }
 }
 
 /// Serialize for PrimitiveType wit special handling for
@@ -63,6 +169,17 @@ impl Serialize for PrimitiveType {
                 scale: s,
             } => serializer.serialize_str(&format!("decimal({p},{s})")),
             Fixed(l) => serializer.serialize_str(&format!("fixed[{l}]")),
+            Geometry { crs } if crs == DEFAULT_CRS => serializer.serialize_str("geometry"),
+            Geometry { crs } => serializer.serialize_str(&format!("geometry({crs})")),
+            Geography { crs, algorithm } => match (crs.as_str(), algorithm) {
+                (DEFAULT_CRS, EdgeAlgorithm::Spherical) => serializer.serialize_str("geography"),
+                (crs, EdgeAlgorithm::Spherical) => {
+                    serializer.serialize_str(&format!("geography({crs})"))
+                }
+                (crs, algorithm) => {
+                    serializer.serialize_str(&format!("geography({crs}, {algorithm})"))
+                }
+            },
             _ => PrimitiveType::serialize(self, serializer),
         }
     }
@@ -80,6 +197,10 @@ impl<'de> Deserialize<'de> for PrimitiveType {
             deserialize_decimal(s.into_deserializer())
         } else if s.starts_with("fixed") {
             deserialize_fixed(s.into_deserializer())
+        } else if s.starts_with("geometry") {
+            deserialize_geometry(s.into_deserializer())
+        } else if s.starts_with("geography") {
+            deserialize_geography(s.into_deserializer())
         } else {
             PrimitiveType::deserialize(s.into_deserializer())
         }
@@ -117,9 +238,20 @@ where
                 .parse()
                 .map_err(|_| de::Error::custom("scale not u8"))
         })?;
+    decimal::validate(precision, scale).map_err(de::Error::custom)?;
     Ok(PrimitiveType::Decimal { precision, scale })
 }
 
+impl std::str::FromStr for PrimitiveType {
+    type Err = Error;
+
+    /// Parse the type from its name in the schema JSON, such as `decimal(9,2)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <PrimitiveType as Deserialize>::deserialize(s.into_deserializer())
+            .map_err(|e: de::value::Error| Error::Invalid(e.to_string()))
+    }
+}
+
 /// Deserialize for the Fixed PrimitiveType
 fn deserialize_fixed<'de, D>(deserializer: D) -> Result<PrimitiveType, D::Error>
 where
@@ -143,10 +275,58 @@ where
                 .parse()
                 .map_err(|_| de::Error::custom("length not u64"))
         })?;
+    if length == 0 {
+        return Err(de::Error::custom("fixed length must be positive"));
+    }
     Ok(PrimitiveType::Fixed(length))
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// Deserialize for the Geometry PrimitiveType
+fn deserialize_geometry<'de, D>(deserializer: D) -> Result<PrimitiveType, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let this = String::deserialize(deserializer)?;
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r#"^geometry(\((?P<crs>[^,()]+)\))?$"#).unwrap();
+    }
+
+    let caps = RE
+        .captures(&this)
+        .ok_or_else(|| de::Error::custom(format!("Invalid geometry format {}", this)))?;
+    let crs = caps
+        .name("crs")
+        .map_or(DEFAULT_CRS, |crs| crs.as_str().trim())
+        .to_string();
+    Ok(PrimitiveType::Geometry { crs })
+}
+
+/// Deserialize for the Geography PrimitiveType
+fn deserialize_geography<'de, D>(deserializer: D) -> Result<PrimitiveType, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let this = String::deserialize(deserializer)?;
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r#"^geography(\((?P<crs>[^,()]+)(,\s*(?P<a>[a-z]+))?\))?$"#).unwrap();
+    }
+
+    let caps = RE
+        .captures(&this)
+        .ok_or_else(|| de::Error::custom(format!("Invalid geography format {}", this)))?;
+    let crs = caps
+        .name("crs")
+        .map_or(DEFAULT_CRS, |crs| crs.as_str().trim())
+        .to_string();
+    let algorithm = match caps.name("a") {
+        Some(a) => EdgeAlgorithm::deserialize(a.as_str().into_deserializer())?,
+        None => EdgeAlgorithm::default(),
+    };
+    Ok(PrimitiveType::Geography { crs, algorithm })
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(untagged)]
 /// A union type of all allowed Schema types.
 pub enum AllType {
@@ -160,7 +340,7 @@ pub enum AllType {
     Map(Map),
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(tag = "type")]
 /// A struct is a tuple of typed values. Each field in the tuple is
 /// named and has an integer id that is unique in the table schema.
@@ -172,7 +352,79 @@ pub struct Struct {
     pub fields: Vec<StructField>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+impl Struct {
+    /// The field with `id`, searching nested structs and the structs within lists and maps.
+    pub fn field_by_id(&self, id: i32) -> Option<&StructField> {
+        self.fields.iter().find_map(|field| {
+            if field.id == id {
+                Some(field)
+            } else {
+                field.field_type.field_by_id(id)
+            }
+        })
+    }
+
+    /// The field with the dotted `name`, such as `location.city`, searching nested structs.
+    pub fn field_by_name(&self, name: &str) -> Option<&StructField> {
+        self.fields.iter().find_map(|field| {
+            if field.name == name {
+                return Some(field);
+            }
+            match (&field.field_type, name.strip_prefix(field.name.as_str())) {
+                (AllType::Struct(nested), Some(rest)) => rest
+                    .strip_prefix('.')
+                    .and_then(|rest| nested.field_by_name(rest)),
+                _ => None,
+            }
+        })
+    }
+
+    fn check_unique_ids(&self, seen: &mut HashSet<i32>) -> Result<(), Error> {
+        self.fields.iter().try_for_each(|field| {
+            insert_id(seen, field.id)?;
+            field.field_type.check_unique_ids(seen)
+        })
+    }
+}
+
+/// Adds `id` to the ids `seen` so far, or returns an error if it is already there.
+fn insert_id(seen: &mut HashSet<i32>, id: i32) -> Result<(), Error> {
+    if seen.insert(id) {
+        Ok(())
+    } else {
+        Err(Error::Invalid(format!("duplicate field id {id}")))
+    }
+}
+
+impl AllType {
+    fn field_by_id(&self, id: i32) -> Option<&StructField> {
+        match self {
+            AllType::Primitive(_) => None,
+            AllType::Struct(s) => s.field_by_id(id),
+            AllType::List(l) => l.element.field_by_id(id),
+            AllType::Map(m) => m.key.field_by_id(id).or_else(|| m.value.field_by_id(id)),
+        }
+    }
+
+    fn check_unique_ids(&self, seen: &mut HashSet<i32>) -> Result<(), Error> {
+        match self {
+            AllType::Primitive(_) => Ok(()),
+            AllType::Struct(s) => s.check_unique_ids(seen),
+            AllType::List(l) => {
+                insert_id(seen, l.element_id)?;
+                l.element.check_unique_ids(seen)
+            }
+            AllType::Map(m) => {
+                insert_id(seen, m.key_id)?;
+                insert_id(seen, m.value_id)?;
+                m.key.check_unique_ids(seen)?;
+                m.value.check_unique_ids(seen)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 /// Details of a struct in a field.
 pub struct StructField {
     /// Unique Id
@@ -187,7 +439,7 @@ pub struct StructField {
     pub doc: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Names and types of fields in a table.
 pub struct SchemaV2 {
@@ -204,7 +456,24 @@ pub struct SchemaV2 {
     pub struct_fields: Struct,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+impl SchemaV2 {
+    /// The field with `id`, which may be nested.
+    pub fn field_by_id(&self, id: i32) -> Option<&StructField> {
+        self.struct_fields.field_by_id(id)
+    }
+
+    /// Returns an error if two fields, list elements, map keys or map values share an id.
+    pub fn check_unique_ids(&self) -> Result<(), Error> {
+        self.struct_fields.check_unique_ids(&mut HashSet::new())
+    }
+
+    /// The field with the dotted `name`, which may be nested in structs.
+    pub fn field_by_name(&self, name: &str) -> Option<&StructField> {
+        self.struct_fields.field_by_name(name)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case", tag = "list")]
 /// A Schema type that contains List  elements.
 pub struct List {
@@ -218,7 +487,7 @@ pub struct List {
     pub element: Box<AllType>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case", tag = "type")]
 /// A Schema type that contains Map elements.
 /// A map is a collection of key-value pairs with a key type and a value type.
@@ -239,7 +508,7 @@ pub struct Map {
     pub value: Box<AllType>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 /// Tables may also define a property schema.name-mapping.default with a JSON name mapping containing a list of field mapping objects.
 /// These mappings provide fallback field ids to be used when a data file does not contain field id information.
 pub struct NameMappings {
@@ -247,7 +516,7 @@ pub struct NameMappings {
     pub default: Vec<NameMapping>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Individual mapping within NameMappings.
 pub struct NameMapping {
@@ -259,6 +528,46 @@ pub struct NameMapping {
     pub fields: Option<Vec<NameMapping>>,
 }
 
+/// Rust types that map to an Iceberg type. Structs implement this with
+/// `#[derive(IcebergSchema)]` from the `derive` feature.
+pub trait IcebergType {
+    /// The Iceberg type of values of this Rust type.
+    fn iceberg_type() -> AllType;
+}
+
+/// Rust structs that describe the rows of a table, usually implemented with
+/// `#[derive(IcebergSchema)]` from the `derive` feature.
+pub trait IcebergSchema: IcebergType {
+    /// The schema of a table with rows of this type.
+    fn iceberg_schema() -> SchemaV2;
+}
+
+macro_rules! primitive_iceberg_type {
+    ($($rust_type:ty => $primitive:ident),* $(,)?) => {
+        $(
+            impl IcebergType for $rust_type {
+                fn iceberg_type() -> AllType {
+                    AllType::Primitive(PrimitiveType::$primitive)
+                }
+            }
+        )*
+    };
+}
+
+primitive_iceberg_type!(
+    bool => Boolean,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    String => String,
+    Uuid => Uuid,
+    NaiveDate => Date,
+    NaiveTime => Time,
+    NaiveDateTime => Timestamp,
+    DateTime<Utc> => Timestampz,
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -270,7 +579,7 @@ mod tests {
             "fields": []
         }
         "#;
-        assert!(serde_json::from_str::<Struct>(&data).is_ok());
+        assert!(serde_json::from_str::<Struct>(data).is_ok());
         let data = r#"
         {
             "type" : "anyother"            
@@ -309,6 +618,35 @@ mod tests {
         assert!(serde_json::from_str::<StructField>(invalid_decimal_data).is_err());
     }
 
+    #[test]
+    fn test_decimal_limits() {
+        for invalid in [
+            "decimal(0,0)",
+            "decimal(39,0)",
+            "decimal(1000,2)",
+            "decimal(2,3)",
+        ] {
+            assert!(invalid.parse::<PrimitiveType>().is_err(), "{invalid}");
+        }
+        assert_eq!(
+            Ok(PrimitiveType::Decimal {
+                precision: 38,
+                scale: 38
+            }),
+            "decimal(38,38)".parse()
+        );
+        assert!(PrimitiveType::decimal(10, 2).is_ok());
+        assert!(PrimitiveType::decimal(0, 0).is_err());
+        assert!(PrimitiveType::decimal(4, 5).is_err());
+    }
+
+    #[test]
+    fn test_fixed_length() {
+        assert!("fixed[0]".parse::<PrimitiveType>().is_err());
+        assert!(PrimitiveType::fixed(0).is_err());
+        assert_eq!(Ok(PrimitiveType::Fixed(16)), PrimitiveType::fixed(16));
+    }
+
     #[test]
     fn test_boolean() {
         let data = r#"
@@ -362,7 +700,7 @@ mod tests {
             PrimitiveType::Float,
             PrimitiveType::Double,
             PrimitiveType::Decimal {
-                precision: 1,
+                precision: 3,
                 scale: 2,
             },
             PrimitiveType::Date,
@@ -373,6 +711,28 @@ mod tests {
             PrimitiveType::Uuid,
             PrimitiveType::Fixed(1),
             PrimitiveType::Binary,
+            PrimitiveType::TimestampNs,
+            PrimitiveType::TimestampzNs,
+            PrimitiveType::Unknown,
+            PrimitiveType::Variant,
+            PrimitiveType::Geometry {
+                crs: DEFAULT_CRS.to_string(),
+            },
+            PrimitiveType::Geometry {
+                crs: "srid:3857".to_string(),
+            },
+            PrimitiveType::Geography {
+                crs: DEFAULT_CRS.to_string(),
+                algorithm: EdgeAlgorithm::Spherical,
+            },
+            PrimitiveType::Geography {
+                crs: "srid:4326".to_string(),
+                algorithm: EdgeAlgorithm::Spherical,
+            },
+            PrimitiveType::Geography {
+                crs: DEFAULT_CRS.to_string(),
+                algorithm: EdgeAlgorithm::Karney,
+            },
         ];
 
         for primitive in type_mappings {
@@ -390,6 +750,254 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_v3_type_names() {
+        let names = vec![
+            ("\"timestamptz\"", PrimitiveType::Timestampz),
+            ("\"timestampz\"", PrimitiveType::Timestampz),
+            ("\"timestamp_ns\"", PrimitiveType::TimestampNs),
+            ("\"timestamptz_ns\"", PrimitiveType::TimestampzNs),
+            ("\"unknown\"", PrimitiveType::Unknown),
+            ("\"variant\"", PrimitiveType::Variant),
+            (
+                "\"geometry\"",
+                PrimitiveType::Geometry {
+                    crs: DEFAULT_CRS.to_string(),
+                },
+            ),
+            (
+                "\"geography\"",
+                PrimitiveType::Geography {
+                    crs: DEFAULT_CRS.to_string(),
+                    algorithm: EdgeAlgorithm::Spherical,
+                },
+            ),
+        ];
+        for (name, expected) in names {
+            assert_eq!(
+                expected,
+                serde_json::from_str::<PrimitiveType>(name).unwrap()
+            );
+        }
+        assert_eq!(
+            "\"timestamptz_ns\"",
+            serde_json::to_string(&PrimitiveType::TimestampzNs).unwrap()
+        );
+        assert_eq!(
+            "\"timestamptz\"",
+            serde_json::to_string(&PrimitiveType::Timestampz).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_geospatial_parameters() {
+        assert_eq!(
+            PrimitiveType::Geometry {
+                crs: "srid:4326".to_string()
+            },
+            serde_json::from_str::<PrimitiveType>("\"geometry(srid:4326)\"").unwrap()
+        );
+        assert_eq!(
+            PrimitiveType::Geography {
+                crs: "projjson:crs_prop".to_string(),
+                algorithm: EdgeAlgorithm::Vincenty
+            },
+            serde_json::from_str::<PrimitiveType>("\"geography(projjson:crs_prop, vincenty)\"")
+                .unwrap()
+        );
+        assert_eq!(
+            "\"geography(OGC:CRS84, karney)\"",
+            serde_json::to_string(&PrimitiveType::Geography {
+                crs: DEFAULT_CRS.to_string(),
+                algorithm: EdgeAlgorithm::Karney
+            })
+            .unwrap()
+        );
+        assert_eq!(
+            "\"geometry\"",
+            serde_json::to_string(&PrimitiveType::Geometry {
+                crs: DEFAULT_CRS.to_string(),
+            })
+            .unwrap()
+        );
+        assert!(serde_json::from_str::<PrimitiveType>("\"geometry()\"").is_err());
+        assert!(serde_json::from_str::<PrimitiveType>("\"geography(a, flat)\"").is_err());
+        assert!(serde_json::from_str::<PrimitiveType>("\"geometry(a, spherical)\"").is_err());
+    }
+
+    #[test]
+    fn test_type_promotion() {
+        use PrimitiveType::*;
+        assert!(Int.can_promote_to(&Long));
+        assert!(Float.can_promote_to(&Double));
+        assert!(Date.can_promote_to(&Timestamp));
+        assert!(Date.can_promote_to(&TimestampNs));
+        assert!(Unknown.can_promote_to(&Variant));
+        assert!(Decimal {
+            precision: 9,
+            scale: 2
+        }
+        .can_promote_to(&Decimal {
+            precision: 18,
+            scale: 2
+        }));
+        assert!(!Decimal {
+            precision: 9,
+            scale: 2
+        }
+        .can_promote_to(&Decimal {
+            precision: 18,
+            scale: 3
+        }));
+        assert!(!Long.can_promote_to(&Int));
+        assert!(!Date.can_promote_to(&Timestampz));
+        assert!(!Timestamp.can_promote_to(&TimestampNs));
+    }
+
+    #[test]
+    fn test_derive_schema() {
+        use iceberg_rs_derive::IcebergSchema;
+        use std::collections::HashMap;
+
+        #[derive(IcebergSchema)]
+        #[allow(dead_code)]
+        struct Location {
+            #[iceberg(id = 10)]
+            lat: f64,
+            #[iceberg(id = 11)]
+            long: f64,
+        }
+
+        #[derive(IcebergSchema)]
+        #[iceberg(schema_id = 3)]
+        #[allow(dead_code)]
+        struct Event {
+            #[iceberg(id = 1, identifier)]
+            id: i64,
+            #[iceberg(id = 2, doc = "Event time", type = "timestamptz")]
+            ts: i64,
+            #[iceberg(id = 3, name = "event_type")]
+            kind: Option<String>,
+            #[iceberg(id = 4, element_id = 5)]
+            tags: Vec<Option<String>>,
+            #[iceberg(id = 6, key_id = 7, value_id = 8)]
+            properties: Option<HashMap<String, i32>>,
+            #[iceberg(id = 9)]
+            location: Location,
+            #[iceberg(id = 12, type = "decimal(9,2)")]
+            price: i64,
+            #[iceberg(id = 13)]
+            payload: Vec<u8>,
+        }
+
+        let schema = Event::iceberg_schema();
+        assert_eq!(3, schema.schema_id);
+        assert_eq!(Some(vec![1]), schema.identifier_field_ids);
+        let fields = &schema.struct_fields.fields;
+        assert_eq!(8, fields.len());
+        assert_eq!(
+            StructField {
+                id: 2,
+                name: "ts".to_string(),
+                required: true,
+                field_type: AllType::Primitive(PrimitiveType::Timestampz),
+                doc: Some("Event time".to_string()),
+            },
+            fields[1]
+        );
+        assert_eq!("event_type", fields[2].name);
+        assert!(!fields[2].required);
+        assert_eq!(
+            AllType::List(List {
+                element_id: 5,
+                element_required: false,
+                element: Box::new(AllType::Primitive(PrimitiveType::String)),
+            }),
+            fields[3].field_type
+        );
+        assert_eq!(
+            AllType::Map(Map {
+                key_id: 7,
+                key: Box::new(AllType::Primitive(PrimitiveType::String)),
+                value_id: 8,
+                value_required: true,
+                value: Box::new(AllType::Primitive(PrimitiveType::Int)),
+            }),
+            fields[4].field_type
+        );
+        assert!(!fields[4].required);
+        assert_eq!(Location::iceberg_type(), fields[5].field_type);
+        assert_eq!(
+            AllType::Primitive(PrimitiveType::Decimal {
+                precision: 9,
+                scale: 2
+            }),
+            fields[6].field_type
+        );
+        assert_eq!(
+            AllType::Primitive(PrimitiveType::Binary),
+            fields[7].field_type
+        );
+
+        // Type overrides are checked by the macro and read like the schema JSON.
+        #[derive(IcebergSchema)]
+        #[allow(dead_code)]
+        struct Overrides {
+            #[iceberg(id = 1, type = "timestamp_ns")]
+            a: i64,
+            #[iceberg(id = 2, type = "fixed[16]")]
+            b: Vec<u8>,
+            #[iceberg(id = 3, type = "geometry")]
+            c: Vec<u8>,
+            #[iceberg(id = 4, type = "geometry(EPSG:4326)")]
+            d: Vec<u8>,
+            #[iceberg(id = 5, type = "geography(EPSG:4326, karney)")]
+            e: Vec<u8>,
+        }
+        let names = [
+            "timestamp_ns",
+            "fixed[16]",
+            "geometry",
+            "geometry(EPSG:4326)",
+            "geography(EPSG:4326, karney)",
+        ];
+        let fields = Overrides::iceberg_schema().struct_fields.fields;
+        for (name, field) in names.iter().zip(&fields) {
+            assert_eq!(AllType::Primitive(name.parse().unwrap()), field.field_type);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "#[derive(IcebergSchema)] on Outer: Invalid: duplicate field id 2")]
+    fn test_derive_nested_duplicate_id() {
+        use iceberg_rs_derive::IcebergSchema;
+
+        #[derive(IcebergSchema)]
+        #[allow(dead_code)]
+        struct Inner {
+            #[iceberg(id = 2)]
+            a: i32,
+        }
+
+        #[derive(IcebergSchema)]
+        #[allow(dead_code)]
+        struct Outer {
+            #[iceberg(id = 1)]
+            inner: Inner,
+            #[iceberg(id = 2)]
+            b: i32,
+        }
+
+        Outer::iceberg_schema();
+    }
+
+    #[test]
+    fn test_parse_primitive() {
+        assert_eq!(Ok(PrimitiveType::Long), "long".parse());
+        assert_eq!(Ok(PrimitiveType::Fixed(16)), "fixed[16]".parse());
+        assert!("varchar".parse::<PrimitiveType>().is_err());
+    }
+
     #[test]
     fn test_schema() {
         let data = r#"
@@ -421,6 +1029,52 @@ mod tests {
         assert_eq!(1, result_struct.name_mapping.unwrap().default.len());
     }
 
+    #[test]
+    fn test_field_by_id() {
+        let field = |id: i32, field_type: AllType| StructField {
+            id,
+            name: format!("field_{id}"),
+            required: false,
+            field_type,
+            doc: None,
+        };
+        let schema = SchemaV2 {
+            schema_id: 0,
+            identifier_field_ids: None,
+            name_mapping: None,
+            struct_fields: Struct {
+                fields: vec![
+                    field(1, AllType::Primitive(PrimitiveType::Long)),
+                    field(
+                        2,
+                        AllType::List(List {
+                            element_id: 3,
+                            element_required: true,
+                            element: Box::new(AllType::Struct(Struct {
+                                fields: vec![field(4, AllType::Primitive(PrimitiveType::Int))],
+                            })),
+                        }),
+                    ),
+                ],
+            },
+        };
+        assert_eq!("field_1", schema.field_by_id(1).unwrap().name);
+        assert_eq!("field_4", schema.field_by_id(4).unwrap().name);
+        assert!(schema.field_by_id(3).is_none());
+        assert!(schema.field_by_id(5).is_none());
+
+        assert!(schema.check_unique_ids().is_ok());
+        let mut duplicate = schema.clone();
+        duplicate
+            .struct_fields
+            .fields
+            .push(field(3, AllType::Primitive(PrimitiveType::Int)));
+        assert!(matches!(
+            duplicate.check_unique_ids(),
+            Err(Error::Invalid(_))
+        ));
+    }
+
     #[test]
     fn test_list_type() {
         let data = r#"
@@ -11,7 +11,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 /// The type of operations included in the snapshot, this allows
 /// certain snapshots to be skipped during operation.
@@ -30,8 +30,9 @@ pub enum Operation {
     Delete,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
-/// Summarises the changes in the snapshot.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+/// Summarises the changes in the snapshot. See [summary](crate::model::summary) for typed access
+/// to its metrics.
 pub struct Summary {
     /// The type of operation in the snapshot
     pub operation: Option<Operation>,
@@ -119,7 +120,7 @@ mod tests {
             } 
         "#;
 
-        let snapshot: SnapshotV2 = serde_json::from_str(&data).unwrap();
+        let snapshot: SnapshotV2 = serde_json::from_str(data).unwrap();
         assert_eq!(Some(Operation::Append), snapshot.summary.operation);
         assert!(snapshot.summary.other.is_empty());
     }
@@ -2,10 +2,24 @@
 Definition of [Sort orders](https://iceberg.apache.org/spec/#sorting) for a Table.
 
 A [SortOrder] is composed of a list of [SortField] where each field has a [Transform],
-[SortDirection] and [NullOrder].
+[SortDirection] and [NullOrder]. Order id `0` is reserved for the unsorted order, which has no
+fields.
+
+[SortOrder::bind] resolves the sources of an order against a schema, and [SortOrder::builder]
+creates orders from source column names. A [BoundSortOrder] compares and sorts rows, using the
+transform results of each field as sort keys ordered by [Value::total_cmp].
+[TableMetadataV2::add_sort_order] assigns the id of a new order of a table.
 
 */
-use crate::model::partition::Transform;
+use std::cmp::Ordering;
+
+use crate::error::Error;
+use crate::model::accessor::{Accessor, StructLike};
+use crate::model::partition::{PartitionField, PartitionSpec, Transform};
+use crate::model::schema::{AllType, PrimitiveType, SchemaV2};
+use crate::model::table::TableMetadataV2;
+use crate::model::transform::check_source;
+use crate::model::values::Value;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
@@ -44,7 +58,7 @@ pub struct SortField {
     pub null_order: NullOrder,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// A sort order is defined by an sort order id and a list of sort fields.
 /// The order of the sort fields within the list defines the order in
@@ -56,10 +70,371 @@ pub struct SortOrder {
     pub fields: Vec<SortField>,
 }
 
+impl SortField {
+    /// Returns true if data sorted by this field is also sorted by `other`.
+    pub fn satisfies(&self, other: &SortField) -> bool {
+        self.source_id == other.source_id
+            && self.direction == other.direction
+            && self.null_order == other.null_order
+            && self.transform.satisfies_order_of(&other.transform)
+    }
+
+    /// Compare two sort keys of the field, the transform results of the values of two rows,
+    /// by the field's direction and null order. The null order applies in both directions.
+    pub fn compare_keys(&self, a: Option<&Value>, b: Option<&Value>) -> Ordering {
+        match (a, b) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) if self.null_order == NullOrder::First => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => self.compare_keys(b, a).reverse(),
+            (Some(a), Some(b)) => match self.direction {
+                SortDirection::Ascending => a.total_cmp(b),
+                SortDirection::Descending => b.total_cmp(a),
+            },
+        }
+    }
+}
+
+impl SortOrder {
+    /// The unsorted order, with id `0` and no fields.
+    pub fn unsorted() -> SortOrder {
+        SortOrder {
+            order_id: 0,
+            fields: vec![],
+        }
+    }
+
+    /// Returns true if the order has no fields, which is the case for order id `0`.
+    pub fn is_unsorted(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns true if data sorted by this order is also sorted by `other`, so that no
+    /// further sort is needed to produce data in `other` order.
+    pub fn satisfies(&self, other: &SortOrder) -> bool {
+        let last = other.fields.len().saturating_sub(1);
+        other.fields.len() <= self.fields.len()
+            && self
+                .fields
+                .iter()
+                .zip(&other.fields)
+                .enumerate()
+                .all(|(i, (field, other))| {
+                    // Only the last field may be finer than the field it satisfies, as a finer
+                    // field breaks up the order of the fields that follow it.
+                    field.satisfies(other)
+                        && (i == last || other.transform.satisfies_order_of(&field.transform))
+                })
+    }
+
+    /// Returns true if data sorted by this order is clustered by the partitions of `spec`,
+    /// so that the rows of each partition are next to each other.
+    pub fn satisfies_partitioning(&self, spec: &PartitionSpec) -> bool {
+        let mut remaining: Vec<&PartitionField> = spec
+            .fields
+            .iter()
+            .filter(|f| f.transform != Transform::Void)
+            .collect();
+        for field in &self.fields {
+            if remaining.is_empty() {
+                break;
+            }
+            remaining.retain(|p| {
+                p.source_id != field.source_id || !field.transform.satisfies_order_of(&p.transform)
+            });
+            // A sort field that varies within a partition breaks up the partitions of the fields that follow it.
+            let constant_in_partition = spec.fields.iter().any(|p| {
+                p.source_id == field.source_id && p.transform.satisfies_order_of(&field.transform)
+            });
+            if !remaining.is_empty() && !constant_in_partition {
+                return false;
+            }
+        }
+        remaining.is_empty()
+    }
+    /// Returns an error unless every field's source is a primitive field of `schema`, outside
+    /// any list or map, that the field's transform can be applied to.
+    pub fn check_compatible(&self, schema: &SchemaV2) -> Result<(), Error> {
+        self.fields
+            .iter()
+            .try_for_each(|f| check_source(schema, f.source_id, &f.transform).map(|_| ()))
+    }
+
+    /// Bind the order to `schema`, resolving the source of each field. Returns an error if a
+    /// source is missing, not a primitive, within a list or map, or incompatible with its
+    /// transform, or if the order id is `0` but the order has fields or the other way round.
+    pub fn bind(&self, schema: &SchemaV2) -> Result<BoundSortOrder, Error> {
+        if self.order_id == 0 && !self.is_unsorted() {
+            return Err(Error::Invalid(
+                "sort order id 0 is reserved for the unsorted order".to_string(),
+            ));
+        }
+        if self.order_id != 0 && self.is_unsorted() {
+            return Err(Error::Invalid(format!(
+                "unsorted order has id {} instead of 0",
+                self.order_id
+            )));
+        }
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                let accessor = check_source(schema, field.source_id, &field.transform)?;
+                let source_type = match accessor.field_type() {
+                    AllType::Primitive(p) => p.clone(),
+                    _ => unreachable!("check_source only accepts primitive sources"),
+                };
+                let result_type = field.transform.result_type(&source_type)?;
+                Ok(BoundSortField {
+                    field: field.clone(),
+                    source_type,
+                    result_type,
+                    accessor,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(BoundSortOrder {
+            order: self.clone(),
+            schema_id: schema.schema_id,
+            fields,
+        })
+    }
+
+    /// Start building a sort order of `schema`.
+    pub fn builder(schema: &SchemaV2) -> SortOrderBuilder<'_> {
+        SortOrderBuilder {
+            schema,
+            order_id: None,
+            fields: vec![],
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A [SortField] with its source resolved against a schema.
+pub struct BoundSortField {
+    field: SortField,
+    source_type: PrimitiveType,
+    result_type: PrimitiveType,
+    accessor: Accessor,
+}
+
+impl BoundSortField {
+    /// The unbound sort field.
+    pub fn field(&self) -> &SortField {
+        &self.field
+    }
+
+    /// The type of the source column.
+    pub fn source_type(&self) -> &PrimitiveType {
+        &self.source_type
+    }
+
+    /// The type of the sort keys produced by the field's transform.
+    pub fn result_type(&self) -> &PrimitiveType {
+        &self.result_type
+    }
+
+    /// Reads the source column from rows of the schema.
+    pub fn accessor(&self) -> &Accessor {
+        &self.accessor
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A [SortOrder] bound to the schema of the data it sorts.
+pub struct BoundSortOrder {
+    order: SortOrder,
+    schema_id: i32,
+    fields: Vec<BoundSortField>,
+}
+
+impl BoundSortOrder {
+    /// The unbound order.
+    pub fn order(&self) -> &SortOrder {
+        &self.order
+    }
+
+    /// The id of the schema the order is bound to.
+    pub fn schema_id(&self) -> i32 {
+        self.schema_id
+    }
+
+    /// The bound fields, in the order of the sort.
+    pub fn fields(&self) -> &[BoundSortField] {
+        &self.fields
+    }
+
+    /// Returns true if the order has no fields.
+    pub fn is_unsorted(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The sort key of `row`, a row of the schema the order is bound to: the transform
+    /// result of the source value of each field.
+    pub fn sort_key<S: StructLike + ?Sized>(&self, row: &S) -> Result<Vec<Option<Value>>, Error> {
+        self.fields
+            .iter()
+            .map(|f| f.field.transform.apply(f.accessor.get(row)))
+            .collect()
+    }
+
+    /// Compare two sort keys produced by [sort_key](BoundSortOrder::sort_key).
+    pub fn compare_keys(&self, a: &[Option<Value>], b: &[Option<Value>]) -> Ordering {
+        self.fields
+            .iter()
+            .zip(a.iter().zip(b))
+            .map(|(f, (a, b))| f.field.compare_keys(a.as_ref(), b.as_ref()))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// Compare two rows of the schema the order is bound to.
+    pub fn compare<S: StructLike + ?Sized>(&self, a: &S, b: &S) -> Result<Ordering, Error> {
+        Ok(self.compare_keys(&self.sort_key(a)?, &self.sort_key(b)?))
+    }
+
+    /// Sort `rows` by the order. The sort is stable, so rows with equal keys keep their order.
+    pub fn sort_rows<S: StructLike>(&self, rows: Vec<S>) -> Result<Vec<S>, Error> {
+        let mut keyed = rows
+            .into_iter()
+            .map(|row| Ok((self.sort_key(&row)?, row)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        keyed.sort_by(|(a, _), (b, _)| self.compare_keys(a, b));
+        Ok(keyed.into_iter().map(|(_, row)| row).collect())
+    }
+}
+
+/// Builds a [SortOrder] from source column names. Errors are reported by [SortOrderBuilder::build].
+pub struct SortOrderBuilder<'a> {
+    schema: &'a SchemaV2,
+    order_id: Option<i32>,
+    fields: Vec<SortField>,
+    error: Option<Error>,
+}
+
+impl<'a> SortOrderBuilder<'a> {
+    /// Set the id of the order. By default it is `1`, or `0` if the order has no fields.
+    pub fn with_order_id(mut self, order_id: i32) -> Self {
+        self.order_id = Some(order_id);
+        self
+    }
+
+    /// Sort ascending by `transform` of `source`, with nulls first.
+    pub fn asc(self, source: &str, transform: Transform) -> Self {
+        self.add(
+            source,
+            transform,
+            SortDirection::Ascending,
+            NullOrder::First,
+        )
+    }
+
+    /// Sort descending by `transform` of `source`, with nulls last.
+    pub fn desc(self, source: &str, transform: Transform) -> Self {
+        self.add(
+            source,
+            transform,
+            SortDirection::Descending,
+            NullOrder::Last,
+        )
+    }
+
+    /// Sort by `transform` of `source` in `direction`, with nulls placed by `null_order`.
+    pub fn add(
+        mut self,
+        source: &str,
+        transform: Transform,
+        direction: SortDirection,
+        null_order: NullOrder,
+    ) -> Self {
+        if self.error.is_none() {
+            match self.schema.field_by_name(source) {
+                Some(field) => self.fields.push(SortField {
+                    source_id: field.id,
+                    transform,
+                    direction,
+                    null_order,
+                }),
+                None => {
+                    self.error = Some(Error::NotFound(format!(
+                        "source field {source} is not in the schema"
+                    )))
+                }
+            }
+        }
+        self
+    }
+
+    /// The order, or the first error from adding a field or binding the order to the schema.
+    pub fn build(self) -> Result<SortOrder, Error> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let default_id = if self.fields.is_empty() { 0 } else { 1 };
+        let order = SortOrder {
+            order_id: self.order_id.unwrap_or(default_id),
+            fields: self.fields,
+        };
+        order.bind(self.schema)?;
+        Ok(order)
+    }
+}
+
+impl TableMetadataV2 {
+    /// Add `order` to the sort orders of the table, returning its id. The id of `order` is
+    /// ignored: if the table already has an order with the same fields that order's id is
+    /// returned, otherwise the order is added with the next unused id. The unsorted order
+    /// always has id `0`.
+    pub fn add_sort_order(&mut self, order: SortOrder) -> Result<i32, Error> {
+        let schema = self
+            .schemas
+            .iter()
+            .find(|s| s.schema_id == self.current_schema_id)
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "current schema {} is not in the table metadata",
+                    self.current_schema_id
+                ))
+            })?;
+        let order_id = if order.is_unsorted() {
+            0
+        } else if let Some(existing) = self.sort_orders.iter().find(|o| o.fields == order.fields) {
+            existing.order_id
+        } else {
+            self.sort_orders
+                .iter()
+                .map(|o| o.order_id)
+                .max()
+                .unwrap_or(0)
+                + 1
+        };
+        let order = SortOrder {
+            order_id,
+            fields: order.fields,
+        };
+        order.bind(schema)?;
+        if !self.sort_orders.iter().any(|o| o.order_id == order_id) {
+            self.sort_orders.push(order);
+        }
+        Ok(order_id)
+    }
+
+    /// Add `order` as with [add_sort_order](TableMetadataV2::add_sort_order) and make it the
+    /// default sort order of the table, returning its id.
+    pub fn replace_sort_order(&mut self, order: SortOrder) -> Result<i32, Error> {
+        let order_id = self.add_sort_order(order)?;
+        self.default_sort_order_id = i64::from(order_id);
+        Ok(order_id)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::model::tests::metadata_with_snapshots;
     use proptest::prelude::*;
 
     #[test]
@@ -73,7 +448,7 @@ mod tests {
             } 
         "#;
 
-        let field: SortField = serde_json::from_str(&data).unwrap();
+        let field: SortField = serde_json::from_str(data).unwrap();
         assert_eq!(3, field.source_id);
         assert_eq!(Transform::Bucket(4), field.transform);
         assert_eq!(SortDirection::Descending, field.direction);
@@ -95,11 +470,358 @@ mod tests {
             } 
         "#;
 
-        let field: SortOrder = serde_json::from_str(&data).unwrap();
+        let field: SortOrder = serde_json::from_str(data).unwrap();
         assert_eq!(1, field.order_id);
         assert_eq!(1, field.fields.len());
     }
 
+    #[test]
+    fn test_check_compatible() {
+        let schema: SchemaV2 = serde_json::from_str(
+            r#"
+            {
+                "schema-id": 0,
+                "type": "struct",
+                "fields": [
+                    { "id": 3, "name": "score", "required": false, "field_type": "double" },
+                    {
+                        "id": 4,
+                        "name": "tags",
+                        "required": false,
+                        "field_type": {
+                            "type": "list",
+                            "element-id": 5,
+                            "element-required": true,
+                            "element": {
+                                "type": "struct",
+                                "fields": [
+                                    { "id": 9, "name": "tag", "required": true, "field_type": "string" }
+                                ]
+                            }
+                        }
+                    },
+                    {
+                        "id": 6,
+                        "name": "properties",
+                        "required": false,
+                        "field_type": {
+                            "type": "map",
+                            "key-id": 7,
+                            "key": "string",
+                            "value-id": 8,
+                            "value-required": true,
+                            "value": {
+                                "type": "struct",
+                                "fields": [
+                                    { "id": 10, "name": "v", "required": true, "field_type": "string" }
+                                ]
+                            }
+                        }
+                    }
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+        let order = |transform| SortOrder {
+            order_id: 1,
+            fields: vec![SortField {
+                source_id: 3,
+                transform,
+                direction: SortDirection::Ascending,
+                null_order: NullOrder::First,
+            }],
+        };
+        assert!(order(Transform::Identity).check_compatible(&schema).is_ok());
+        assert!(order(Transform::Bucket(4))
+            .check_compatible(&schema)
+            .is_err());
+        for source_id in [9, 10] {
+            let order = SortOrder {
+                order_id: 1,
+                fields: vec![field(source_id, Transform::Identity)],
+            };
+            assert!(matches!(
+                order.check_compatible(&schema),
+                Err(Error::Invalid(_))
+            ));
+        }
+    }
+
+    fn field(source_id: i32, transform: Transform) -> SortField {
+        SortField {
+            source_id,
+            transform,
+            direction: SortDirection::Ascending,
+            null_order: NullOrder::First,
+        }
+    }
+
+    fn order(fields: Vec<SortField>) -> SortOrder {
+        SortOrder {
+            order_id: 1,
+            fields,
+        }
+    }
+
+    #[test]
+    fn test_satisfies() {
+        let hourly = order(vec![
+            field(1, Transform::Hour),
+            field(2, Transform::Identity),
+        ]);
+        assert!(hourly.satisfies(&order(vec![field(1, Transform::Day)])));
+        assert!(hourly.satisfies(&order(vec![])));
+        assert!(!hourly.satisfies(&order(vec![
+            field(1, Transform::Day),
+            field(2, Transform::Identity)
+        ])));
+        assert!(!order(vec![field(1, Transform::Day)]).satisfies(&hourly));
+        let mut descending = field(1, Transform::Hour);
+        descending.direction = SortDirection::Descending;
+        assert!(!order(vec![descending]).satisfies(&order(vec![field(1, Transform::Hour)])));
+        assert!(order(vec![]).is_unsorted());
+    }
+
+    #[test]
+    fn test_satisfies_partitioning() {
+        let spec = |transforms: Vec<(i32, Transform)>| PartitionSpec {
+            spec_id: 0,
+            fields: transforms
+                .into_iter()
+                .enumerate()
+                .map(|(i, (source_id, transform))| PartitionField {
+                    source_id,
+                    field_id: 1000 + i as i32,
+                    name: format!("p{i}"),
+                    transform,
+                })
+                .collect(),
+        };
+        let daily = spec(vec![(1, Transform::Day), (2, Transform::Identity)]);
+        assert!(order(vec![
+            field(2, Transform::Identity),
+            field(1, Transform::Day)
+        ])
+        .satisfies_partitioning(&daily));
+        assert!(order(vec![
+            field(1, Transform::Day),
+            field(2, Transform::Identity),
+            field(1, Transform::Hour)
+        ])
+        .satisfies_partitioning(&daily));
+        assert!(order(vec![
+            field(2, Transform::Identity),
+            field(1, Transform::Hour)
+        ])
+        .satisfies_partitioning(&daily));
+        // Hours within a day break up the partitions by the second field.
+        assert!(!order(vec![
+            field(1, Transform::Hour),
+            field(2, Transform::Identity)
+        ])
+        .satisfies_partitioning(&daily));
+        assert!(!order(vec![field(1, Transform::Day)]).satisfies_partitioning(&daily));
+        assert!(order(vec![]).satisfies_partitioning(&spec(vec![(1, Transform::Void)])));
+        assert!(!order(vec![field(1, Transform::Identity)])
+            .satisfies_partitioning(&spec(vec![(1, Transform::Bucket(4))])));
+    }
+
+    fn metadata() -> TableMetadataV2 {
+        let mut metadata = metadata_with_snapshots(&[]);
+        metadata.schemas = vec![serde_json::from_str(
+            r#"
+            {
+                "schema-id": 0,
+                "type": "struct",
+                "fields": [
+                    { "id": 1, "name": "id", "required": true, "field_type": "long" },
+                    { "id": 2, "name": "ts", "required": true, "field_type": "timestamptz" },
+                    {
+                        "id": 3,
+                        "name": "tags",
+                        "required": false,
+                        "field_type": {
+                            "type": "list",
+                            "element-id": 4,
+                            "element-required": false,
+                            "element": {
+                                "type": "struct",
+                                "fields": [
+                                    { "id": 5, "name": "tag", "required": false, "field_type": "string" }
+                                ]
+                            }
+                        }
+                    }
+                ]
+            }
+            "#,
+        )
+        .unwrap()];
+        metadata.last_column_id = 5;
+        metadata
+    }
+
+    #[test]
+    fn test_bind() {
+        let metadata = metadata();
+        let schema = &metadata.schemas[0];
+        let bound = order(vec![
+            field(2, Transform::Day),
+            field(1, Transform::Bucket(4)),
+        ])
+        .bind(schema)
+        .unwrap();
+        assert_eq!(0, bound.schema_id());
+        assert_eq!(&PrimitiveType::Timestampz, bound.fields()[0].source_type());
+        assert_eq!(&PrimitiveType::Date, bound.fields()[0].result_type());
+        assert!(SortOrder::unsorted().bind(schema).unwrap().is_unsorted());
+
+        assert!(matches!(
+            order(vec![field(5, Transform::Identity)]).bind(schema),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            order(vec![field(9, Transform::Identity)]).bind(schema),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            order(vec![field(1, Transform::Day)]).bind(schema),
+            Err(Error::Invalid(_))
+        ));
+        let mut reserved = order(vec![field(1, Transform::Identity)]);
+        reserved.order_id = 0;
+        assert!(reserved.bind(schema).is_err());
+        assert!(order(vec![]).bind(schema).is_err());
+    }
+
+    #[test]
+    fn test_builder() {
+        let metadata = metadata();
+        let schema = &metadata.schemas[0];
+        let order = SortOrder::builder(schema)
+            .desc("ts", Transform::Day)
+            .asc("id", Transform::Identity)
+            .build()
+            .unwrap();
+        assert_eq!(1, order.order_id);
+        assert_eq!(
+            vec![
+                SortField {
+                    source_id: 2,
+                    transform: Transform::Day,
+                    direction: SortDirection::Descending,
+                    null_order: NullOrder::Last,
+                },
+                field(1, Transform::Identity)
+            ],
+            order.fields
+        );
+        assert_eq!(
+            SortOrder::unsorted(),
+            SortOrder::builder(schema).build().unwrap()
+        );
+        assert!(matches!(
+            SortOrder::builder(schema)
+                .asc("missing", Transform::Identity)
+                .build(),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            SortOrder::builder(schema)
+                .asc("id", Transform::Hour)
+                .build(),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_sort_order() {
+        let mut metadata = metadata();
+        let by_id = order(vec![field(1, Transform::Identity)]);
+        let by_day = order(vec![field(2, Transform::Day)]);
+        assert_eq!(1, metadata.replace_sort_order(by_id.clone()).unwrap());
+        assert_eq!(2, metadata.add_sort_order(by_day).unwrap());
+        assert_eq!(1, metadata.default_sort_order_id);
+        assert_eq!(1, metadata.add_sort_order(by_id).unwrap());
+        assert_eq!(
+            0,
+            metadata.replace_sort_order(SortOrder::unsorted()).unwrap()
+        );
+        assert_eq!(0, metadata.default_sort_order_id);
+        assert_eq!(
+            vec![0, 1, 2],
+            metadata
+                .sort_orders
+                .iter()
+                .map(|o| o.order_id)
+                .collect::<Vec<_>>()
+        );
+        assert!(metadata
+            .add_sort_order(order(vec![field(5, Transform::Identity)]))
+            .is_err());
+        assert_eq!(3, metadata.sort_orders.len());
+    }
+
+    #[test]
+    fn test_compare_keys() {
+        let mut desc = field(1, Transform::Identity);
+        desc.direction = SortDirection::Descending;
+        let one = Value::Int(1);
+        let two = Value::Int(2);
+        assert_eq!(Ordering::Greater, desc.compare_keys(Some(&one), Some(&two)));
+        // Nulls are first in both directions.
+        assert_eq!(Ordering::Less, desc.compare_keys(None, Some(&two)));
+        assert_eq!(
+            Ordering::Less,
+            field(1, Transform::Identity).compare_keys(None, Some(&one))
+        );
+        desc.null_order = NullOrder::Last;
+        assert_eq!(Ordering::Greater, desc.compare_keys(None, Some(&one)));
+        assert_eq!(Ordering::Less, desc.compare_keys(Some(&one), None));
+        assert_eq!(Ordering::Equal, desc.compare_keys(None, None));
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let metadata = metadata();
+        let schema = &metadata.schemas[0];
+        let order = SortOrder::builder(schema)
+            .asc("ts", Transform::Day)
+            .desc("id", Transform::Identity)
+            .build()
+            .unwrap()
+            .bind(schema)
+            .unwrap();
+        let day = 86_400_000_000;
+        let row =
+            |id: Option<i64>, ts: i64| vec![id.map(Value::Long), Some(Value::Timestampz(ts)), None];
+        let rows = vec![
+            row(Some(1), day + 5),
+            row(Some(2), 3),
+            row(None, day),
+            row(Some(3), day),
+            row(Some(1), 1),
+            row(Some(1), 0),
+        ];
+        let sorted = order.sort_rows(rows).unwrap();
+        assert_eq!(
+            vec![
+                row(Some(2), 3),
+                row(Some(1), 1),
+                row(Some(1), 0),
+                row(Some(3), day),
+                row(Some(1), day + 5),
+                row(None, day),
+            ],
+            sorted
+        );
+        assert_eq!(
+            Ordering::Equal,
+            order.compare(&row(Some(1), 1), &row(Some(1), 0)).unwrap()
+        );
+    }
+
     fn sort_direction_strategy() -> impl Strategy<Value = SortDirection> {
         prop_oneof![
             Just(SortDirection::Ascending),
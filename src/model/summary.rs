@@ -0,0 +1,509 @@
+/*!
+Typed access to the [metrics](https://iceberg.apache.org/spec/#optional-snapshot-summary-fields)
+of a snapshot [Summary].
+
+The summary of a snapshot is stored as a string map. [Metric] lists the keys the spec defines,
+and [Summary::metric] reads them as numbers. A [SummaryBuilder] accumulates the metrics of the
+files added and removed by a commit, and computes the `total-*` metrics from the summary of the
+parent snapshot.
+*/
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{Error, Result};
+use crate::model::partition::PartitionKey;
+use crate::model::snapshot::{Operation, Summary};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+/// The numeric metrics of a snapshot summary.
+pub enum Metric {
+    /// Number of data files added in the snapshot.
+    AddedDataFiles,
+    /// Number of data files deleted in the snapshot.
+    DeletedDataFiles,
+    /// Total number of live data files in the snapshot.
+    TotalDataFiles,
+    /// Number of delete files added in the snapshot.
+    AddedDeleteFiles,
+    /// Number of equality delete files added in the snapshot.
+    AddedEqualityDeleteFiles,
+    /// Number of position delete files added in the snapshot.
+    AddedPositionDeleteFiles,
+    /// Number of delete files removed in the snapshot.
+    RemovedDeleteFiles,
+    /// Number of equality delete files removed in the snapshot.
+    RemovedEqualityDeleteFiles,
+    /// Number of position delete files removed in the snapshot.
+    RemovedPositionDeleteFiles,
+    /// Total number of live delete files in the snapshot.
+    TotalDeleteFiles,
+    /// Number of records added in the snapshot.
+    AddedRecords,
+    /// Number of records deleted in the snapshot.
+    DeletedRecords,
+    /// Total number of records in the snapshot.
+    TotalRecords,
+    /// The size in bytes of files added in the snapshot.
+    AddedFilesSize,
+    /// The size in bytes of files removed in the snapshot.
+    RemovedFilesSize,
+    /// Total size in bytes of live files in the snapshot.
+    TotalFilesSize,
+    /// Number of position delete records added in the snapshot.
+    AddedPositionDeletes,
+    /// Number of position delete records removed in the snapshot.
+    RemovedPositionDeletes,
+    /// Total number of position delete records in the snapshot.
+    TotalPositionDeletes,
+    /// Number of equality delete records added in the snapshot.
+    AddedEqualityDeletes,
+    /// Number of equality delete records removed in the snapshot.
+    RemovedEqualityDeletes,
+    /// Total number of equality delete records in the snapshot.
+    TotalEqualityDeletes,
+    /// Number of duplicate files deleted (duplicates are files recorded more than once in the manifest).
+    DeletedDuplicateFiles,
+    /// Number of partitions with files added or removed in the snapshot.
+    ChangedPartitionCount,
+}
+
+impl Metric {
+    /// Every metric, in the order of the spec.
+    pub const ALL: [Metric; 24] = [
+        Metric::AddedDataFiles,
+        Metric::DeletedDataFiles,
+        Metric::TotalDataFiles,
+        Metric::AddedDeleteFiles,
+        Metric::AddedEqualityDeleteFiles,
+        Metric::AddedPositionDeleteFiles,
+        Metric::RemovedDeleteFiles,
+        Metric::RemovedEqualityDeleteFiles,
+        Metric::RemovedPositionDeleteFiles,
+        Metric::TotalDeleteFiles,
+        Metric::AddedRecords,
+        Metric::DeletedRecords,
+        Metric::TotalRecords,
+        Metric::AddedFilesSize,
+        Metric::RemovedFilesSize,
+        Metric::TotalFilesSize,
+        Metric::AddedPositionDeletes,
+        Metric::RemovedPositionDeletes,
+        Metric::TotalPositionDeletes,
+        Metric::AddedEqualityDeletes,
+        Metric::RemovedEqualityDeletes,
+        Metric::TotalEqualityDeletes,
+        Metric::DeletedDuplicateFiles,
+        Metric::ChangedPartitionCount,
+    ];
+
+    /// The key of the metric in the summary.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Metric::AddedDataFiles => "added-data-files",
+            Metric::DeletedDataFiles => "deleted-data-files",
+            Metric::TotalDataFiles => "total-data-files",
+            Metric::AddedDeleteFiles => "added-delete-files",
+            Metric::AddedEqualityDeleteFiles => "added-equality-delete-files",
+            Metric::AddedPositionDeleteFiles => "added-position-delete-files",
+            Metric::RemovedDeleteFiles => "removed-delete-files",
+            Metric::RemovedEqualityDeleteFiles => "removed-equality-delete-files",
+            Metric::RemovedPositionDeleteFiles => "removed-position-delete-files",
+            Metric::TotalDeleteFiles => "total-delete-files",
+            Metric::AddedRecords => "added-records",
+            Metric::DeletedRecords => "deleted-records",
+            Metric::TotalRecords => "total-records",
+            Metric::AddedFilesSize => "added-files-size",
+            Metric::RemovedFilesSize => "removed-files-size",
+            Metric::TotalFilesSize => "total-files-size",
+            Metric::AddedPositionDeletes => "added-position-deletes",
+            Metric::RemovedPositionDeletes => "removed-position-deletes",
+            Metric::TotalPositionDeletes => "total-position-deletes",
+            Metric::AddedEqualityDeletes => "added-equality-deletes",
+            Metric::RemovedEqualityDeletes => "removed-equality-deletes",
+            Metric::TotalEqualityDeletes => "total-equality-deletes",
+            Metric::DeletedDuplicateFiles => "deleted-duplicate-files",
+            Metric::ChangedPartitionCount => "changed-partition-count",
+        }
+    }
+
+    /// The metric with summary key `key`, if it is one of the spec's metrics.
+    pub fn from_key(key: &str) -> Option<Metric> {
+        Metric::ALL.into_iter().find(|m| m.key() == key)
+    }
+}
+
+/// The `total-*` metrics, with the metrics that add to and subtract from them.
+const TOTALS: [(Metric, Metric, Metric); 6] = [
+    (
+        Metric::TotalDataFiles,
+        Metric::AddedDataFiles,
+        Metric::DeletedDataFiles,
+    ),
+    (
+        Metric::TotalDeleteFiles,
+        Metric::AddedDeleteFiles,
+        Metric::RemovedDeleteFiles,
+    ),
+    (
+        Metric::TotalRecords,
+        Metric::AddedRecords,
+        Metric::DeletedRecords,
+    ),
+    (
+        Metric::TotalFilesSize,
+        Metric::AddedFilesSize,
+        Metric::RemovedFilesSize,
+    ),
+    (
+        Metric::TotalPositionDeletes,
+        Metric::AddedPositionDeletes,
+        Metric::RemovedPositionDeletes,
+    ),
+    (
+        Metric::TotalEqualityDeletes,
+        Metric::AddedEqualityDeletes,
+        Metric::RemovedEqualityDeletes,
+    ),
+];
+
+impl Summary {
+    /// The value of `metric`, or `None` if the summary does not have it. Returns an error if
+    /// the value is not a number.
+    pub fn metric(&self, metric: Metric) -> Result<Option<i64>> {
+        self.other
+            .get(metric.key())
+            .map(|v| {
+                v.parse::<i64>().map_err(|_| {
+                    Error::Invalid(format!("summary {} is not a number: {v}", metric.key()))
+                })
+            })
+            .transpose()
+    }
+
+    /// Set the value of `metric`.
+    pub fn set_metric(&mut self, metric: Metric, value: i64) {
+        self.other
+            .insert(metric.key().to_string(), value.to_string());
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The content of a data or delete file.
+pub enum FileContent {
+    /// A data file.
+    Data,
+    /// A delete file of position deletes.
+    PositionDeletes,
+    /// A delete file of equality deletes.
+    EqualityDeletes,
+}
+
+/// Builds the [Summary] of a snapshot from the files it adds and removes. Errors are reported
+/// by [SummaryBuilder::build].
+pub struct SummaryBuilder {
+    operation: Option<Operation>,
+    metrics: HashMap<Metric, i64>,
+    changed_partitions: HashSet<PartitionKey>,
+    properties: HashMap<String, String>,
+    error: Option<Error>,
+}
+
+impl Default for SummaryBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SummaryBuilder {
+    /// A builder without files.
+    pub fn new() -> Self {
+        SummaryBuilder {
+            operation: None,
+            metrics: HashMap::new(),
+            changed_partitions: HashSet::new(),
+            properties: HashMap::new(),
+            error: None,
+        }
+    }
+
+    /// Set the operation of the snapshot.
+    pub fn with_operation(mut self, operation: Operation) -> Self {
+        self.operation = Some(operation);
+        self
+    }
+
+    /// Set a summary property that is not a metric.
+    pub fn with_property(mut self, key: &str, value: &str) -> Self {
+        self.properties.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    fn increment(&mut self, metric: Metric, value: i64) {
+        if self.error.is_some() {
+            return;
+        }
+        if value < 0 {
+            self.error = Some(Error::Invalid(format!(
+                "{} cannot be increased by a negative count: {value}",
+                metric.key()
+            )));
+            return;
+        }
+        let current = self.metrics.entry(metric).or_insert(0);
+        match current.checked_add(value) {
+            Some(sum) => *current = sum,
+            None => {
+                self.error = Some(Error::Invalid(format!(
+                    "{} is out of range: {current} plus {value}",
+                    metric.key()
+                )))
+            }
+        }
+    }
+
+    /// Account for a file added in `partition`, with `record_count` rows (or deletes) and a
+    /// size of `file_size_in_bytes`.
+    pub fn add_file(
+        mut self,
+        content: FileContent,
+        record_count: i64,
+        file_size_in_bytes: i64,
+        partition: &PartitionKey,
+    ) -> Self {
+        self.changed_partitions.insert(partition.clone());
+        self.increment(Metric::AddedFilesSize, file_size_in_bytes);
+        match content {
+            FileContent::Data => {
+                self.increment(Metric::AddedDataFiles, 1);
+                self.increment(Metric::AddedRecords, record_count);
+            }
+            FileContent::PositionDeletes => {
+                self.increment(Metric::AddedDeleteFiles, 1);
+                self.increment(Metric::AddedPositionDeleteFiles, 1);
+                self.increment(Metric::AddedPositionDeletes, record_count);
+            }
+            FileContent::EqualityDeletes => {
+                self.increment(Metric::AddedDeleteFiles, 1);
+                self.increment(Metric::AddedEqualityDeleteFiles, 1);
+                self.increment(Metric::AddedEqualityDeletes, record_count);
+            }
+        }
+        self
+    }
+
+    /// Account for a file removed from `partition`, with `record_count` rows (or deletes) and
+    /// a size of `file_size_in_bytes`.
+    pub fn remove_file(
+        mut self,
+        content: FileContent,
+        record_count: i64,
+        file_size_in_bytes: i64,
+        partition: &PartitionKey,
+    ) -> Self {
+        self.changed_partitions.insert(partition.clone());
+        self.increment(Metric::RemovedFilesSize, file_size_in_bytes);
+        match content {
+            FileContent::Data => {
+                self.increment(Metric::DeletedDataFiles, 1);
+                self.increment(Metric::DeletedRecords, record_count);
+            }
+            FileContent::PositionDeletes => {
+                self.increment(Metric::RemovedDeleteFiles, 1);
+                self.increment(Metric::RemovedPositionDeleteFiles, 1);
+                self.increment(Metric::RemovedPositionDeletes, record_count);
+            }
+            FileContent::EqualityDeletes => {
+                self.increment(Metric::RemovedDeleteFiles, 1);
+                self.increment(Metric::RemovedEqualityDeleteFiles, 1);
+                self.increment(Metric::RemovedEqualityDeletes, record_count);
+            }
+        }
+        self
+    }
+
+    /// Account for `count` duplicate entries of removed files.
+    pub fn deleted_duplicate_files(mut self, count: i64) -> Self {
+        self.increment(Metric::DeletedDuplicateFiles, count);
+        self
+    }
+
+    /// The summary of the snapshot, with `total-*` metrics computed from the summary of the
+    /// `parent` snapshot. A snapshot without a parent starts from zero. Totals that the
+    /// parent summary does not have are left out, as they cannot be computed.
+    ///
+    /// Returns an error if a file had a negative count or size, or if a metric is out of range.
+    pub fn build(self, parent: Option<&Summary>) -> Result<Summary> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let mut summary = Summary {
+            operation: self.operation,
+            other: self.properties,
+        };
+        for (metric, value) in &self.metrics {
+            if *value != 0 {
+                summary.set_metric(*metric, *value);
+            }
+        }
+        summary.set_metric(
+            Metric::ChangedPartitionCount,
+            self.changed_partitions.len() as i64,
+        );
+        for (total, added, removed) in TOTALS {
+            let previous = match parent {
+                None => Some(0),
+                Some(parent) => parent.metric(total)?,
+            };
+            if let Some(previous) = previous {
+                let count = |metric: Metric| self.metrics.get(&metric).copied().unwrap_or(0);
+                let out_of_range = |change: Metric, by: &str| {
+                    Error::Invalid(format!(
+                        "{} is out of range: {previous} in the parent snapshot {by} {} {}",
+                        total.key(),
+                        count(change),
+                        change.key()
+                    ))
+                };
+                let value = previous
+                    .checked_add(count(added))
+                    .ok_or_else(|| out_of_range(added, "plus"))?
+                    .checked_sub(count(removed))
+                    .ok_or_else(|| out_of_range(removed, "minus"))?;
+                summary.set_metric(total, value);
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::values::Value;
+
+    fn partition(day: i32) -> PartitionKey {
+        PartitionKey::from(vec![Some(Value::Date(day))])
+    }
+
+    #[test]
+    fn test_metric() {
+        let summary: Summary = serde_json::from_str(
+            r#"{ "operation": "append", "added-data-files": "4", "total-records": "x" }"#,
+        )
+        .unwrap();
+        assert_eq!(Some(4), summary.metric(Metric::AddedDataFiles).unwrap());
+        assert_eq!(None, summary.metric(Metric::DeletedDataFiles).unwrap());
+        assert!(summary.metric(Metric::TotalRecords).is_err());
+        assert_eq!(
+            Some(Metric::ChangedPartitionCount),
+            Metric::from_key("changed-partition-count")
+        );
+        assert_eq!(None, Metric::from_key("operation"));
+    }
+
+    #[test]
+    fn test_build() {
+        let first = SummaryBuilder::new()
+            .with_operation(Operation::Append)
+            .add_file(FileContent::Data, 100, 1000, &partition(1))
+            .add_file(FileContent::Data, 50, 500, &partition(1))
+            .add_file(FileContent::Data, 10, 200, &partition(2))
+            .build(None)
+            .unwrap();
+        assert_eq!(Some(Operation::Append), first.operation);
+        assert_eq!(Some(3), first.metric(Metric::AddedDataFiles).unwrap());
+        assert_eq!(Some(160), first.metric(Metric::TotalRecords).unwrap());
+        assert_eq!(Some(1700), first.metric(Metric::TotalFilesSize).unwrap());
+        assert_eq!(
+            Some(2),
+            first.metric(Metric::ChangedPartitionCount).unwrap()
+        );
+        assert_eq!(Some(0), first.metric(Metric::TotalDeleteFiles).unwrap());
+        assert_eq!(None, first.metric(Metric::DeletedDataFiles).unwrap());
+
+        let second = SummaryBuilder::new()
+            .with_operation(Operation::Overwrite)
+            .with_property("spark.app.id", "app-1")
+            .remove_file(FileContent::Data, 10, 200, &partition(2))
+            .add_file(FileContent::PositionDeletes, 5, 30, &partition(1))
+            .build(Some(&first))
+            .unwrap();
+        assert_eq!(Some(2), second.metric(Metric::TotalDataFiles).unwrap());
+        assert_eq!(Some(150), second.metric(Metric::TotalRecords).unwrap());
+        assert_eq!(Some(1530), second.metric(Metric::TotalFilesSize).unwrap());
+        assert_eq!(Some(1), second.metric(Metric::TotalDeleteFiles).unwrap());
+        assert_eq!(
+            Some(5),
+            second.metric(Metric::TotalPositionDeletes).unwrap()
+        );
+        assert_eq!(
+            Some(1),
+            second.metric(Metric::AddedPositionDeleteFiles).unwrap()
+        );
+        assert_eq!(
+            Some(2),
+            second.metric(Metric::ChangedPartitionCount).unwrap()
+        );
+        assert_eq!(
+            Some("app-1"),
+            second.other.get("spark.app.id").map(String::as_str)
+        );
+
+        // Totals missing from the parent summary cannot be computed.
+        let mut parent = first;
+        parent.other.remove(Metric::TotalRecords.key());
+        let third = SummaryBuilder::new()
+            .add_file(FileContent::Data, 1, 1, &partition(3))
+            .build(Some(&parent))
+            .unwrap();
+        assert_eq!(None, third.metric(Metric::TotalRecords).unwrap());
+        assert_eq!(Some(4), third.metric(Metric::TotalDataFiles).unwrap());
+
+        // Totals of the parent summary that overflow are invalid.
+        parent.set_metric(Metric::TotalRecords, i64::MAX);
+        assert!(matches!(
+            SummaryBuilder::new()
+                .add_file(FileContent::Data, 1, 1, &partition(3))
+                .build(Some(&parent)),
+            Err(Error::Invalid(_))
+        ));
+        parent.set_metric(Metric::TotalRecords, i64::MIN);
+        assert!(matches!(
+            SummaryBuilder::new()
+                .remove_file(FileContent::Data, 1, 1, &partition(3))
+                .build(Some(&parent)),
+            Err(Error::Invalid(msg)) if msg.contains("minus 1 deleted-records")
+        ));
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let error = |builder: SummaryBuilder| match builder.build(None) {
+            Err(Error::Invalid(msg)) => msg,
+            other => panic!("expected an error, found {other:?}"),
+        };
+        // Counts that overflow while adding files, rather than in the totals.
+        let msg = error(
+            SummaryBuilder::new()
+                .add_file(FileContent::Data, i64::MAX, 1, &partition(1))
+                .add_file(FileContent::Data, 1, 1, &partition(1)),
+        );
+        assert!(msg.starts_with("added-records is out of range"), "{msg}");
+        let msg = error(
+            SummaryBuilder::new()
+                .remove_file(FileContent::Data, 1, i64::MAX, &partition(1))
+                .remove_file(FileContent::Data, 1, i64::MAX, &partition(1)),
+        );
+        assert!(
+            msg.starts_with("removed-files-size is out of range"),
+            "{msg}"
+        );
+
+        let msg = error(SummaryBuilder::new().add_file(FileContent::Data, -1, 1, &partition(1)));
+        assert!(msg.starts_with("added-records cannot be"), "{msg}");
+        let msg = error(SummaryBuilder::new().deleted_duplicate_files(-2));
+        assert!(
+            msg.starts_with("deleted-duplicate-files cannot be"),
+            "{msg}"
+        );
+    }
+}
@@ -79,6 +79,15 @@ pub struct TableMetadataV2 {
     pub refs: Option<HashMap<String, Reference>>,
 }
 
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+/// Version of the table format. The version decides what changes to the metadata are allowed.
+pub enum FormatVersion {
+    /// Version 1, for analytic data tables.
+    V1,
+    /// Version 2, which adds row level deletes.
+    V2,
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 /// Encodes changes to the previous metadata files for the table
@@ -158,7 +167,7 @@ mod tests {
                 "default-sort-order-id": 0
             }
         "#;
-        let metadata = serde_json::from_str::<TableMetadataV2>(&data)?;
+        let metadata = serde_json::from_str::<TableMetadataV2>(data)?;
         //test serialise deserialise works.
         let metadata_two: TableMetadataV2 =
             serde_json::from_str(&serde_json::to_string(&metadata)?)?;
@@ -175,7 +184,7 @@ mod tests {
                 "table-uuid": "xxxx"
             }
         "#;
-        assert!(serde_json::from_str::<TableMetadataV2>(&data).is_err());
+        assert!(serde_json::from_str::<TableMetadataV2>(data).is_err());
         Ok(())
     }
     #[test]
@@ -185,7 +194,7 @@ mod tests {
                 "format-version" : 1
             }
         "#;
-        assert!(serde_json::from_str::<TableMetadataV2>(&data).is_err());
+        assert!(serde_json::from_str::<TableMetadataV2>(data).is_err());
         Ok(())
     }
 }
@@ -0,0 +1,804 @@
+/*!
+Evaluation of partition [transforms](https://iceberg.apache.org/spec/#partition-transforms).
+
+[Transform::apply] computes the transform result of a single [Value]. Results match the Java
+reference implementation, so data files written with these values are partitioned the same way
+as files written by other engines.
+
+[Transform::to_human_string] formats transform results the way the Java implementation does in
+partition paths, and [Transform::parse_human_string] reads them back.
+
+[Transform::result_type] and [Transform::can_transform] check which source types a transform
+accepts and the type of the values it produces.
+
+Bucket transforms hash the value with [bucket_hash], a 32-bit Murmur3 x86 hash of the value
+serialised as described in the [appendix](https://iceberg.apache.org/spec/#appendix-b-32-bit-hash-requirements)
+of the spec.
+*/
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+use crate::error::{Error, Result};
+use crate::model::accessor::Accessor;
+use crate::model::partition::Transform;
+use crate::model::schema::{AllType, PrimitiveType, SchemaV2};
+use crate::model::values::{
+    date_to_days, days_to_date, decimal_from_string, decimal_to_bytes, decimal_to_string,
+    micros_to_datetime, micros_to_time, nanos_to_datetime, Value,
+};
+
+pub(crate) const MICROS_PER_HOUR: i64 = 3_600_000_000;
+pub(crate) const MICROS_PER_DAY: i64 = 86_400_000_000;
+pub(crate) const NANOS_PER_MICRO: i64 = 1_000;
+
+impl Transform {
+    /// Returns true if the transform can be applied to values of the `source` type.
+    pub fn can_transform(&self, source: &PrimitiveType) -> bool {
+        use PrimitiveType::*;
+        match self {
+            Transform::Void => true,
+            Transform::Identity => !matches!(source, Variant | Geometry { .. } | Geography { .. }),
+            Transform::Bucket(_) => matches!(
+                source,
+                Int | Long
+                    | Decimal { .. }
+                    | Date
+                    | Time
+                    | Timestamp
+                    | Timestampz
+                    | TimestampNs
+                    | TimestampzNs
+                    | String
+                    | Uuid
+                    | Fixed(_)
+                    | Binary
+            ),
+            Transform::Truncate(_) => {
+                matches!(source, Int | Long | Decimal { .. } | String | Binary)
+            }
+            Transform::Year | Transform::Month | Transform::Day => matches!(
+                source,
+                Date | Timestamp | Timestampz | TimestampNs | TimestampzNs
+            ),
+            Transform::Hour => {
+                matches!(source, Timestamp | Timestampz | TimestampNs | TimestampzNs)
+            }
+        }
+    }
+
+    /// Returns true if the transform never reverses the order of values, so that data sorted by
+    /// the source is also sorted by the transform result. The converse does not hold: values
+    /// with the same result may be in any order of the source.
+    pub fn preserves_order(&self) -> bool {
+        matches!(
+            self,
+            Transform::Identity
+                | Transform::Truncate(_)
+                | Transform::Year
+                | Transform::Month
+                | Transform::Day
+                | Transform::Hour
+        )
+    }
+
+    /// Returns true if data sorted by this transform of a column is also sorted by `other`
+    /// transform of the same column. For example `day` satisfies `month` and `identity`
+    /// satisfies every transform that preserves order.
+    ///
+    /// Whether truncating to one width satisfies another depends on the source type, which is
+    /// not known here: `truncate[4]` of a string satisfies `truncate[2]`, while for numbers
+    /// `truncate[W1]` satisfies `truncate[W2]` when `W2` is a multiple of `W1`. Truncate
+    /// transforms are therefore only taken to satisfy the same width.
+    pub fn satisfies_order_of(&self, other: &Transform) -> bool {
+        if self == other {
+            return true;
+        }
+        let granularity = |t: &Transform| match t {
+            Transform::Hour => Some(0),
+            Transform::Day => Some(1),
+            Transform::Month => Some(2),
+            Transform::Year => Some(3),
+            _ => None,
+        };
+        match (self, granularity(self), granularity(other)) {
+            (Transform::Identity, _, _) => other.preserves_order(),
+            (_, Some(this), Some(other)) => this <= other,
+            _ => false,
+        }
+    }
+
+    /// The type of the values produced by applying the transform to values of the `source` type.
+    pub fn result_type(&self, source: &PrimitiveType) -> Result<PrimitiveType> {
+        if !self.can_transform(source) {
+            return Err(Error::Invalid(format!(
+                "cannot apply {self:?} to {source:?}"
+            )));
+        }
+        Ok(match self {
+            Transform::Void | Transform::Identity | Transform::Truncate(_) => source.clone(),
+            Transform::Bucket(_) | Transform::Year | Transform::Month | Transform::Hour => {
+                PrimitiveType::Int
+            }
+            Transform::Day => PrimitiveType::Date,
+        })
+    }
+
+    /// Apply the transform to a value. Nulls transform to null, as does every value
+    /// for the [Void](Transform::Void) transform.
+    pub fn apply(&self, value: Option<&Value>) -> Result<Option<Value>> {
+        let value = match (self, value) {
+            (Transform::Void, _) | (_, None) => return Ok(None),
+            (_, Some(value)) => value,
+        };
+        let result = match self {
+            Transform::Void => unreachable!(),
+            Transform::Identity => value.clone(),
+            Transform::Bucket(n) => {
+                if *n == 0 {
+                    return Err(Error::Invalid("bucket count must be positive".to_string()));
+                }
+                Value::Int(((bucket_hash(value)? & i32::MAX) as u32 % n) as i32)
+            }
+            Transform::Truncate(width) => truncate(value, *width)?,
+            Transform::Year => Value::Int(year(value, self)?),
+            Transform::Month => Value::Int(month(value, self)?),
+            Transform::Day => Value::Date(day(value, self)?),
+            Transform::Hour => Value::Int(hour(value, self)?),
+        };
+        Ok(Some(result))
+    }
+}
+
+impl Transform {
+    /// The human readable form of a result of the transform applied to values of the `source` type,
+    /// as used in partition paths. Null is `null`.
+    pub fn to_human_string(&self, source: &PrimitiveType, value: Option<&Value>) -> Result<String> {
+        let value = match value {
+            None => return Ok("null".to_string()),
+            Some(value) => value,
+        };
+        let invalid = || Error::Invalid(format!("{value:?} is not a result of {self:?}"));
+        match (self, value) {
+            (Transform::Void, _) => Ok("null".to_string()),
+            (Transform::Bucket(_), Value::Int(v)) => Ok(v.to_string()),
+            (Transform::Year, Value::Int(v)) => {
+                Ok(format!("{:04}", v.checked_add(1970).ok_or_else(invalid)?))
+            }
+            (Transform::Month, Value::Int(v)) => Ok(format!(
+                "{:04}-{:02}",
+                1970 + v.div_euclid(12),
+                v.rem_euclid(12) + 1
+            )),
+            (Transform::Day, Value::Date(v)) => {
+                Ok(days_to_date(*v)?.format("%Y-%m-%d").to_string())
+            }
+            (Transform::Hour, Value::Int(v)) => {
+                Ok(micros_to_datetime(i64::from(*v) * MICROS_PER_HOUR)?
+                    .format("%Y-%m-%d-%H")
+                    .to_string())
+            }
+            (Transform::Identity | Transform::Truncate(_), value) => human_value(value, source),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Parse the human readable form of a result of the transform applied to values of the `source` type.
+    /// The inverse of [Transform::to_human_string].
+    pub fn parse_human_string(&self, source: &PrimitiveType, s: &str) -> Result<Option<Value>> {
+        if s == "null" || *self == Transform::Void {
+            return Ok(None);
+        }
+        let invalid = || Error::Invalid(format!("cannot parse {s} as a result of {self:?}"));
+        let value = match self {
+            Transform::Void => unreachable!(),
+            Transform::Bucket(_) => Value::Int(s.parse().map_err(|_| invalid())?),
+            Transform::Year => Value::Int(
+                s.parse::<i32>()
+                    .ok()
+                    .and_then(|year| year.checked_sub(1970))
+                    .ok_or_else(invalid)?,
+            ),
+            Transform::Month => {
+                let date = NaiveDate::parse_from_str(&format!("{s}-01"), "%Y-%m-%d")
+                    .map_err(|_| invalid())?;
+                Value::Int((date.year() - 1970) * 12 + date.month0() as i32)
+            }
+            Transform::Day => Value::Date(date_to_days(
+                &NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| invalid())?,
+            )),
+            Transform::Hour => {
+                let (date, hour) = s.rsplit_once('-').ok_or_else(invalid)?;
+                let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| invalid())?;
+                let hour: i32 = hour.parse().map_err(|_| invalid())?;
+                if !(0..24).contains(&hour) {
+                    return Err(invalid());
+                }
+                Value::Int(
+                    date_to_days(&date)
+                        .checked_mul(24)
+                        .and_then(|hours| hours.checked_add(hour))
+                        .ok_or_else(invalid)?,
+                )
+            }
+            Transform::Identity | Transform::Truncate(_) => parse_human_value(s, source)?,
+        };
+        Ok(Some(value))
+    }
+}
+
+/// Formats times like Java's `LocalTime`, which omits zero seconds and prints fractions
+/// of a second in groups of three digits.
+fn human_time(time: &NaiveTime) -> String {
+    let mut s = format!("{:02}:{:02}", time.hour(), time.minute());
+    let nanos = time.nanosecond();
+    if time.second() != 0 || nanos != 0 {
+        s.push_str(&format!(":{:02}", time.second()));
+    }
+    if nanos != 0 {
+        if nanos.is_multiple_of(1_000_000) {
+            s.push_str(&format!(".{:03}", nanos / 1_000_000));
+        } else if nanos.is_multiple_of(1_000) {
+            s.push_str(&format!(".{:06}", nanos / 1_000));
+        } else {
+            s.push_str(&format!(".{nanos:09}"));
+        }
+    }
+    s
+}
+
+fn human_timestamp(ts: &NaiveDateTime) -> String {
+    format!(
+        "{}T{}",
+        ts.date().format("%Y-%m-%d"),
+        human_time(&ts.time())
+    )
+}
+
+fn human_value(value: &Value, source: &PrimitiveType) -> Result<String> {
+    Ok(match (value, source) {
+        (Value::Boolean(v), _) => v.to_string(),
+        (Value::Int(v), _) => v.to_string(),
+        (Value::Long(v), _) => v.to_string(),
+        (Value::Float(v), _) => format!("{v:?}"),
+        (Value::Double(v), _) => format!("{v:?}"),
+        (Value::Decimal(v), PrimitiveType::Decimal { scale, .. }) => decimal_to_string(*v, *scale),
+        (Value::Date(v), _) => days_to_date(*v)?.format("%Y-%m-%d").to_string(),
+        (Value::Time(v), _) => human_time(&micros_to_time(*v)?),
+        (Value::Timestamp(v), _) => human_timestamp(&micros_to_datetime(*v)?),
+        (Value::Timestampz(v), _) => format!("{}Z", human_timestamp(&micros_to_datetime(*v)?)),
+        (Value::TimestampNs(v), _) => human_timestamp(&nanos_to_datetime(*v)),
+        (Value::TimestampzNs(v), _) => format!("{}Z", human_timestamp(&nanos_to_datetime(*v))),
+        (Value::String(v), _) => v.clone(),
+        (Value::Uuid(v), _) => v.to_string(),
+        (Value::Fixed(v) | Value::Binary(v), _) => BASE64.encode(v),
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "human readable form of {value:?} as {source:?}"
+            )))
+        }
+    })
+}
+
+fn parse_human_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .ok()
+}
+
+fn parse_human_timestamp(s: &str) -> Option<NaiveDateTime> {
+    let (date, time) = s.split_once('T')?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(date.and_time(parse_human_time(time)?))
+}
+
+fn parse_human_value(s: &str, source: &PrimitiveType) -> Result<Value> {
+    let invalid = || Error::Invalid(format!("cannot parse {s} as {source:?}"));
+    let micros = |ts: NaiveDateTime| ts.and_utc().timestamp_micros();
+    let nanos = |ts: NaiveDateTime| ts.and_utc().timestamp_nanos_opt().ok_or_else(invalid);
+    let utc = |s: &str| s.strip_suffix('Z').and_then(parse_human_timestamp);
+    Ok(match source {
+        PrimitiveType::Boolean => Value::Boolean(s.parse().map_err(|_| invalid())?),
+        PrimitiveType::Int => Value::Int(s.parse().map_err(|_| invalid())?),
+        PrimitiveType::Long => Value::Long(s.parse().map_err(|_| invalid())?),
+        PrimitiveType::Float => Value::Float(s.parse().map_err(|_| invalid())?),
+        PrimitiveType::Double => Value::Double(s.parse().map_err(|_| invalid())?),
+        PrimitiveType::Decimal { scale, .. } => Value::Decimal(decimal_from_string(s, *scale)?),
+        PrimitiveType::Date => Value::Date(date_to_days(
+            &NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| invalid())?,
+        )),
+        PrimitiveType::Time => {
+            let time = parse_human_time(s).ok_or_else(invalid)?;
+            Value::Time(
+                i64::from(time.num_seconds_from_midnight()) * 1_000_000
+                    + i64::from(time.nanosecond()) / 1_000,
+            )
+        }
+        PrimitiveType::Timestamp => {
+            Value::Timestamp(micros(parse_human_timestamp(s).ok_or_else(invalid)?))
+        }
+        PrimitiveType::Timestampz => Value::Timestampz(micros(utc(s).ok_or_else(invalid)?)),
+        PrimitiveType::TimestampNs => {
+            Value::TimestampNs(nanos(parse_human_timestamp(s).ok_or_else(invalid)?)?)
+        }
+        PrimitiveType::TimestampzNs => Value::TimestampzNs(nanos(utc(s).ok_or_else(invalid)?)?),
+        PrimitiveType::String => Value::String(s.to_string()),
+        PrimitiveType::Uuid => Value::Uuid(s.parse().map_err(|_| invalid())?),
+        PrimitiveType::Fixed(_) => Value::Fixed(BASE64.decode(s).map_err(|_| invalid())?),
+        PrimitiveType::Binary => Value::Binary(BASE64.decode(s).map_err(|_| invalid())?),
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "human readable form of {source:?}"
+            )))
+        }
+    })
+}
+
+/// The accessor for `source_id`, unless it is not a primitive field of `schema`, is within a list
+/// or map, or cannot be transformed by `transform`.
+pub(crate) fn check_source(
+    schema: &SchemaV2,
+    source_id: i32,
+    transform: &Transform,
+) -> Result<Accessor> {
+    let field = schema
+        .field_by_id(source_id)
+        .ok_or_else(|| Error::NotFound(format!("source field {source_id} is not in the schema")))?;
+    let source = match &field.field_type {
+        AllType::Primitive(source) => source,
+        _ => {
+            return Err(Error::Invalid(format!(
+                "source field {source_id} ({}) is not a primitive type",
+                field.name
+            )))
+        }
+    };
+    transform.result_type(source)?;
+    schema.accessor_for_field(source_id).ok_or_else(|| {
+        Error::Invalid(format!(
+            "source field {source_id} ({}) is within a list or map",
+            field.name
+        ))
+    })
+}
+
+fn unsupported(transform: &Transform, value: &Value) -> Error {
+    Error::Unsupported(format!("cannot apply {transform:?} to {value:?}"))
+}
+
+/// The 32-bit hash of a value, used by the bucket transform.
+pub fn bucket_hash(value: &Value) -> Result<i32> {
+    let hash_long = |v: i64| murmur3_32(&v.to_le_bytes());
+    Ok(match value {
+        Value::Int(v) | Value::Date(v) => hash_long(i64::from(*v)),
+        Value::Long(v) | Value::Time(v) | Value::Timestamp(v) | Value::Timestampz(v) => {
+            hash_long(*v)
+        }
+        // Nanosecond timestamps hash as microseconds, so they hash the same as the
+        // microsecond timestamp of a promoted column.
+        Value::TimestampNs(v) | Value::TimestampzNs(v) => hash_long(v.div_euclid(NANOS_PER_MICRO)),
+        Value::Decimal(v) => murmur3_32(&decimal_to_bytes(*v)),
+        Value::String(v) => murmur3_32(v.as_bytes()),
+        Value::Uuid(v) => murmur3_32(v.as_bytes()),
+        Value::Fixed(v) | Value::Binary(v) => murmur3_32(v),
+        _ => return Err(unsupported(&Transform::Bucket(1), value)),
+    })
+}
+
+fn truncate(value: &Value, width: u32) -> Result<Value> {
+    if width == 0 || width > i32::MAX as u32 {
+        return Err(Error::Invalid(format!(
+            "truncate width must be a positive int, not {width}"
+        )));
+    }
+    Ok(match value {
+        Value::Int(v) => {
+            let w = width as i32;
+            Value::Int(v.wrapping_sub(v.rem_euclid(w)))
+        }
+        Value::Long(v) => {
+            let w = i64::from(width);
+            Value::Long(v.wrapping_sub(v.rem_euclid(w)))
+        }
+        Value::Decimal(v) => {
+            let w = i128::from(width);
+            Value::Decimal(v.wrapping_sub(v.rem_euclid(w)))
+        }
+        Value::String(s) => Value::String(s.chars().take(width as usize).collect()),
+        Value::Binary(b) => Value::Binary(b.iter().take(width as usize).copied().collect()),
+        _ => return Err(unsupported(&Transform::Truncate(width), value)),
+    })
+}
+
+/// Days from 1970-01-01 of a date or timestamp.
+fn day(value: &Value, transform: &Transform) -> Result<i32> {
+    let days = match value {
+        Value::Date(v) => i64::from(*v),
+        Value::Timestamp(v) | Value::Timestampz(v) => v.div_euclid(MICROS_PER_DAY),
+        Value::TimestampNs(v) | Value::TimestampzNs(v) => {
+            v.div_euclid(MICROS_PER_DAY * NANOS_PER_MICRO)
+        }
+        _ => return Err(unsupported(transform, value)),
+    };
+    i32::try_from(days).map_err(|_| Error::Invalid(format!("{value:?} is out of range")))
+}
+
+fn year(value: &Value, transform: &Transform) -> Result<i32> {
+    years_from_days(day(value, transform)?)
+}
+
+fn month(value: &Value, transform: &Transform) -> Result<i32> {
+    months_from_days(day(value, transform)?)
+}
+
+/// Years from 1970 of the date `days` from 1970-01-01.
+pub(crate) fn years_from_days(days: i32) -> Result<i32> {
+    Ok(days_to_date(days)?.year() - 1970)
+}
+
+/// Months from 1970-01 of the date `days` from 1970-01-01.
+pub(crate) fn months_from_days(days: i32) -> Result<i32> {
+    let date = days_to_date(days)?;
+    Ok((date.year() - 1970) * 12 + date.month0() as i32)
+}
+
+fn hour(value: &Value, transform: &Transform) -> Result<i32> {
+    let hours = match value {
+        Value::Timestamp(v) | Value::Timestampz(v) => v.div_euclid(MICROS_PER_HOUR),
+        Value::TimestampNs(v) | Value::TimestampzNs(v) => {
+            v.div_euclid(MICROS_PER_HOUR * NANOS_PER_MICRO)
+        }
+        _ => return Err(unsupported(transform, value)),
+    };
+    i32::try_from(hours).map_err(|_| Error::Invalid(format!("{value:?} is out of range")))
+}
+
+/// Murmur3 32-bit x86 hash with a seed of 0.
+pub(crate) fn murmur3_32(data: &[u8]) -> i32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    let mix_k1 = |k1: u32| k1.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+
+    let mut h1: u32 = 0;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let k1 = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        h1 ^= mix_k1(k1);
+        h1 = h1.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k1 = 0u32;
+        for (i, b) in tail.iter().enumerate() {
+            k1 ^= u32::from(*b) << (8 * i);
+        }
+        h1 ^= mix_k1(k1);
+    }
+
+    h1 ^= data.len() as u32;
+    h1 ^= h1 >> 16;
+    h1 = h1.wrapping_mul(0x85eb_ca6b);
+    h1 ^= h1 >> 13;
+    h1 = h1.wrapping_mul(0xc2b2_ae35);
+    h1 ^= h1 >> 16;
+    h1 as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    /// Hash values from the spec's bucket transform examples.
+    #[test]
+    fn test_bucket_hash_spec_vectors() {
+        let vectors = vec![
+            (Value::Int(34), 2017239379),
+            (Value::Long(34), 2017239379),
+            (Value::Decimal(1420), -500754589),
+            (Value::Date(17486), -653330422),
+            (Value::Time(81_068_000_000), -662762989),
+            (Value::Timestamp(1_510_871_468_000_000), -2047944441),
+            (Value::Timestampz(1_510_871_468_000_000), -2047944441),
+            (Value::TimestampNs(1_510_871_468_000_001_001), -1207196810),
+            (Value::TimestampzNs(1_510_871_468_000_001_001), -1207196810),
+            (Value::String("iceberg".to_string()), 1210000089),
+            (
+                Value::Uuid(Uuid::parse_str("f79c3e09-677c-4bbd-a479-3f349cb785e7").unwrap()),
+                1488055340,
+            ),
+            (Value::Fixed(vec![0, 1, 2, 3]), -188683207),
+            (Value::Binary(vec![0, 1, 2, 3]), -188683207),
+        ];
+        for (value, expected) in vectors {
+            assert_eq!(expected, bucket_hash(&value).unwrap(), "{value:?}");
+        }
+        assert!(bucket_hash(&Value::Double(1.0)).is_err());
+        assert!(bucket_hash(&Value::Boolean(true)).is_err());
+    }
+
+    #[test]
+    fn test_bucket() {
+        let bucket = Transform::Bucket(16);
+        assert_eq!(
+            Some(Value::Int((2017239379 & i32::MAX) % 16)),
+            bucket.apply(Some(&Value::Int(34))).unwrap()
+        );
+        assert_eq!(
+            Some(Value::Int((-653330422 & i32::MAX) % 16)),
+            bucket.apply(Some(&Value::Date(17486))).unwrap()
+        );
+        assert_eq!(None, bucket.apply(None).unwrap());
+        assert!(Transform::Bucket(0).apply(Some(&Value::Int(1))).is_err());
+    }
+
+    #[test]
+    fn test_truncate() {
+        let truncate = Transform::Truncate(10);
+        let cases = vec![
+            (Value::Int(1), Value::Int(0)),
+            (Value::Int(-1), Value::Int(-10)),
+            (Value::Long(1), Value::Long(0)),
+            (Value::Long(-1), Value::Long(-10)),
+            (Value::Decimal(1065), Value::Decimal(1060)),
+            (Value::Decimal(-1065), Value::Decimal(-1070)),
+            // The minimum values wrap around, as in the reference implementation.
+            (Value::Int(i32::MIN), Value::Int(i32::MAX - 1)),
+            (Value::Long(i64::MIN), Value::Long(i64::MAX - 1)),
+            (Value::Decimal(i128::MIN), Value::Decimal(i128::MAX - 1)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(Some(expected), truncate.apply(Some(&input)).unwrap());
+        }
+        assert_eq!(
+            Some(Value::Decimal(1050)),
+            Transform::Truncate(50)
+                .apply(Some(&Value::Decimal(1065)))
+                .unwrap()
+        );
+        assert_eq!(
+            Some(Value::String("ice".to_string())),
+            Transform::Truncate(3)
+                .apply(Some(&Value::String("iceberg".to_string())))
+                .unwrap()
+        );
+        // Strings are truncated by code point, not byte.
+        assert_eq!(
+            Some(Value::String("\u{1F600}\u{00E9}".to_string())),
+            Transform::Truncate(2)
+                .apply(Some(&Value::String("\u{1F600}\u{00E9}x".to_string())))
+                .unwrap()
+        );
+        assert_eq!(
+            Some(Value::Binary(vec![1, 2])),
+            Transform::Truncate(2)
+                .apply(Some(&Value::Binary(vec![1, 2, 3])))
+                .unwrap()
+        );
+        assert!(truncate.apply(Some(&Value::Double(1.0))).is_err());
+        for width in [0, u32::MAX] {
+            assert!(matches!(
+                Transform::Truncate(width).apply(Some(&Value::Int(i32::MIN))),
+                Err(Error::Invalid(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_temporal_transforms() {
+        // 2017-11-16T22:31:08
+        let ts = Value::Timestamp(1_510_871_468_000_000);
+        let ts_ns = Value::TimestampzNs(1_510_871_468_000_000_000);
+        let date = Value::Date(17486);
+        for value in [&ts, &ts_ns, &date] {
+            assert_eq!(
+                Some(Value::Int(47)),
+                Transform::Year.apply(Some(value)).unwrap()
+            );
+            assert_eq!(
+                Some(Value::Int(47 * 12 + 10)),
+                Transform::Month.apply(Some(value)).unwrap()
+            );
+            assert_eq!(
+                Some(Value::Date(17486)),
+                Transform::Day.apply(Some(value)).unwrap()
+            );
+        }
+        assert_eq!(
+            Some(Value::Int(419686)),
+            Transform::Hour.apply(Some(&ts)).unwrap()
+        );
+        assert_eq!(
+            Some(Value::Int(419686)),
+            Transform::Hour.apply(Some(&ts_ns)).unwrap()
+        );
+        assert!(Transform::Hour.apply(Some(&date)).is_err());
+    }
+
+    #[test]
+    fn test_temporal_before_epoch() {
+        // 1969-12-31T23:59:59.999999
+        let ts = Value::Timestamp(-1);
+        assert_eq!(
+            Some(Value::Int(-1)),
+            Transform::Year.apply(Some(&ts)).unwrap()
+        );
+        assert_eq!(
+            Some(Value::Int(-1)),
+            Transform::Month.apply(Some(&ts)).unwrap()
+        );
+        assert_eq!(
+            Some(Value::Date(-1)),
+            Transform::Day.apply(Some(&ts)).unwrap()
+        );
+        assert_eq!(
+            Some(Value::Int(-1)),
+            Transform::Hour.apply(Some(&ts)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_result_type() {
+        let ts = PrimitiveType::Timestampz;
+        assert_eq!(
+            PrimitiveType::Int,
+            Transform::Hour.result_type(&ts).unwrap()
+        );
+        assert_eq!(
+            PrimitiveType::Date,
+            Transform::Day.result_type(&ts).unwrap()
+        );
+        assert_eq!(
+            PrimitiveType::Int,
+            Transform::Bucket(4)
+                .result_type(&PrimitiveType::String)
+                .unwrap()
+        );
+        let decimal = PrimitiveType::decimal(9, 2).unwrap();
+        assert_eq!(
+            decimal,
+            Transform::Truncate(10).result_type(&decimal).unwrap()
+        );
+        assert_eq!(
+            PrimitiveType::Double,
+            Transform::Void.result_type(&PrimitiveType::Double).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_can_transform() {
+        assert!(!Transform::Hour.can_transform(&PrimitiveType::Date));
+        assert!(Transform::Day.can_transform(&PrimitiveType::Date));
+        assert!(!Transform::Bucket(4).can_transform(&PrimitiveType::Float));
+        assert!(!Transform::Truncate(4).can_transform(&PrimitiveType::Uuid));
+        assert!(!Transform::Year.can_transform(&PrimitiveType::Long));
+        assert!(!Transform::Identity.can_transform(&PrimitiveType::Variant));
+        assert!(Transform::Void.can_transform(&PrimitiveType::Variant));
+        assert!(Transform::Hour.result_type(&PrimitiveType::Date).is_err());
+    }
+
+    #[test]
+    fn test_human_string() {
+        let ts = PrimitiveType::Timestampz;
+        let cases = vec![
+            (Transform::Year, ts.clone(), Value::Int(47), "2017"),
+            (
+                Transform::Month,
+                ts.clone(),
+                Value::Int(47 * 12 + 10),
+                "2017-11",
+            ),
+            (Transform::Day, ts.clone(), Value::Date(17486), "2017-11-16"),
+            (
+                Transform::Hour,
+                ts.clone(),
+                Value::Int(419686),
+                "2017-11-16-22",
+            ),
+            (Transform::Bucket(16), ts.clone(), Value::Int(3), "3"),
+            (
+                Transform::Identity,
+                ts,
+                Value::Timestampz(1_510_871_468_000_000),
+                "2017-11-16T22:31:08Z",
+            ),
+            (
+                Transform::Identity,
+                PrimitiveType::Timestamp,
+                Value::Timestamp(1_510_871_460_000_000),
+                "2017-11-16T22:31",
+            ),
+            (
+                Transform::Identity,
+                PrimitiveType::Time,
+                Value::Time(81_068_000_100),
+                "22:31:08.000100",
+            ),
+            (
+                Transform::Truncate(10),
+                PrimitiveType::decimal(9, 2).unwrap(),
+                Value::Decimal(-1070),
+                "-10.70",
+            ),
+            (
+                Transform::Identity,
+                PrimitiveType::Binary,
+                Value::Binary(vec![0, 1, 2, 3]),
+                "AAECAw==",
+            ),
+            (
+                Transform::Identity,
+                PrimitiveType::String,
+                Value::String("a b/c".to_string()),
+                "a b/c",
+            ),
+        ];
+        for (transform, source, value, expected) in cases {
+            assert_eq!(
+                expected,
+                transform.to_human_string(&source, Some(&value)).unwrap()
+            );
+            assert_eq!(
+                Some(value),
+                transform.parse_human_string(&source, expected).unwrap()
+            );
+        }
+        assert_eq!(
+            "null",
+            Transform::Day
+                .to_human_string(&PrimitiveType::Date, None)
+                .unwrap()
+        );
+        assert_eq!(
+            None,
+            Transform::Day
+                .parse_human_string(&PrimitiveType::Date, "null")
+                .unwrap()
+        );
+        assert!(Transform::Hour
+            .parse_human_string(&PrimitiveType::Timestamp, "2017-11-16-25")
+            .is_err());
+
+        // Results that overflow an int are invalid rather than wrapping.
+        assert!(matches!(
+            Transform::Year.parse_human_string(&PrimitiveType::Date, "-2147483648"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            Transform::Year.to_human_string(&PrimitiveType::Date, Some(&Value::Int(i32::MAX))),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            Transform::Hour.parse_human_string(&PrimitiveType::Timestamp, "+262000-01-01-00"),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_satisfies_order_of() {
+        assert!(Transform::Day.preserves_order());
+        assert!(!Transform::Bucket(4).preserves_order());
+        assert!(!Transform::Void.preserves_order());
+
+        assert!(Transform::Day.satisfies_order_of(&Transform::Month));
+        assert!(Transform::Hour.satisfies_order_of(&Transform::Year));
+        assert!(!Transform::Month.satisfies_order_of(&Transform::Day));
+        assert!(Transform::Identity.satisfies_order_of(&Transform::Truncate(4)));
+        assert!(Transform::Identity.satisfies_order_of(&Transform::Hour));
+        assert!(!Transform::Identity.satisfies_order_of(&Transform::Bucket(4)));
+        assert!(Transform::Bucket(4).satisfies_order_of(&Transform::Bucket(4)));
+        assert!(!Transform::Bucket(4).satisfies_order_of(&Transform::Bucket(8)));
+        assert!(!Transform::Truncate(4).satisfies_order_of(&Transform::Truncate(2)));
+        assert!(!Transform::Day.satisfies_order_of(&Transform::Identity));
+    }
+
+    #[test]
+    fn test_identity_and_void() {
+        let value = Value::Double(1.5);
+        assert_eq!(
+            Some(value.clone()),
+            Transform::Identity.apply(Some(&value)).unwrap()
+        );
+        assert_eq!(None, Transform::Void.apply(Some(&value)).unwrap());
+    }
+}
@@ -0,0 +1,988 @@
+/*!
+Typed values for the [types](crate::model::schema) of a table schema.
+
+A [Value] is used for partition tuples, column bounds and default values. The value does not
+carry the full type it was read with (for example the scale of a decimal), so converting to and
+from the [binary](https://iceberg.apache.org/spec/#binary-single-value-serialization) and
+[JSON](https://iceberg.apache.org/spec/#json-single-value-serialization) single-value
+serialisations requires the [PrimitiveType] or [AllType] of the value.
+*/
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde_json::{Map as JsonMap, Number, Value as JsonValue};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::model::decimal;
+use crate::model::schema::{AllType, PrimitiveType};
+use crate::model::variant::Variant as VariantValue;
+
+#[derive(Debug, Clone)]
+/// A single value of an Iceberg type.
+pub enum Value {
+    /// True or False
+    Boolean(bool),
+    /// 32-bit signed integer
+    Int(i32),
+    /// 64-bit signed integer
+    Long(i64),
+    /// 32-bit IEEE 753 floating bit.
+    Float(f32),
+    /// 64-bit IEEE 753 floating bit.
+    Double(f64),
+    /// Fixed point decimal, stored as the unscaled value.
+    Decimal(i128),
+    /// Days from 1970-01-01.
+    Date(i32),
+    /// Microseconds from midnight.
+    Time(i64),
+    /// Microseconds from 1970-01-01 00:00:00.
+    Timestamp(i64),
+    /// Microseconds from 1970-01-01 00:00:00 UTC.
+    Timestampz(i64),
+    /// Nanoseconds from 1970-01-01 00:00:00.
+    TimestampNs(i64),
+    /// Nanoseconds from 1970-01-01 00:00:00 UTC.
+    TimestampzNs(i64),
+    /// UTF-8 character sequence
+    String(String),
+    /// Universally Unique Identifier
+    Uuid(Uuid),
+    /// Fixed length byte array
+    Fixed(Vec<u8>),
+    /// Arbitrary-length byte array.
+    Binary(Vec<u8>),
+    /// Semi-structured variant value.
+    Variant(VariantValue),
+    /// Geometry stored as well-known binary (WKB).
+    Geometry(Vec<u8>),
+    /// Geography stored as well-known binary (WKB).
+    Geography(Vec<u8>),
+    /// Field values of a struct, in field order.
+    Struct(Vec<Option<Value>>),
+    /// Elements of a list.
+    List(Vec<Option<Value>>),
+    /// Key value pairs of a map.
+    Map(Vec<(Value, Option<Value>)>),
+}
+
+/// Floating point values are equal when their bit patterns are equal, so that
+/// `NaN` can be used in partition tuples.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        use Value::*;
+        match (self, other) {
+            (Boolean(a), Boolean(b)) => a == b,
+            (Int(a), Int(b)) | (Date(a), Date(b)) => a == b,
+            (Long(a), Long(b))
+            | (Time(a), Time(b))
+            | (Timestamp(a), Timestamp(b))
+            | (Timestampz(a), Timestampz(b))
+            | (TimestampNs(a), TimestampNs(b))
+            | (TimestampzNs(a), TimestampzNs(b)) => a == b,
+            (Float(a), Float(b)) => a.to_bits() == b.to_bits(),
+            (Double(a), Double(b)) => a.to_bits() == b.to_bits(),
+            (Decimal(a), Decimal(b)) => a == b,
+            (String(a), String(b)) => a == b,
+            (Uuid(a), Uuid(b)) => a == b,
+            (Fixed(a), Fixed(b))
+            | (Binary(a), Binary(b))
+            | (Geometry(a), Geometry(b))
+            | (Geography(a), Geography(b)) => a == b,
+            (Variant(a), Variant(b)) => a == b,
+            (Struct(a), Struct(b)) | (List(a), List(b)) => a == b,
+            (Map(a), Map(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use Value::*;
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Boolean(v) => v.hash(state),
+            Int(v) | Date(v) => v.hash(state),
+            Long(v) | Time(v) | Timestamp(v) | Timestampz(v) | TimestampNs(v) | TimestampzNs(v) => {
+                v.hash(state)
+            }
+            Float(v) => v.to_bits().hash(state),
+            Double(v) => v.to_bits().hash(state),
+            Decimal(v) => v.hash(state),
+            String(v) => v.hash(state),
+            Uuid(v) => v.hash(state),
+            Fixed(v) | Binary(v) | Geometry(v) | Geography(v) => v.hash(state),
+            Variant(v) => v.hash(state),
+            Struct(v) | List(v) => v.hash(state),
+            Map(v) => v.hash(state),
+        }
+    }
+}
+
+/// Compare floating point values the way Java's `Float.compare` does: `-0.0` is less than `0.0`
+/// and `NaN` is greater than every other value. NaNs are ordered by bit pattern, so that only
+/// equal values compare as equal.
+macro_rules! float_cmp {
+    ($a:expr, $b:expr) => {
+        match ($a.is_nan(), $b.is_nan()) {
+            (true, true) => $a.to_bits().cmp(&$b.to_bits()),
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => $a.total_cmp($b),
+        }
+    };
+}
+
+fn optional_cmp(a: &Option<Value>, b: &Option<Value>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(b),
+        _ => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+impl Value {
+    /// A total order of values of the same type, as used to sort data:
+    ///
+    /// * numbers, dates and times by value, with floating point values ordered as by Java's
+    ///   `Float.compare` (`-0.0 < 0.0`, and `NaN` after every other value),
+    /// * strings by code point,
+    /// * binary, fixed and UUID values by their unsigned bytes,
+    /// * structs and lists element by element, with nulls first.
+    ///
+    /// Values of different types are ordered by type, and variants compare as equal.
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        use Value::*;
+        match (self, other) {
+            (Boolean(a), Boolean(b)) => a.cmp(b),
+            (Int(a), Int(b)) | (Date(a), Date(b)) => a.cmp(b),
+            (Long(a), Long(b))
+            | (Time(a), Time(b))
+            | (Timestamp(a), Timestamp(b))
+            | (Timestampz(a), Timestampz(b))
+            | (TimestampNs(a), TimestampNs(b))
+            | (TimestampzNs(a), TimestampzNs(b)) => a.cmp(b),
+            (Float(a), Float(b)) => float_cmp!(a, b),
+            (Double(a), Double(b)) => float_cmp!(a, b),
+            (Decimal(a), Decimal(b)) => a.cmp(b),
+            // The bytes of UTF-8 strings are in the same order as their code points.
+            (String(a), String(b)) => a.as_bytes().cmp(b.as_bytes()),
+            (Uuid(a), Uuid(b)) => a.as_bytes().cmp(b.as_bytes()),
+            (Fixed(a), Fixed(b))
+            | (Binary(a), Binary(b))
+            | (Geometry(a), Geometry(b))
+            | (Geography(a), Geography(b)) => a.cmp(b),
+            (Variant(_), Variant(_)) => Ordering::Equal,
+            (Struct(a), Struct(b)) | (List(a), List(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| optional_cmp(a, b))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (Map(a), Map(b)) => a
+                .iter()
+                .zip(b)
+                .map(|((ka, va), (kb, vb))| ka.total_cmp(kb).then_with(|| optional_cmp(va, vb)))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+
+    fn type_rank(&self) -> u8 {
+        use Value::*;
+        match self {
+            Boolean(_) => 0,
+            Int(_) => 1,
+            Long(_) => 2,
+            Float(_) => 3,
+            Double(_) => 4,
+            Decimal(_) => 5,
+            Date(_) => 6,
+            Time(_) => 7,
+            Timestamp(_) => 8,
+            Timestampz(_) => 9,
+            TimestampNs(_) => 10,
+            TimestampzNs(_) => 11,
+            String(_) => 12,
+            Uuid(_) => 13,
+            Fixed(_) => 14,
+            Binary(_) => 15,
+            Variant(_) => 16,
+            Geometry(_) => 17,
+            Geography(_) => 18,
+            Struct(_) => 19,
+            List(_) => 20,
+            Map(_) => 21,
+        }
+    }
+}
+
+impl Value {
+    /// Serialise the value using the
+    /// [binary single-value serialisation](https://iceberg.apache.org/spec/#binary-single-value-serialization)
+    /// used for lower and upper bounds.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        use Value::*;
+        Ok(match self {
+            Boolean(v) => vec![u8::from(*v)],
+            Int(v) | Date(v) => v.to_le_bytes().to_vec(),
+            Long(v) | Time(v) | Timestamp(v) | Timestampz(v) | TimestampNs(v) | TimestampzNs(v) => {
+                v.to_le_bytes().to_vec()
+            }
+            Float(v) => v.to_le_bytes().to_vec(),
+            Double(v) => v.to_le_bytes().to_vec(),
+            Decimal(v) => decimal_to_bytes(*v),
+            String(v) => v.as_bytes().to_vec(),
+            Uuid(v) => v.as_bytes().to_vec(),
+            Fixed(v) | Binary(v) | Geometry(v) | Geography(v) => v.clone(),
+            Variant(_) | Struct(_) | List(_) | Map(_) => {
+                return Err(Error::Unsupported(
+                    "binary serialisation of variant and nested values".to_string(),
+                ))
+            }
+        })
+    }
+
+    /// Read a value of `data_type` from its
+    /// [binary single-value serialisation](https://iceberg.apache.org/spec/#binary-single-value-serialization).
+    ///
+    /// Geometry and geography values are read as WKB. Their column bounds are not values but
+    /// the corners of a box, read with
+    /// [BoundingBox::from_bounds](crate::model::geo::BoundingBox::from_bounds).
+    pub fn try_from_bytes(bytes: &[u8], data_type: &PrimitiveType) -> Result<Value> {
+        Ok(match data_type {
+            PrimitiveType::Boolean => match bytes {
+                [0] => Value::Boolean(false),
+                [1] => Value::Boolean(true),
+                _ => return Err(invalid_bytes(data_type, bytes)),
+            },
+            PrimitiveType::Int => Value::Int(i32::from_le_bytes(fixed_bytes(bytes, data_type)?)),
+            PrimitiveType::Date => Value::Date(i32::from_le_bytes(fixed_bytes(bytes, data_type)?)),
+            PrimitiveType::Long => match bytes.len() {
+                // Bounds of an int column promoted to long are stored in 4 bytes.
+                4 => Value::Long(i32::from_le_bytes(fixed_bytes(bytes, data_type)?).into()),
+                _ => Value::Long(i64::from_le_bytes(fixed_bytes(bytes, data_type)?)),
+            },
+            PrimitiveType::Float => {
+                Value::Float(f32::from_le_bytes(fixed_bytes(bytes, data_type)?))
+            }
+            PrimitiveType::Double => match bytes.len() {
+                // Bounds of a float column promoted to double are stored in 4 bytes.
+                4 => Value::Double(f32::from_le_bytes(fixed_bytes(bytes, data_type)?).into()),
+                _ => Value::Double(f64::from_le_bytes(fixed_bytes(bytes, data_type)?)),
+            },
+            PrimitiveType::Time => Value::Time(i64::from_le_bytes(fixed_bytes(bytes, data_type)?)),
+            PrimitiveType::Timestamp => {
+                Value::Timestamp(i64::from_le_bytes(fixed_bytes(bytes, data_type)?))
+            }
+            PrimitiveType::Timestampz => {
+                Value::Timestampz(i64::from_le_bytes(fixed_bytes(bytes, data_type)?))
+            }
+            PrimitiveType::TimestampNs => {
+                Value::TimestampNs(i64::from_le_bytes(fixed_bytes(bytes, data_type)?))
+            }
+            PrimitiveType::TimestampzNs => {
+                Value::TimestampzNs(i64::from_le_bytes(fixed_bytes(bytes, data_type)?))
+            }
+            PrimitiveType::Decimal { precision, .. } => {
+                let unscaled = decimal_from_bytes(bytes)?;
+                if !decimal::fits_precision(unscaled, *precision) {
+                    return Err(invalid_bytes(data_type, bytes));
+                }
+                Value::Decimal(unscaled)
+            }
+            PrimitiveType::String => Value::String(
+                std::str::from_utf8(bytes)
+                    .map_err(|_| invalid_bytes(data_type, bytes))?
+                    .to_string(),
+            ),
+            PrimitiveType::Uuid => {
+                Value::Uuid(Uuid::from_slice(bytes).map_err(|_| invalid_bytes(data_type, bytes))?)
+            }
+            PrimitiveType::Fixed(len) => {
+                if bytes.len() as u64 != *len {
+                    return Err(invalid_bytes(data_type, bytes));
+                }
+                Value::Fixed(bytes.to_vec())
+            }
+            PrimitiveType::Binary => Value::Binary(bytes.to_vec()),
+            PrimitiveType::Geometry { .. } => Value::Geometry(bytes.to_vec()),
+            PrimitiveType::Geography { .. } => Value::Geography(bytes.to_vec()),
+            PrimitiveType::Unknown | PrimitiveType::Variant => {
+                return Err(Error::Unsupported(format!(
+                    "binary serialisation of {data_type:?} values"
+                )))
+            }
+        })
+    }
+
+    /// Serialise the value using the
+    /// [JSON single-value serialisation](https://iceberg.apache.org/spec/#json-single-value-serialization)
+    /// used for default values and partition statistics.
+    pub fn to_json(&self, data_type: &AllType) -> Result<JsonValue> {
+        use Value::*;
+        Ok(match (self, data_type) {
+            (Boolean(v), _) => JsonValue::Bool(*v),
+            (Int(v), _) => JsonValue::from(*v),
+            (Long(v), _) => JsonValue::from(*v),
+            (Float(v), _) => float_to_json(f64::from(*v))?,
+            (Double(v), _) => float_to_json(*v)?,
+            (Decimal(v), AllType::Primitive(PrimitiveType::Decimal { scale, .. })) => {
+                JsonValue::String(decimal_to_string(*v, *scale))
+            }
+            (Date(v), _) => JsonValue::String(days_to_date(*v)?.format("%Y-%m-%d").to_string()),
+            (Time(v), _) => {
+                JsonValue::String(micros_to_time(*v)?.format("%H:%M:%S%.6f").to_string())
+            }
+            (Timestamp(v), _) => JsonValue::String(
+                micros_to_datetime(*v)?
+                    .format("%Y-%m-%dT%H:%M:%S%.6f")
+                    .to_string(),
+            ),
+            (Timestampz(v), _) => JsonValue::String(
+                micros_to_datetime(*v)?
+                    .format("%Y-%m-%dT%H:%M:%S%.6f+00:00")
+                    .to_string(),
+            ),
+            (TimestampNs(v), _) => JsonValue::String(
+                nanos_to_datetime(*v)
+                    .format("%Y-%m-%dT%H:%M:%S%.9f")
+                    .to_string(),
+            ),
+            (TimestampzNs(v), _) => JsonValue::String(
+                nanos_to_datetime(*v)
+                    .format("%Y-%m-%dT%H:%M:%S%.9f+00:00")
+                    .to_string(),
+            ),
+            (String(v), _) => JsonValue::String(v.clone()),
+            (Uuid(v), _) => JsonValue::String(v.to_hyphenated().to_string()),
+            (Fixed(v), _) | (Binary(v), _) => JsonValue::String(to_hex(v)),
+            (Struct(fields), AllType::Struct(struct_type)) => {
+                if fields.len() != struct_type.fields.len() {
+                    return Err(Error::Invalid(format!(
+                        "struct value has {} fields but type has {}",
+                        fields.len(),
+                        struct_type.fields.len()
+                    )));
+                }
+                let mut object = JsonMap::new();
+                for (field, value) in struct_type.fields.iter().zip(fields) {
+                    object.insert(
+                        field.id.to_string(),
+                        optional_to_json(value.as_ref(), &field.field_type)?,
+                    );
+                }
+                JsonValue::Object(object)
+            }
+            (List(elements), AllType::List(list_type)) => JsonValue::Array(
+                elements
+                    .iter()
+                    .map(|e| optional_to_json(e.as_ref(), &list_type.element))
+                    .collect::<Result<_>>()?,
+            ),
+            (Map(entries), AllType::Map(map_type)) => {
+                let keys = entries
+                    .iter()
+                    .map(|(k, _)| k.to_json(&map_type.key))
+                    .collect::<Result<_>>()?;
+                let values = entries
+                    .iter()
+                    .map(|(_, v)| optional_to_json(v.as_ref(), &map_type.value))
+                    .collect::<Result<_>>()?;
+                let mut object = JsonMap::new();
+                object.insert("keys".to_string(), JsonValue::Array(keys));
+                object.insert("values".to_string(), JsonValue::Array(values));
+                JsonValue::Object(object)
+            }
+            (Variant(_), _) => {
+                return Err(Error::Unsupported(
+                    "JSON serialisation of variant values".to_string(),
+                ))
+            }
+            (Geometry(_), _) | (Geography(_), _) => {
+                return Err(Error::Unsupported(
+                    "JSON serialisation of geospatial values".to_string(),
+                ))
+            }
+            (value, data_type) => {
+                return Err(Error::Invalid(format!(
+                    "value {value:?} does not match type {data_type:?}"
+                )))
+            }
+        })
+    }
+
+    /// Read a value of `data_type` from its
+    /// [JSON single-value serialisation](https://iceberg.apache.org/spec/#json-single-value-serialization).
+    /// A JSON `null` is read as `None`.
+    pub fn try_from_json(value: &JsonValue, data_type: &AllType) -> Result<Option<Value>> {
+        if value.is_null() {
+            return Ok(None);
+        }
+        let invalid = || Error::Invalid(format!("cannot read {value} as {data_type:?}"));
+        Ok(Some(match data_type {
+            AllType::Primitive(primitive) => match primitive {
+                PrimitiveType::Boolean => Value::Boolean(value.as_bool().ok_or_else(invalid)?),
+                PrimitiveType::Int => Value::Int(
+                    value
+                        .as_i64()
+                        .and_then(|v| i32::try_from(v).ok())
+                        .ok_or_else(invalid)?,
+                ),
+                PrimitiveType::Long => Value::Long(value.as_i64().ok_or_else(invalid)?),
+                PrimitiveType::Float => Value::Float(value.as_f64().ok_or_else(invalid)? as f32),
+                PrimitiveType::Double => Value::Double(value.as_f64().ok_or_else(invalid)?),
+                PrimitiveType::Decimal { precision, scale } => {
+                    let unscaled =
+                        decimal_from_string(value.as_str().ok_or_else(invalid)?, *scale)?;
+                    if !decimal::fits_precision(unscaled, *precision) {
+                        return Err(invalid());
+                    }
+                    Value::Decimal(unscaled)
+                }
+                PrimitiveType::Date => {
+                    let date =
+                        NaiveDate::parse_from_str(value.as_str().ok_or_else(invalid)?, "%Y-%m-%d")
+                            .map_err(|_| invalid())?;
+                    Value::Date(date_to_days(&date))
+                }
+                PrimitiveType::Time => {
+                    let time = NaiveTime::parse_from_str(
+                        value.as_str().ok_or_else(invalid)?,
+                        "%H:%M:%S%.f",
+                    )
+                    .map_err(|_| invalid())?;
+                    Value::Time(
+                        i64::from(time.num_seconds_from_midnight()) * 1_000_000
+                            + i64::from(time.nanosecond()) / 1_000,
+                    )
+                }
+                PrimitiveType::Timestamp => Value::Timestamp(
+                    parse_timestamp(value.as_str().ok_or_else(invalid)?)
+                        .ok_or_else(invalid)?
+                        .and_utc()
+                        .timestamp_micros(),
+                ),
+                PrimitiveType::TimestampNs => Value::TimestampNs(
+                    parse_timestamp(value.as_str().ok_or_else(invalid)?)
+                        .and_then(|ts| ts.and_utc().timestamp_nanos_opt())
+                        .ok_or_else(invalid)?,
+                ),
+                PrimitiveType::Timestampz => Value::Timestampz(
+                    DateTime::parse_from_rfc3339(value.as_str().ok_or_else(invalid)?)
+                        .map_err(|_| invalid())?
+                        .timestamp_micros(),
+                ),
+                PrimitiveType::TimestampzNs => Value::TimestampzNs(
+                    DateTime::parse_from_rfc3339(value.as_str().ok_or_else(invalid)?)
+                        .ok()
+                        .and_then(|ts| ts.timestamp_nanos_opt())
+                        .ok_or_else(invalid)?,
+                ),
+                PrimitiveType::String => {
+                    Value::String(value.as_str().ok_or_else(invalid)?.to_string())
+                }
+                PrimitiveType::Uuid => Value::Uuid(
+                    Uuid::parse_str(value.as_str().ok_or_else(invalid)?).map_err(|_| invalid())?,
+                ),
+                PrimitiveType::Fixed(len) => {
+                    let bytes =
+                        from_hex(value.as_str().ok_or_else(invalid)?).ok_or_else(invalid)?;
+                    if bytes.len() as u64 != *len {
+                        return Err(invalid());
+                    }
+                    Value::Fixed(bytes)
+                }
+                PrimitiveType::Binary => Value::Binary(
+                    from_hex(value.as_str().ok_or_else(invalid)?).ok_or_else(invalid)?,
+                ),
+                PrimitiveType::Unknown => return Err(invalid()),
+                PrimitiveType::Variant
+                | PrimitiveType::Geometry { .. }
+                | PrimitiveType::Geography { .. } => {
+                    return Err(Error::Unsupported(format!(
+                        "JSON serialisation of {primitive:?} values"
+                    )))
+                }
+            },
+            AllType::Struct(struct_type) => {
+                let object = value.as_object().ok_or_else(invalid)?;
+                Value::Struct(
+                    struct_type
+                        .fields
+                        .iter()
+                        .map(|field| match object.get(&field.id.to_string()) {
+                            Some(v) => Value::try_from_json(v, &field.field_type),
+                            None => Ok(None),
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            AllType::List(list_type) => Value::List(
+                value
+                    .as_array()
+                    .ok_or_else(invalid)?
+                    .iter()
+                    .map(|e| Value::try_from_json(e, &list_type.element))
+                    .collect::<Result<_>>()?,
+            ),
+            AllType::Map(map_type) => {
+                let object = value.as_object().ok_or_else(invalid)?;
+                let keys = object
+                    .get("keys")
+                    .and_then(JsonValue::as_array)
+                    .ok_or_else(invalid)?;
+                let values = object
+                    .get("values")
+                    .and_then(JsonValue::as_array)
+                    .ok_or_else(invalid)?;
+                if keys.len() != values.len() {
+                    return Err(invalid());
+                }
+                Value::Map(
+                    keys.iter()
+                        .zip(values)
+                        .map(|(k, v)| {
+                            let key = Value::try_from_json(k, &map_type.key)?.ok_or_else(|| {
+                                Error::Invalid("map keys cannot be null".to_string())
+                            })?;
+                            Ok((key, Value::try_from_json(v, &map_type.value)?))
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+        }))
+    }
+}
+
+fn optional_to_json(value: Option<&Value>, data_type: &AllType) -> Result<JsonValue> {
+    match value {
+        Some(v) => v.to_json(data_type),
+        None => Ok(JsonValue::Null),
+    }
+}
+
+fn float_to_json(v: f64) -> Result<JsonValue> {
+    Number::from_f64(v)
+        .map(JsonValue::Number)
+        .ok_or_else(|| Error::Unsupported(format!("JSON serialisation of {v}")))
+}
+
+fn invalid_bytes(data_type: &PrimitiveType, bytes: &[u8]) -> Error {
+    Error::Invalid(format!("cannot read {bytes:?} as {data_type:?}"))
+}
+
+fn fixed_bytes<const N: usize>(bytes: &[u8], data_type: &PrimitiveType) -> Result<[u8; N]> {
+    bytes
+        .try_into()
+        .map_err(|_| invalid_bytes(data_type, bytes))
+}
+
+/// The minimal big-endian two's-complement representation of an unscaled decimal.
+pub(crate) fn decimal_to_bytes(unscaled: i128) -> Vec<u8> {
+    let bytes = unscaled.to_be_bytes();
+    let mut start = 0;
+    // Drop leading bytes that only carry sign extension.
+    while start < bytes.len() - 1 {
+        let redundant = (bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0);
+        if !redundant {
+            break;
+        }
+        start += 1;
+    }
+    bytes[start..].to_vec()
+}
+
+/// Read an unscaled decimal from big-endian two's-complement bytes.
+pub(crate) fn decimal_from_bytes(bytes: &[u8]) -> Result<i128> {
+    if bytes.is_empty() || bytes.len() > 16 {
+        return Err(Error::Invalid(format!(
+            "decimal must be stored in 1 to 16 bytes, found {}",
+            bytes.len()
+        )));
+    }
+    let fill = if bytes[0] & 0x80 != 0 { 0xff } else { 0x00 };
+    let mut buf = [fill; 16];
+    buf[16 - bytes.len()..].copy_from_slice(bytes);
+    Ok(i128::from_be_bytes(buf))
+}
+
+/// Format an unscaled decimal with `scale` digits after the decimal point.
+pub(crate) fn decimal_to_string(unscaled: i128, scale: u8) -> String {
+    let digits = unscaled.unsigned_abs().to_string();
+    let sign = if unscaled < 0 { "-" } else { "" };
+    let scale = scale as usize;
+    if scale == 0 {
+        return format!("{sign}{digits}");
+    }
+    let digits = format!("{digits:0>width$}", width = scale + 1);
+    let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+    format!("{sign}{int_part}.{frac_part}")
+}
+
+/// Parse a decimal string that has exactly `scale` digits after the decimal point.
+pub(crate) fn decimal_from_string(s: &str, scale: u8) -> Result<i128> {
+    let invalid = || Error::Invalid(format!("cannot parse {s} as decimal with scale {scale}"));
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (int_part, frac_part) = match unsigned.split_once('.') {
+        Some((i, f)) => (i, f),
+        None => (unsigned, ""),
+    };
+    if frac_part.len() != scale as usize
+        || int_part.is_empty()
+        || !int_part
+            .chars()
+            .chain(frac_part.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let unscaled: i128 = format!("{int_part}{frac_part}")
+        .parse()
+        .map_err(|_| invalid())?;
+    Ok(if negative { -unscaled } else { unscaled })
+}
+
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// The calendar date `days` after 1970-01-01.
+pub(crate) fn days_to_date(days: i32) -> Result<NaiveDate> {
+    days.checked_add(UNIX_EPOCH_DAYS_FROM_CE)
+        .and_then(NaiveDate::from_num_days_from_ce_opt)
+        .ok_or_else(|| Error::Invalid(format!("date out of range: {days}")))
+}
+
+/// The number of days from 1970-01-01 to `date`.
+pub(crate) fn date_to_days(date: &NaiveDate) -> i32 {
+    date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE
+}
+
+pub(crate) fn micros_to_time(micros: i64) -> Result<NaiveTime> {
+    let secs = u32::try_from(micros.div_euclid(1_000_000))
+        .map_err(|_| Error::Invalid(format!("time out of range: {micros}")))?;
+    let nanos = micros.rem_euclid(1_000_000) as u32 * 1_000;
+    NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos)
+        .ok_or_else(|| Error::Invalid(format!("time out of range: {micros}")))
+}
+
+/// The UTC date time `micros` microseconds after 1970-01-01 00:00:00.
+pub(crate) fn micros_to_datetime(micros: i64) -> Result<NaiveDateTime> {
+    DateTime::from_timestamp_micros(micros)
+        .map(|ts| ts.naive_utc())
+        .ok_or_else(|| Error::Invalid(format!("timestamp out of range: {micros}")))
+}
+
+/// The UTC date time `nanos` nanoseconds after 1970-01-01 00:00:00.
+pub(crate) fn nanos_to_datetime(nanos: i64) -> NaiveDateTime {
+    DateTime::from_timestamp_nanos(nanos).naive_utc()
+}
+
+fn parse_timestamp(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+pub(crate) fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::schema::{List, Map, Struct, StructField};
+    use serde_json::json;
+
+    fn primitive(p: PrimitiveType) -> AllType {
+        AllType::Primitive(p)
+    }
+
+    #[test]
+    fn test_total_cmp() {
+        let sorted = |mut values: Vec<Value>| {
+            values.sort_by(Value::total_cmp);
+            values
+        };
+        assert_eq!(
+            vec![
+                Value::Double(f64::NEG_INFINITY),
+                Value::Double(-0.0),
+                Value::Double(0.0),
+                Value::Double(f64::INFINITY),
+                Value::Double(f64::NAN),
+            ],
+            sorted(vec![
+                Value::Double(f64::NAN),
+                Value::Double(0.0),
+                Value::Double(f64::INFINITY),
+                Value::Double(-0.0),
+                Value::Double(f64::NEG_INFINITY),
+            ])
+        );
+        assert_eq!(
+            Ordering::Greater,
+            Value::Float(-f32::NAN).total_cmp(&Value::Float(f32::MAX))
+        );
+        // Unsigned bytes: 0x80 is after 0x7f.
+        assert_eq!(
+            Ordering::Greater,
+            Value::Binary(vec![0x80]).total_cmp(&Value::Binary(vec![0x7f, 0xff]))
+        );
+        // Code points: U+FFFF is before U+10000, although its UTF-16 code unit is not.
+        assert_eq!(
+            Ordering::Less,
+            Value::String("\u{FFFF}".to_string())
+                .total_cmp(&Value::String("\u{10000}".to_string()))
+        );
+        assert_eq!(
+            Ordering::Less,
+            Value::Struct(vec![None, Some(Value::Int(2))])
+                .total_cmp(&Value::Struct(vec![Some(Value::Int(1)), None]))
+        );
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let values = vec![
+            (Value::Boolean(true), PrimitiveType::Boolean),
+            (Value::Int(-34), PrimitiveType::Int),
+            (Value::Long(1 << 40), PrimitiveType::Long),
+            (Value::Float(1.5), PrimitiveType::Float),
+            (Value::Double(-2.25), PrimitiveType::Double),
+            (Value::Date(17_486), PrimitiveType::Date),
+            (Value::Time(81_068_123_456), PrimitiveType::Time),
+            (
+                Value::Timestamp(1_510_871_468_123_456),
+                PrimitiveType::Timestamp,
+            ),
+            (
+                Value::Timestampz(1_510_871_468_123_456),
+                PrimitiveType::Timestampz,
+            ),
+            (
+                Value::TimestampNs(1_510_871_468_123_456_789),
+                PrimitiveType::TimestampNs,
+            ),
+            (
+                Value::TimestampzNs(1_510_871_468_123_456_789),
+                PrimitiveType::TimestampzNs,
+            ),
+            (Value::String("iceberg".to_string()), PrimitiveType::String),
+            (Value::Uuid(Uuid::new_v4()), PrimitiveType::Uuid),
+            (Value::Fixed(vec![1, 2, 3]), PrimitiveType::Fixed(3)),
+            (Value::Binary(vec![0, 255]), PrimitiveType::Binary),
+            (
+                Value::Geometry(vec![1, 1, 0, 0, 0]),
+                PrimitiveType::Geometry {
+                    crs: crate::model::schema::DEFAULT_CRS.to_string(),
+                },
+            ),
+            (
+                Value::Decimal(-1420),
+                PrimitiveType::Decimal {
+                    precision: 9,
+                    scale: 2,
+                },
+            ),
+        ];
+        for (value, data_type) in values {
+            let bytes = value.to_bytes().unwrap();
+            assert_eq!(value, Value::try_from_bytes(&bytes, &data_type).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_binary_encodings() {
+        assert_eq!(vec![0x01, 0, 0, 0], Value::Int(1).to_bytes().unwrap());
+        assert_eq!(vec![0x05, 0x8c], Value::Decimal(1420).to_bytes().unwrap());
+        assert_eq!(vec![0xfa, 0x74], Value::Decimal(-1420).to_bytes().unwrap());
+        assert_eq!(vec![0x00, 0x80], Value::Decimal(128).to_bytes().unwrap());
+        assert_eq!(vec![0x80], Value::Decimal(-128).to_bytes().unwrap());
+        assert_eq!(
+            Value::Long(7),
+            Value::try_from_bytes(&[7, 0, 0, 0], &PrimitiveType::Long).unwrap()
+        );
+        assert!(Value::try_from_bytes(&[1, 2], &PrimitiveType::Int).is_err());
+        assert!(Value::try_from_bytes(
+            &[0x05, 0x8c],
+            &PrimitiveType::Decimal {
+                precision: 3,
+                scale: 2
+            }
+        )
+        .is_err());
+        assert!(Value::try_from_bytes(&[], &PrimitiveType::Unknown).is_err());
+        assert!(Value::try_from_bytes(&[], &PrimitiveType::Variant).is_err());
+    }
+
+    #[test]
+    fn test_json_primitives() {
+        let values = vec![
+            (Value::Boolean(true), PrimitiveType::Boolean, json!(true)),
+            (Value::Int(34), PrimitiveType::Int, json!(34)),
+            (Value::Long(34), PrimitiveType::Long, json!(34)),
+            (Value::Double(1.5), PrimitiveType::Double, json!(1.5)),
+            (
+                Value::Decimal(1420),
+                PrimitiveType::Decimal {
+                    precision: 9,
+                    scale: 2,
+                },
+                json!("14.20"),
+            ),
+            (
+                Value::Decimal(-5),
+                PrimitiveType::Decimal {
+                    precision: 9,
+                    scale: 2,
+                },
+                json!("-0.05"),
+            ),
+            (
+                Value::Date(17_486),
+                PrimitiveType::Date,
+                json!("2017-11-16"),
+            ),
+            (
+                Value::Time(81_068_123_456),
+                PrimitiveType::Time,
+                json!("22:31:08.123456"),
+            ),
+            (
+                Value::Timestamp(1_510_871_468_123_456),
+                PrimitiveType::Timestamp,
+                json!("2017-11-16T22:31:08.123456"),
+            ),
+            (
+                Value::Timestampz(1_510_871_468_123_456),
+                PrimitiveType::Timestampz,
+                json!("2017-11-16T22:31:08.123456+00:00"),
+            ),
+            (
+                Value::TimestampNs(1_510_871_468_123_456_789),
+                PrimitiveType::TimestampNs,
+                json!("2017-11-16T22:31:08.123456789"),
+            ),
+            (
+                Value::TimestampzNs(1_510_871_468_123_456_789),
+                PrimitiveType::TimestampzNs,
+                json!("2017-11-16T22:31:08.123456789+00:00"),
+            ),
+            (
+                Value::Uuid(Uuid::parse_str("f79c3e09-677c-4bbd-a479-3f349cb785e7").unwrap()),
+                PrimitiveType::Uuid,
+                json!("f79c3e09-677c-4bbd-a479-3f349cb785e7"),
+            ),
+            (
+                Value::Binary(vec![0, 0, 255, 0, 0]),
+                PrimitiveType::Binary,
+                json!("0000FF0000"),
+            ),
+        ];
+        for (value, data_type, expected) in values {
+            let data_type = primitive(data_type);
+            assert_eq!(expected, value.to_json(&data_type).unwrap());
+            assert_eq!(
+                Some(value),
+                Value::try_from_json(&expected, &data_type).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_v3_types() {
+        assert_eq!(
+            None,
+            Value::try_from_json(&JsonValue::Null, &primitive(PrimitiveType::Unknown)).unwrap()
+        );
+        assert!(Value::try_from_json(&json!(1), &primitive(PrimitiveType::Unknown)).is_err());
+        assert!(Value::try_from_json(&json!("{}"), &primitive(PrimitiveType::Variant)).is_err());
+        assert_eq!(
+            Some(Value::Timestampz(1_510_871_468_123_456)),
+            Value::try_from_json(
+                &json!("2017-11-16T23:31:08.123456+01:00"),
+                &primitive(PrimitiveType::Timestampz)
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_json_nested() {
+        let struct_type = AllType::Struct(Struct {
+            fields: vec![
+                StructField {
+                    id: 1,
+                    name: "id".to_string(),
+                    required: true,
+                    field_type: primitive(PrimitiveType::Int),
+                    doc: None,
+                },
+                StructField {
+                    id: 2,
+                    name: "tags".to_string(),
+                    required: false,
+                    field_type: AllType::List(List {
+                        element_id: 3,
+                        element_required: false,
+                        element: Box::new(primitive(PrimitiveType::String)),
+                    }),
+                    doc: None,
+                },
+                StructField {
+                    id: 4,
+                    name: "props".to_string(),
+                    required: false,
+                    field_type: AllType::Map(Map {
+                        key_id: 5,
+                        key: Box::new(primitive(PrimitiveType::String)),
+                        value_id: 6,
+                        value_required: false,
+                        value: Box::new(primitive(PrimitiveType::Long)),
+                    }),
+                    doc: None,
+                },
+            ],
+        });
+        let value = Value::Struct(vec![
+            Some(Value::Int(1)),
+            Some(Value::List(vec![
+                Some(Value::String("a".to_string())),
+                None,
+            ])),
+            Some(Value::Map(vec![(
+                Value::String("k".to_string()),
+                Some(Value::Long(2)),
+            )])),
+        ]);
+        let expected = json!({"1": 1, "2": ["a", null], "4": {"keys": ["k"], "values": [2]}});
+        assert_eq!(expected, value.to_json(&struct_type).unwrap());
+        assert_eq!(
+            Some(value),
+            Value::try_from_json(&expected, &struct_type).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_float_equality() {
+        assert_eq!(Value::Double(f64::NAN), Value::Double(f64::NAN));
+        assert_ne!(Value::Double(0.0), Value::Double(-0.0));
+    }
+}
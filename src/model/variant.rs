@@ -0,0 +1,1056 @@
+/*!
+Values of the semi-structured [variant](https://iceberg.apache.org/spec/#semi-structured-types) type.
+
+A [Variant] is stored as two binary buffers, the metadata (a dictionary of object field names)
+and the value, using the
+[Parquet variant encoding](https://github.com/apache/parquet-format/blob/master/VariantEncoding.md).
+[Variant::encode] and [Variant::decode] convert between the two.
+
+Variant columns may be [shredded](https://github.com/apache/parquet-format/blob/master/VariantShredding.md)
+in Parquet, where parts of the value are stored in typed columns next to the binary `value`
+column. A [ShreddedType] describes that layout and [ShreddedType::shred] and
+[ShreddedType::unshred] split a variant into, and rebuild it from, a [ShreddedValue].
+*/
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::model::decimal;
+use crate::model::schema::PrimitiveType;
+
+const VERSION: u8 = 1;
+
+const BASIC_PRIMITIVE: u8 = 0;
+const BASIC_SHORT_STRING: u8 = 1;
+const BASIC_OBJECT: u8 = 2;
+const BASIC_ARRAY: u8 = 3;
+
+const MAX_SHORT_STRING: usize = 63;
+
+#[derive(Debug, Clone)]
+/// A decoded variant value.
+pub enum Variant {
+    /// Variant null
+    Null,
+    /// True or False
+    Boolean(bool),
+    /// 8-bit signed integer
+    Int8(i8),
+    /// 16-bit signed integer
+    Int16(i16),
+    /// 32-bit signed integer
+    Int32(i32),
+    /// 64-bit signed integer
+    Int64(i64),
+    /// 32-bit IEEE 753 floating bit.
+    Float(f32),
+    /// 64-bit IEEE 753 floating bit.
+    Double(f64),
+    /// Fixed point decimal with up to 38 digits.
+    Decimal {
+        /// The unscaled value.
+        unscaled: i128,
+        /// The number of digits to the right of the decimal point.
+        scale: u8,
+    },
+    /// Days from 1970-01-01.
+    Date(i32),
+    /// Microseconds from midnight.
+    Time(i64),
+    /// Microseconds from 1970-01-01 00:00:00 UTC.
+    Timestampz(i64),
+    /// Microseconds from 1970-01-01 00:00:00.
+    Timestamp(i64),
+    /// Nanoseconds from 1970-01-01 00:00:00 UTC.
+    TimestampzNs(i64),
+    /// Nanoseconds from 1970-01-01 00:00:00.
+    TimestampNs(i64),
+    /// Arbitrary-length byte array.
+    Binary(Vec<u8>),
+    /// UTF-8 character sequence
+    String(String),
+    /// Universally Unique Identifier
+    Uuid(Uuid),
+    /// Fields of an object, ordered by name.
+    Object(BTreeMap<String, Variant>),
+    /// Elements of an array.
+    Array(Vec<Variant>),
+}
+
+/// Floating point values are equal when their bit patterns are equal, matching [Value](crate::model::values::Value).
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        use Variant::*;
+        match (self, other) {
+            (Null, Null) => true,
+            (Boolean(a), Boolean(b)) => a == b,
+            (Int8(a), Int8(b)) => a == b,
+            (Int16(a), Int16(b)) => a == b,
+            (Int32(a), Int32(b)) | (Date(a), Date(b)) => a == b,
+            (Int64(a), Int64(b))
+            | (Time(a), Time(b))
+            | (Timestampz(a), Timestampz(b))
+            | (Timestamp(a), Timestamp(b))
+            | (TimestampzNs(a), TimestampzNs(b))
+            | (TimestampNs(a), TimestampNs(b)) => a == b,
+            (Float(a), Float(b)) => a.to_bits() == b.to_bits(),
+            (Double(a), Double(b)) => a.to_bits() == b.to_bits(),
+            (
+                Decimal {
+                    unscaled: a,
+                    scale: sa,
+                },
+                Decimal {
+                    unscaled: b,
+                    scale: sb,
+                },
+            ) => a == b && sa == sb,
+            (Binary(a), Binary(b)) => a == b,
+            (String(a), String(b)) => a == b,
+            (Uuid(a), Uuid(b)) => a == b,
+            (Object(a), Object(b)) => a == b,
+            (Array(a), Array(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Variant {}
+
+impl Hash for Variant {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use Variant::*;
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Null => (),
+            Boolean(v) => v.hash(state),
+            Int8(v) => v.hash(state),
+            Int16(v) => v.hash(state),
+            Int32(v) | Date(v) => v.hash(state),
+            Int64(v) | Time(v) | Timestampz(v) | Timestamp(v) | TimestampzNs(v)
+            | TimestampNs(v) => v.hash(state),
+            Float(v) => v.to_bits().hash(state),
+            Double(v) => v.to_bits().hash(state),
+            Decimal { unscaled, scale } => {
+                unscaled.hash(state);
+                scale.hash(state);
+            }
+            Binary(v) => v.hash(state),
+            String(v) => v.hash(state),
+            Uuid(v) => v.hash(state),
+            Object(v) => v.hash(state),
+            Array(v) => v.hash(state),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// An element of a [VariantPath].
+pub enum PathElement {
+    /// Field of an object.
+    Field(String),
+    /// Zero based index into an array.
+    Index(usize),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A path to a value nested within a variant, for example `$.event.items[0].name`.
+pub struct VariantPath(pub Vec<PathElement>);
+
+impl VariantPath {
+    /// Parse a path of dot separated field names and `[n]` array indexes,
+    /// optionally starting with `$`.
+    pub fn parse(path: &str) -> Result<VariantPath> {
+        let invalid = || Error::Invalid(format!("invalid variant path {path}"));
+        let rest = path.strip_prefix('$').unwrap_or(path);
+        let mut elements = vec![];
+        let mut chars = rest.chars().peekable();
+        let mut expect_field = !path.starts_with('$');
+        while let Some(c) = chars.peek().copied() {
+            if c == '[' {
+                chars.next();
+                let mut digits = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    digits.push(c);
+                }
+                elements.push(PathElement::Index(digits.parse().map_err(|_| invalid())?));
+                expect_field = false;
+                continue;
+            }
+            if c == '.' {
+                chars.next();
+            } else if !expect_field {
+                return Err(invalid());
+            }
+            let mut name = String::new();
+            while let Some(c) = chars.peek().copied() {
+                if c == '.' || c == '[' {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            if name.is_empty() {
+                return Err(invalid());
+            }
+            elements.push(PathElement::Field(name));
+            expect_field = false;
+        }
+        Ok(VariantPath(elements))
+    }
+}
+
+impl Variant {
+    /// Encode the variant into its `(metadata, value)` binary representation.
+    /// The metadata dictionary is sorted and contains each object field name once.
+    pub fn encode(&self) -> (Vec<u8>, Vec<u8>) {
+        let mut names = BTreeSet::new();
+        self.collect_field_names(&mut names);
+        let names: Vec<&str> = names.into_iter().collect();
+
+        let mut value = vec![];
+        self.encode_value(&names, &mut value);
+        (encode_metadata(&names), value)
+    }
+
+    /// Decode a variant from its metadata and value buffers.
+    pub fn decode(metadata: &[u8], value: &[u8]) -> Result<Variant> {
+        let dictionary = decode_metadata(metadata)?;
+        decode_value(value, &dictionary)
+    }
+
+    /// Returns the value at `path`, or `None` if a field or index along the path does not exist.
+    pub fn get_path(&self, path: &VariantPath) -> Option<&Variant> {
+        path.0
+            .iter()
+            .try_fold(self, |current, element| match (current, element) {
+                (Variant::Object(fields), PathElement::Field(name)) => fields.get(name),
+                (Variant::Array(elements), PathElement::Index(i)) => elements.get(*i),
+                _ => None,
+            })
+    }
+
+    /// Convert a JSON document to a variant. Integers use the smallest integer type
+    /// that holds them and other numbers are stored as doubles.
+    pub fn from_json(json: &JsonValue) -> Variant {
+        match json {
+            JsonValue::Null => Variant::Null,
+            JsonValue::Bool(b) => Variant::Boolean(*b),
+            JsonValue::Number(n) => match n.as_i64() {
+                Some(i) => Variant::from_i64(i),
+                None => Variant::Double(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            JsonValue::String(s) => Variant::String(s.clone()),
+            JsonValue::Array(elements) => {
+                Variant::Array(elements.iter().map(Variant::from_json).collect())
+            }
+            JsonValue::Object(fields) => Variant::Object(
+                fields
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Variant::from_json(v)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Convert the variant to JSON. Values without a JSON equivalent are written as strings,
+    /// using the same formats as the
+    /// [JSON single-value serialisation](https://iceberg.apache.org/spec/#json-single-value-serialization).
+    pub fn to_json(&self) -> Result<JsonValue> {
+        use crate::model::schema::AllType;
+        use crate::model::values::{decimal_to_string, to_hex, Value};
+        let as_value =
+            |value: Value, data_type: PrimitiveType| value.to_json(&AllType::Primitive(data_type));
+        Ok(match self {
+            Variant::Null => JsonValue::Null,
+            Variant::Boolean(b) => JsonValue::Bool(*b),
+            Variant::Int8(v) => JsonValue::from(*v),
+            Variant::Int16(v) => JsonValue::from(*v),
+            Variant::Int32(v) => JsonValue::from(*v),
+            Variant::Int64(v) => JsonValue::from(*v),
+            Variant::Float(v) => as_value(Value::Float(*v), PrimitiveType::Float)?,
+            Variant::Double(v) => as_value(Value::Double(*v), PrimitiveType::Double)?,
+            Variant::Decimal { unscaled, scale } => {
+                JsonValue::String(decimal_to_string(*unscaled, *scale))
+            }
+            Variant::Date(v) => as_value(Value::Date(*v), PrimitiveType::Date)?,
+            Variant::Time(v) => as_value(Value::Time(*v), PrimitiveType::Time)?,
+            Variant::Timestampz(v) => as_value(Value::Timestampz(*v), PrimitiveType::Timestampz)?,
+            Variant::Timestamp(v) => as_value(Value::Timestamp(*v), PrimitiveType::Timestamp)?,
+            Variant::TimestampzNs(v) => {
+                as_value(Value::TimestampzNs(*v), PrimitiveType::TimestampzNs)?
+            }
+            Variant::TimestampNs(v) => {
+                as_value(Value::TimestampNs(*v), PrimitiveType::TimestampNs)?
+            }
+            Variant::Binary(v) => JsonValue::String(to_hex(v)),
+            Variant::String(s) => JsonValue::String(s.clone()),
+            Variant::Uuid(u) => JsonValue::String(u.to_hyphenated().to_string()),
+            Variant::Object(fields) => JsonValue::Object(
+                fields
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), v.to_json()?)))
+                    .collect::<Result<_>>()?,
+            ),
+            Variant::Array(elements) => JsonValue::Array(
+                elements
+                    .iter()
+                    .map(Variant::to_json)
+                    .collect::<Result<_>>()?,
+            ),
+        })
+    }
+
+    fn from_i64(i: i64) -> Variant {
+        if let Ok(v) = i8::try_from(i) {
+            Variant::Int8(v)
+        } else if let Ok(v) = i16::try_from(i) {
+            Variant::Int16(v)
+        } else if let Ok(v) = i32::try_from(i) {
+            Variant::Int32(v)
+        } else {
+            Variant::Int64(i)
+        }
+    }
+
+    fn collect_field_names<'a>(&'a self, names: &mut BTreeSet<&'a str>) {
+        match self {
+            Variant::Object(fields) => {
+                for (name, value) in fields {
+                    names.insert(name);
+                    value.collect_field_names(names);
+                }
+            }
+            Variant::Array(elements) => {
+                for element in elements {
+                    element.collect_field_names(names);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn encode_value(&self, names: &[&str], out: &mut Vec<u8>) {
+        let primitive = |id: u8| id << 2 | BASIC_PRIMITIVE;
+        match self {
+            Variant::Null => out.push(primitive(0)),
+            Variant::Boolean(true) => out.push(primitive(1)),
+            Variant::Boolean(false) => out.push(primitive(2)),
+            Variant::Int8(v) => {
+                out.push(primitive(3));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::Int16(v) => {
+                out.push(primitive(4));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::Int32(v) => {
+                out.push(primitive(5));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::Int64(v) => {
+                out.push(primitive(6));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::Double(v) => {
+                out.push(primitive(7));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            // The width is chosen by precision: decimal4 up to 9 digits, decimal8 up to 18.
+            Variant::Decimal { unscaled, scale } => match decimal::precision_of(*unscaled) {
+                0..=9 => {
+                    out.push(primitive(8));
+                    out.push(*scale);
+                    out.extend_from_slice(&(*unscaled as i32).to_le_bytes());
+                }
+                10..=18 => {
+                    out.push(primitive(9));
+                    out.push(*scale);
+                    out.extend_from_slice(&(*unscaled as i64).to_le_bytes());
+                }
+                _ => {
+                    out.push(primitive(10));
+                    out.push(*scale);
+                    out.extend_from_slice(&unscaled.to_le_bytes());
+                }
+            },
+            Variant::Date(v) => {
+                out.push(primitive(11));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::Timestampz(v) => {
+                out.push(primitive(12));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::Timestamp(v) => {
+                out.push(primitive(13));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::Float(v) => {
+                out.push(primitive(14));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::Binary(v) => {
+                out.push(primitive(15));
+                out.extend_from_slice(&(v.len() as u32).to_le_bytes());
+                out.extend_from_slice(v);
+            }
+            Variant::String(s) if s.len() <= MAX_SHORT_STRING => {
+                out.push((s.len() as u8) << 2 | BASIC_SHORT_STRING);
+                out.extend_from_slice(s.as_bytes());
+            }
+            Variant::String(s) => {
+                out.push(primitive(16));
+                out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                out.extend_from_slice(s.as_bytes());
+            }
+            Variant::Time(v) => {
+                out.push(primitive(17));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::TimestampzNs(v) => {
+                out.push(primitive(18));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::TimestampNs(v) => {
+                out.push(primitive(19));
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            Variant::Uuid(v) => {
+                out.push(primitive(20));
+                out.extend_from_slice(v.as_bytes());
+            }
+            Variant::Object(fields) => {
+                let mut values = vec![];
+                let mut offsets = vec![];
+                let mut ids = vec![];
+                // Fields are ordered by name, as the spec requires.
+                for (name, value) in fields {
+                    ids.push(names.binary_search(&name.as_str()).unwrap_or_default());
+                    offsets.push(values.len());
+                    value.encode_value(names, &mut values);
+                }
+                offsets.push(values.len());
+                let is_large = fields.len() > u8::MAX as usize;
+                let id_size = int_size(names.len().saturating_sub(1));
+                let offset_size = int_size(values.len());
+                out.push(
+                    (u8::from(is_large) << 4 | (id_size - 1) << 2 | (offset_size - 1)) << 2
+                        | BASIC_OBJECT,
+                );
+                write_uint(out, fields.len(), if is_large { 4 } else { 1 });
+                for id in ids {
+                    write_uint(out, id, id_size);
+                }
+                for offset in offsets {
+                    write_uint(out, offset, offset_size);
+                }
+                out.extend_from_slice(&values);
+            }
+            Variant::Array(elements) => {
+                let mut values = vec![];
+                let mut offsets = vec![];
+                for element in elements {
+                    offsets.push(values.len());
+                    element.encode_value(names, &mut values);
+                }
+                offsets.push(values.len());
+                let is_large = elements.len() > u8::MAX as usize;
+                let offset_size = int_size(values.len());
+                out.push((u8::from(is_large) << 2 | (offset_size - 1)) << 2 | BASIC_ARRAY);
+                write_uint(out, elements.len(), if is_large { 4 } else { 1 });
+                for offset in offsets {
+                    write_uint(out, offset, offset_size);
+                }
+                out.extend_from_slice(&values);
+            }
+        }
+    }
+}
+
+/// The number of bytes, between 1 and 4, needed to store `max`.
+fn int_size(max: usize) -> u8 {
+    match max {
+        0..=0xff => 1,
+        0x100..=0xffff => 2,
+        0x1_0000..=0xff_ffff => 3,
+        _ => 4,
+    }
+}
+
+fn write_uint(out: &mut Vec<u8>, value: usize, size: u8) {
+    out.extend_from_slice(&(value as u32).to_le_bytes()[..size as usize]);
+}
+
+fn read_uint(bytes: &[u8], pos: usize, size: usize) -> Result<usize> {
+    let slice = bytes
+        .get(pos..pos + size)
+        .ok_or_else(|| Error::Invalid("variant buffer too short".to_string()))?;
+    let mut buf = [0u8; 4];
+    buf[..size].copy_from_slice(slice);
+    Ok(u32::from_le_bytes(buf) as usize)
+}
+
+fn read_bytes<const N: usize>(bytes: &[u8], pos: usize) -> Result<[u8; N]> {
+    bytes
+        .get(pos..pos + N)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| Error::Invalid("variant buffer too short".to_string()))
+}
+
+fn read_slice(bytes: &[u8], pos: usize, len: usize) -> Result<&[u8]> {
+    bytes
+        .get(pos..pos + len)
+        .ok_or_else(|| Error::Invalid("variant buffer too short".to_string()))
+}
+
+fn encode_metadata(names: &[&str]) -> Vec<u8> {
+    let total: usize = names.iter().map(|n| n.len()).sum();
+    let offset_size = int_size(total.max(names.len()));
+    let sorted = 1u8 << 4;
+    let mut out = vec![(offset_size - 1) << 6 | sorted | VERSION];
+    write_uint(&mut out, names.len(), offset_size);
+    let mut offset = 0;
+    write_uint(&mut out, offset, offset_size);
+    for name in names {
+        offset += name.len();
+        write_uint(&mut out, offset, offset_size);
+    }
+    for name in names {
+        out.extend_from_slice(name.as_bytes());
+    }
+    out
+}
+
+fn decode_metadata(metadata: &[u8]) -> Result<Vec<String>> {
+    let header = *metadata
+        .first()
+        .ok_or_else(|| Error::Invalid("empty variant metadata".to_string()))?;
+    if header & 0x0f != VERSION {
+        return Err(Error::Unsupported(format!(
+            "variant metadata version {}",
+            header & 0x0f
+        )));
+    }
+    let offset_size = ((header >> 6) + 1) as usize;
+    let size = read_uint(metadata, 1, offset_size)?;
+    let offsets_start = 1 + offset_size;
+    let strings_start = offsets_start + (size + 1) * offset_size;
+    (0..size)
+        .map(|i| {
+            let start = read_uint(metadata, offsets_start + i * offset_size, offset_size)?;
+            let end = read_uint(metadata, offsets_start + (i + 1) * offset_size, offset_size)?;
+            let bytes = metadata
+                .get(strings_start + start..strings_start + end)
+                .ok_or_else(|| Error::Invalid("variant metadata too short".to_string()))?;
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| Error::Invalid("variant field name is not UTF-8".to_string()))
+        })
+        .collect()
+}
+
+fn decode_value(value: &[u8], dictionary: &[String]) -> Result<Variant> {
+    let header = *value
+        .first()
+        .ok_or_else(|| Error::Invalid("empty variant value".to_string()))?;
+    let value_header = header >> 2;
+    Ok(match header & 0x03 {
+        BASIC_PRIMITIVE => match value_header {
+            0 => Variant::Null,
+            1 => Variant::Boolean(true),
+            2 => Variant::Boolean(false),
+            3 => Variant::Int8(i8::from_le_bytes(read_bytes(value, 1)?)),
+            4 => Variant::Int16(i16::from_le_bytes(read_bytes(value, 1)?)),
+            5 => Variant::Int32(i32::from_le_bytes(read_bytes(value, 1)?)),
+            6 => Variant::Int64(i64::from_le_bytes(read_bytes(value, 1)?)),
+            7 => Variant::Double(f64::from_le_bytes(read_bytes(value, 1)?)),
+            8 => Variant::Decimal {
+                scale: read_bytes::<1>(value, 1)?[0],
+                unscaled: i32::from_le_bytes(read_bytes(value, 2)?).into(),
+            },
+            9 => Variant::Decimal {
+                scale: read_bytes::<1>(value, 1)?[0],
+                unscaled: i64::from_le_bytes(read_bytes(value, 2)?).into(),
+            },
+            10 => Variant::Decimal {
+                scale: read_bytes::<1>(value, 1)?[0],
+                unscaled: i128::from_le_bytes(read_bytes(value, 2)?),
+            },
+            11 => Variant::Date(i32::from_le_bytes(read_bytes(value, 1)?)),
+            12 => Variant::Timestampz(i64::from_le_bytes(read_bytes(value, 1)?)),
+            13 => Variant::Timestamp(i64::from_le_bytes(read_bytes(value, 1)?)),
+            14 => Variant::Float(f32::from_le_bytes(read_bytes(value, 1)?)),
+            15 => {
+                let len = read_uint(value, 1, 4)?;
+                Variant::Binary(read_slice(value, 5, len)?.to_vec())
+            }
+            16 => {
+                let len = read_uint(value, 1, 4)?;
+                Variant::String(utf8(read_slice(value, 5, len)?)?)
+            }
+            17 => Variant::Time(i64::from_le_bytes(read_bytes(value, 1)?)),
+            18 => Variant::TimestampzNs(i64::from_le_bytes(read_bytes(value, 1)?)),
+            19 => Variant::TimestampNs(i64::from_le_bytes(read_bytes(value, 1)?)),
+            20 => Variant::Uuid(Uuid::from_bytes(read_bytes(value, 1)?)),
+            id => {
+                return Err(Error::Unsupported(format!(
+                    "variant primitive type id {id}"
+                )))
+            }
+        },
+        BASIC_SHORT_STRING => Variant::String(utf8(read_slice(value, 1, value_header as usize)?)?),
+        BASIC_OBJECT => {
+            let offset_size = ((value_header & 0x03) + 1) as usize;
+            let id_size = ((value_header >> 2 & 0x03) + 1) as usize;
+            let count_size = if value_header >> 4 & 0x01 == 1 { 4 } else { 1 };
+            let count = read_uint(value, 1, count_size)?;
+            let ids_start = 1 + count_size;
+            let offsets_start = ids_start + count * id_size;
+            let values_start = offsets_start + (count + 1) * offset_size;
+            let mut fields = BTreeMap::new();
+            for i in 0..count {
+                let id = read_uint(value, ids_start + i * id_size, id_size)?;
+                let name = dictionary.get(id).ok_or_else(|| {
+                    Error::Invalid(format!("variant field id {id} not in metadata"))
+                })?;
+                let offset = read_uint(value, offsets_start + i * offset_size, offset_size)?;
+                let field = value
+                    .get(values_start + offset..)
+                    .ok_or_else(|| Error::Invalid("variant buffer too short".to_string()))?;
+                fields.insert(name.clone(), decode_value(field, dictionary)?);
+            }
+            Variant::Object(fields)
+        }
+        _ => {
+            let offset_size = ((value_header & 0x03) + 1) as usize;
+            let count_size = if value_header >> 2 & 0x01 == 1 { 4 } else { 1 };
+            let count = read_uint(value, 1, count_size)?;
+            let offsets_start = 1 + count_size;
+            let values_start = offsets_start + (count + 1) * offset_size;
+            let elements = (0..count)
+                .map(|i| {
+                    let offset = read_uint(value, offsets_start + i * offset_size, offset_size)?;
+                    let element = value
+                        .get(values_start + offset..)
+                        .ok_or_else(|| Error::Invalid("variant buffer too short".to_string()))?;
+                    decode_value(element, dictionary)
+                })
+                .collect::<Result<_>>()?;
+            Variant::Array(elements)
+        }
+    })
+}
+
+fn utf8(bytes: &[u8]) -> Result<String> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| Error::Invalid("variant string is not UTF-8".to_string()))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The layout of a shredded variant column. Each level of a shredded column is a group with
+/// an optional binary `value` column and, unless the level is [ShreddedType::Unshredded],
+/// a `typed_value` column of the described type.
+pub enum ShreddedType {
+    /// Values are only stored in the binary `value` column.
+    Unshredded,
+    /// Values of the primitive type are stored in `typed_value`.
+    Primitive(PrimitiveType),
+    /// Objects are stored with a group per listed field in `typed_value`, and the
+    /// remaining fields in `value`.
+    Object(Vec<(String, ShreddedType)>),
+    /// Arrays are stored as a list of shredded elements in `typed_value`.
+    Array(Box<ShreddedType>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The contents of the `typed_value` column of a shredded variant.
+pub enum TypedValue {
+    /// A primitive value, matching a [ShreddedType::Primitive] type.
+    Primitive(Variant),
+    /// The shredded fields, in the order of the [ShreddedType::Object] fields.
+    Object(Vec<ShreddedValue>),
+    /// The shredded elements of an array.
+    Array(Vec<ShreddedValue>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+/// A variant split into its `value` and `typed_value` columns. When both are `None`
+/// the value is missing, which is only valid for object fields.
+pub struct ShreddedValue {
+    /// The part of the variant that is not shredded.
+    pub value: Option<Variant>,
+    /// The shredded part of the variant.
+    pub typed_value: Option<TypedValue>,
+}
+
+impl ShreddedType {
+    /// The Parquet column paths of the shredded layout, relative to the variant column group.
+    pub fn column_paths(&self) -> Vec<String> {
+        let mut paths = vec!["metadata".to_string()];
+        self.collect_paths("", &mut paths);
+        paths
+    }
+
+    fn collect_paths(&self, prefix: &str, paths: &mut Vec<String>) {
+        paths.push(format!("{prefix}value"));
+        match self {
+            ShreddedType::Unshredded => (),
+            ShreddedType::Primitive(_) => paths.push(format!("{prefix}typed_value")),
+            ShreddedType::Object(fields) => {
+                for (name, field_type) in fields {
+                    field_type.collect_paths(&format!("{prefix}typed_value.{name}."), paths);
+                }
+            }
+            ShreddedType::Array(element) => {
+                element.collect_paths(&format!("{prefix}typed_value.list.element."), paths)
+            }
+        }
+    }
+
+    /// Split `variant` into the columns of this layout.
+    pub fn shred(&self, variant: &Variant) -> ShreddedValue {
+        match (self, variant) {
+            (ShreddedType::Primitive(data_type), _) if matches_primitive(variant, data_type) => {
+                ShreddedValue {
+                    value: None,
+                    typed_value: Some(TypedValue::Primitive(variant.clone())),
+                }
+            }
+            (ShreddedType::Object(shredded_fields), Variant::Object(fields)) => {
+                let typed = shredded_fields
+                    .iter()
+                    .map(|(name, field_type)| match fields.get(name) {
+                        Some(field) => field_type.shred(field),
+                        None => ShreddedValue::default(),
+                    })
+                    .collect();
+                let residual: BTreeMap<String, Variant> = fields
+                    .iter()
+                    .filter(|(name, _)| !shredded_fields.iter().any(|(n, _)| n == *name))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                ShreddedValue {
+                    value: (!residual.is_empty()).then_some(Variant::Object(residual)),
+                    typed_value: Some(TypedValue::Object(typed)),
+                }
+            }
+            (ShreddedType::Array(element_type), Variant::Array(elements)) => ShreddedValue {
+                value: None,
+                typed_value: Some(TypedValue::Array(
+                    elements.iter().map(|e| element_type.shred(e)).collect(),
+                )),
+            },
+            _ => ShreddedValue {
+                value: Some(variant.clone()),
+                typed_value: None,
+            },
+        }
+    }
+
+    /// Rebuild a variant from the columns of this layout. Returns `None` for a missing value.
+    pub fn unshred(&self, shredded: &ShreddedValue) -> Result<Option<Variant>> {
+        match (&shredded.value, &shredded.typed_value) {
+            (None, None) => Ok(None),
+            (Some(value), None) => Ok(Some(value.clone())),
+            (None, Some(TypedValue::Primitive(value))) => Ok(Some(value.clone())),
+            (value, Some(TypedValue::Object(typed))) => {
+                let shredded_fields = match self {
+                    ShreddedType::Object(fields) if fields.len() == typed.len() => fields,
+                    _ => {
+                        return Err(Error::Invalid(
+                            "shredded object does not match the shredding schema".to_string(),
+                        ))
+                    }
+                };
+                let mut fields = match value {
+                    Some(Variant::Object(residual)) => residual.clone(),
+                    Some(_) => {
+                        return Err(Error::Invalid(
+                            "partially shredded value must be an object".to_string(),
+                        ))
+                    }
+                    None => BTreeMap::new(),
+                };
+                for ((name, field_type), field) in shredded_fields.iter().zip(typed) {
+                    if let Some(v) = field_type.unshred(field)? {
+                        if fields.insert(name.clone(), v).is_some() {
+                            return Err(Error::Invalid(format!(
+                                "field {name} is both shredded and in the residual object"
+                            )));
+                        }
+                    }
+                }
+                Ok(Some(Variant::Object(fields)))
+            }
+            (None, Some(TypedValue::Array(elements))) => {
+                let element_type = match self {
+                    ShreddedType::Array(element_type) => element_type,
+                    _ => {
+                        return Err(Error::Invalid(
+                            "shredded array does not match the shredding schema".to_string(),
+                        ))
+                    }
+                };
+                let elements = elements
+                    .iter()
+                    .map(|e| {
+                        element_type.unshred(e)?.ok_or_else(|| {
+                            Error::Invalid("array elements cannot be missing".to_string())
+                        })
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Some(Variant::Array(elements)))
+            }
+            (Some(_), Some(_)) => Err(Error::Invalid(
+                "only objects can have both value and typed_value".to_string(),
+            )),
+        }
+    }
+}
+
+/// Returns true if a variant primitive can be stored in a `typed_value` column of `data_type`.
+fn matches_primitive(variant: &Variant, data_type: &PrimitiveType) -> bool {
+    match (variant, data_type) {
+        (Variant::Boolean(_), PrimitiveType::Boolean) => true,
+        (Variant::Int8(_), PrimitiveType::Int)
+        | (Variant::Int16(_), PrimitiveType::Int)
+        | (Variant::Int32(_), PrimitiveType::Int) => true,
+        (Variant::Int64(_), PrimitiveType::Long) => true,
+        (Variant::Float(_), PrimitiveType::Float) => true,
+        (Variant::Double(_), PrimitiveType::Double) => true,
+        (
+            Variant::Decimal { unscaled, scale },
+            PrimitiveType::Decimal {
+                precision,
+                scale: s,
+            },
+        ) => scale == s && decimal::fits_precision(*unscaled, *precision),
+        (Variant::Date(_), PrimitiveType::Date) => true,
+        (Variant::Time(_), PrimitiveType::Time) => true,
+        (Variant::Timestamp(_), PrimitiveType::Timestamp) => true,
+        (Variant::Timestampz(_), PrimitiveType::Timestampz) => true,
+        (Variant::TimestampNs(_), PrimitiveType::TimestampNs) => true,
+        (Variant::TimestampzNs(_), PrimitiveType::TimestampzNs) => true,
+        (Variant::String(_), PrimitiveType::String) => true,
+        (Variant::Binary(_), PrimitiveType::Binary) => true,
+        (Variant::Uuid(_), PrimitiveType::Uuid) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event() -> Variant {
+        Variant::from_json(&json!({
+            "id": 1234567,
+            "type": "click",
+            "items": [{"name": "a", "qty": 2}, {"name": "b"}],
+            "ok": true,
+            "score": 1.5,
+            "note": null
+        }))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let long_string = "x".repeat(100);
+        let variants = vec![
+            Variant::Null,
+            Variant::Boolean(false),
+            Variant::Int8(-3),
+            Variant::Int16(300),
+            Variant::Int32(-70_000),
+            Variant::Int64(1 << 40),
+            Variant::Float(1.5),
+            Variant::Double(-2.25),
+            Variant::Decimal {
+                unscaled: 1420,
+                scale: 2,
+            },
+            Variant::Decimal {
+                unscaled: 1 << 40,
+                scale: 4,
+            },
+            Variant::Decimal {
+                unscaled: 1 << 100,
+                scale: 10,
+            },
+            Variant::Date(17_486),
+            Variant::Time(81_068_123_456),
+            Variant::Timestamp(1_510_871_468_123_456),
+            Variant::Timestampz(1_510_871_468_123_456),
+            Variant::TimestampNs(1_510_871_468_123_456_789),
+            Variant::TimestampzNs(1_510_871_468_123_456_789),
+            Variant::Binary(vec![0, 1, 255]),
+            Variant::String("short".to_string()),
+            Variant::String(long_string),
+            Variant::Uuid(Uuid::new_v4()),
+            Variant::Array((0..300).map(Variant::Int32).collect()),
+            event(),
+        ];
+        for variant in variants {
+            let (metadata, value) = variant.encode();
+            assert_eq!(variant, Variant::decode(&metadata, &value).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_encoding() {
+        let (metadata, value) = Variant::from_json(&json!({"b": 1, "a": "x"})).encode();
+        // version 1, sorted, one byte offsets; two names "a" and "b".
+        assert_eq!(vec![0x11, 2, 0, 1, 2, b'a', b'b'], metadata);
+        assert_eq!(
+            vec![
+                0x02, // object, one byte ids and offsets
+                2,    // two fields
+                0, 1, // field ids of "a" and "b"
+                0, 2, 4, // offsets
+                0x05, b'x', // short string "x"
+                0x0c, 1, // int8 1
+            ],
+            value
+        );
+        assert_eq!(
+            vec![0x11, 0, 0],
+            Variant::Int32(1).encode().0,
+            "empty dictionary"
+        );
+        assert!(Variant::decode(&[0x12, 0, 0], &[0]).is_err());
+    }
+
+    #[test]
+    fn test_decimal_widths() {
+        // (unscaled, type id, value length)
+        let cases = [
+            (999_999_999, 8, 6),
+            (-999_999_999, 8, 6),
+            (1_000_000_000, 9, 10),
+            (2_000_000_000, 9, 10),
+            (999_999_999_999_999_999, 9, 10),
+            (-999_999_999_999_999_999, 9, 10),
+            (1_000_000_000_000_000_000, 10, 18),
+        ];
+        for (unscaled, type_id, len) in cases {
+            let variant = Variant::Decimal { unscaled, scale: 2 };
+            let (metadata, value) = variant.encode();
+            assert_eq!(type_id << 2, value[0], "{unscaled}");
+            assert_eq!(len, value.len(), "{unscaled}");
+            assert_eq!(variant, Variant::decode(&metadata, &value).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_path_extraction() {
+        let event = event();
+        let path = VariantPath::parse("$.items[0].name").unwrap();
+        assert_eq!(
+            vec![
+                PathElement::Field("items".to_string()),
+                PathElement::Index(0),
+                PathElement::Field("name".to_string())
+            ],
+            path.0
+        );
+        assert_eq!(
+            Some(&Variant::String("a".to_string())),
+            event.get_path(&path)
+        );
+        assert_eq!(
+            Some(&Variant::Int32(1234567)),
+            event.get_path(&VariantPath::parse("id").unwrap())
+        );
+        assert_eq!(
+            None,
+            event.get_path(&VariantPath::parse("$.items[1].qty").unwrap())
+        );
+        assert_eq!(None, event.get_path(&VariantPath::parse("$.id.x").unwrap()));
+        assert!(VariantPath::parse("$.a[x]").is_err());
+        assert!(VariantPath::parse("$a").is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let json = json!({"a": [1, 2.5, "x", null, true], "b": {"c": -1}});
+        assert_eq!(json, Variant::from_json(&json).to_json().unwrap());
+    }
+
+    #[test]
+    fn test_shredding() {
+        let layout = ShreddedType::Object(vec![
+            (
+                "id".to_string(),
+                ShreddedType::Primitive(PrimitiveType::Long),
+            ),
+            (
+                "type".to_string(),
+                ShreddedType::Primitive(PrimitiveType::String),
+            ),
+            (
+                "items".to_string(),
+                ShreddedType::Array(Box::new(ShreddedType::Object(vec![(
+                    "name".to_string(),
+                    ShreddedType::Primitive(PrimitiveType::String),
+                )]))),
+            ),
+            ("missing".to_string(), ShreddedType::Unshredded),
+        ]);
+        let event = event();
+        let shredded = layout.shred(&event);
+
+        let typed = match &shredded.typed_value {
+            Some(TypedValue::Object(fields)) => fields,
+            other => panic!("unexpected {other:?}"),
+        };
+        // id is an int32, which does not match the long column so stays in value.
+        assert_eq!(Some(Variant::Int32(1234567)), typed[0].value);
+        assert_eq!(
+            Some(TypedValue::Primitive(Variant::String("click".to_string()))),
+            typed[1].typed_value
+        );
+        assert_eq!(ShreddedValue::default(), typed[3]);
+        match &shredded.value {
+            Some(Variant::Object(residual)) => assert_eq!(
+                vec!["note", "ok", "score"],
+                residual.keys().collect::<Vec<_>>()
+            ),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        assert_eq!(Some(event), layout.unshred(&shredded).unwrap());
+        assert_eq!(
+            Some(Variant::Int8(1)),
+            layout.unshred(&layout.shred(&Variant::Int8(1))).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_column_paths() {
+        let layout = ShreddedType::Object(vec![
+            ("a".to_string(), ShreddedType::Primitive(PrimitiveType::Int)),
+            (
+                "b".to_string(),
+                ShreddedType::Array(Box::new(ShreddedType::Unshredded)),
+            ),
+        ]);
+        assert_eq!(
+            vec![
+                "metadata",
+                "value",
+                "typed_value.a.value",
+                "typed_value.a.typed_value",
+                "typed_value.b.value",
+                "typed_value.b.typed_value.list.element.value",
+            ],
+            layout.column_paths()
+        );
+    }
+}